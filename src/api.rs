@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::config::ApiConfig;
+use crate::risk::{PerformanceSummary, Position, RiskManager};
+use crate::trading::{SignalLog, SignalRecord};
+
+/// What the API handlers read and toggle; obtained from the engine with
+/// `TradingEngine::risk_manager` and `TradingEngine::trading_enabled`.
+#[derive(Clone)]
+pub struct ApiState {
+    risk_manager: Arc<RiskManager>,
+    trading_enabled: Arc<AtomicBool>,
+    signal_log: Option<Arc<SignalLog>>,
+}
+
+impl ApiState {
+    pub fn new(risk_manager: Arc<RiskManager>, trading_enabled: Arc<AtomicBool>) -> Self {
+        Self {
+            risk_manager,
+            trading_enabled,
+            signal_log: None,
+        }
+    }
+
+    /// Serves `TradingEngine::signal_log` at `/signals`.
+    pub fn with_signal_log(mut self, signal_log: Arc<SignalLog>) -> Self {
+        self.signal_log = Some(signal_log);
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusResponse {
+    pub paused: bool,
+    pub can_trade: bool,
+    pub open_positions: u32,
+    pub daily_loss_pct: Decimal,
+    pub performance: PerformanceSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PauseResponse {
+    pub paused: bool,
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/positions", get(positions))
+        .route("/signals", get(signals))
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .with_state(state)
+}
+
+/// Binds the API and serves it in the background. Returns the bound
+/// address, which differs from the configured one when port 0 is used.
+pub async fn serve(config: &ApiConfig, state: ApiState) -> Result<SocketAddr> {
+    let addr: SocketAddr = format!("{}:{}", config.bind_address, config.port)
+        .parse()
+        .context("Invalid API bind address")?;
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind API to {}", addr))?;
+    let addr = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router(state)).await {
+            error!("API server stopped: {}", e);
+        }
+    });
+
+    info!("HTTP API available at http://{}", addr);
+    Ok(addr)
+}
+
+async fn status(State(state): State<ApiState>) -> Json<StatusResponse> {
+    Json(StatusResponse {
+        paused: !state.trading_enabled.load(Ordering::SeqCst),
+        can_trade: state.risk_manager.can_trade(),
+        open_positions: state.risk_manager.open_positions_count(),
+        daily_loss_pct: state.risk_manager.current_daily_loss(),
+        performance: state.risk_manager.performance(),
+    })
+}
+
+async fn positions(State(state): State<ApiState>) -> Json<Vec<Position>> {
+    Json(state.risk_manager.positions())
+}
+
+async fn signals(State(state): State<ApiState>) -> Json<Vec<SignalRecord>> {
+    Json(
+        state
+            .signal_log
+            .map(|log| log.records())
+            .unwrap_or_default(),
+    )
+}
+
+async fn pause(State(state): State<ApiState>) -> Json<PauseResponse> {
+    state.trading_enabled.store(false, Ordering::SeqCst);
+    info!("Trading paused via API");
+    Json(PauseResponse { paused: true })
+}
+
+async fn resume(State(state): State<ApiState>) -> Json<PauseResponse> {
+    state.trading_enabled.store(true, Ordering::SeqCst);
+    info!("Trading resumed via API");
+    Json(PauseResponse { paused: false })
+}
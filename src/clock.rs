@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Source of wall-clock time. Components read the time through one of these
+/// instead of the system clock, so tests and replays can control it with a
+/// `MockClock`.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Milliseconds since the Unix epoch, as Binance timestamps are.
+    fn now_ms(&self) -> u64 {
+        self.now().timestamp_millis() as u64
+    }
+}
+
+/// The real time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that stands still until moved with `advance` or `set`.
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let by = chrono::Duration::from_std(by).expect("duration out of range");
+        *self.now.lock().unwrap() += by;
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_only_moves_when_told() {
+        let start: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now_ms(), 1704067200000);
+
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(clock.now_ms(), 1704067201500);
+
+        clock.set("2024-06-01T12:00:00Z".parse().unwrap());
+        assert_eq!(clock.now().to_rfc3339(), "2024-06-01T12:00:00+00:00");
+    }
+}
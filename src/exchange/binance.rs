@@ -1,28 +1,116 @@
-use anyhow::{Context, Result};
+use anyhow::Context;
 use hmac::{Hmac, Mac};
-use reqwest::Client;
+use reqwest::{Client, Method, RequestBuilder};
+use rust_decimal::Decimal;
 use sha2::Sha256;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::{debug, instrument};
-
-use crate::config::ExchangeCredentials;
-
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, instrument, warn};
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::{ClientConfig, Environment, ExchangeCredentials};
+
+use super::cache::ResponseCache;
+use super::check::CredentialCheck;
+use super::error::{parse_response, BinanceError};
 use super::models::*;
+use super::rate_limit::{RateLimiter, DEFAULT_WEIGHT_PER_MINUTE};
+use super::retry::{retry_with_backoff, RetryPolicy};
 
 type HmacSha256 = Hmac<Sha256>;
 
+// Request weights as documented for each Binance spot endpoint
+const WEIGHT_ACCOUNT: u32 = 20;
+const WEIGHT_TICKER_PRICE: u32 = 2;
+const WEIGHT_ALL_TICKER_PRICES: u32 = 4;
+const WEIGHT_TICKER_24H: u32 = 2;
+const WEIGHT_ALL_TICKERS_24H: u32 = 80;
+const WEIGHT_KLINES: u32 = 2;
+/// Most klines Binance returns for one request.
+pub const MAX_KLINES_PER_REQUEST: u32 = 1000;
+const WEIGHT_ORDER: u32 = 1;
+const WEIGHT_TEST_ORDER: u32 = 1;
+const WEIGHT_OCO_ORDER: u32 = 1;
+const WEIGHT_OPEN_ORDERS_SYMBOL: u32 = 6;
+const WEIGHT_OPEN_ORDERS_ALL: u32 = 80;
+const WEIGHT_CANCEL_ORDER: u32 = 1;
+const WEIGHT_CANCEL_ALL_ORDERS: u32 = 1;
+const WEIGHT_QUERY_ORDER: u32 = 4;
+const WEIGHT_ALL_ORDERS: u32 = 20;
+const WEIGHT_MY_TRADES: u32 = 20;
+const WEIGHT_EXCHANGE_INFO: u32 = 20;
+const WEIGHT_USER_DATA_STREAM: u32 = 2;
+const WEIGHT_SERVER_TIME: u32 = 1;
+
+/// Levels fetched for order book pricing; the smallest depth weight tier.
+pub const ORDER_BOOK_PRICING_DEPTH: u32 = 5;
+
+/// Candle intervals Binance serves klines for.
+pub const KLINE_INTERVALS: &[&str] = &[
+    "1s", "1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "6h", "8h", "12h", "1d", "3d", "1w",
+    "1M",
+];
+
+/// Tracing target of the audit log, so it can be filtered or sent to a file
+/// of its own.
+pub const AUDIT_TARGET: &str = "cryptobot::audit";
+
+/// Interval of the klines in `MarketData` unless a strategy asks for another.
+pub const DEFAULT_KLINE_INTERVAL: &str = "1h";
+
 pub struct BinanceClient {
     client: Client,
     credentials: ExchangeCredentials,
     base_url: String,
+    rate_limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+    exchange_info_cache: Option<ResponseCache>,
+    order_book_pricing: bool,
+    audit_log: bool,
+    clock: Arc<dyn Clock>,
+    /// Added to the clock when stamping signed requests; set by `sync_time`
+    time_offset_ms: AtomicI64,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerTime {
+    server_time: u64,
 }
 
 impl BinanceClient {
-    pub fn new(credentials: ExchangeCredentials) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .context("Failed to create HTTP client")?;
+    /// Builds the HTTP client from `config`. Fails on credentials that could
+    /// never sign a request, so a config typo stops startup instead of every
+    /// signed call.
+    pub fn new(credentials: ExchangeCredentials, config: &ClientConfig) -> anyhow::Result<Self> {
+        let mut builder = Client::builder().timeout(Duration::from_secs(config.timeout_secs));
+        if let Some(proxy_url) = &config.proxy_url {
+            // The URL may carry proxy credentials, so it stays out of the error
+            let proxy = reqwest::Proxy::all(proxy_url).context("Invalid proxy URL")?;
+            builder = builder.proxy(proxy);
+        }
+        let client = builder.build().context("Failed to create HTTP client")?;
+
+        Self::with_client(credentials, client)
+    }
+
+    /// Uses `client` as is, for TLS or connection settings `ClientConfig`
+    /// does not cover.
+    pub fn with_client(credentials: ExchangeCredentials, client: Client) -> anyhow::Result<Self> {
+        for (name, value) in [
+            ("API key", &credentials.api_key),
+            ("secret key", &credentials.secret_key),
+        ] {
+            anyhow::ensure!(!value.is_empty(), "Binance {} is empty", name);
+            anyhow::ensure!(
+                value.trim() == value,
+                "Binance {} has leading or trailing whitespace",
+                name
+            );
+        }
 
         let base_url = credentials.environment.base_url().to_string();
 
@@ -30,25 +118,84 @@ impl BinanceClient {
             client,
             credentials,
             base_url,
+            rate_limiter: RateLimiter::new(DEFAULT_WEIGHT_PER_MINUTE),
+            retry_policy: RetryPolicy::default(),
+            exchange_info_cache: None,
+            order_book_pricing: false,
+            audit_log: false,
+            clock: Arc::new(SystemClock),
+            time_offset_ms: AtomicI64::new(0),
         })
     }
 
-    fn timestamp() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64
+    /// Caps outgoing requests at `weight_per_minute` of Binance request weight.
+    pub fn with_rate_limit(mut self, weight_per_minute: u32) -> Self {
+        self.rate_limiter = RateLimiter::new(weight_per_minute);
+        self
+    }
+
+    /// Controls how transient failures (timeouts, 5xx, rate limits) are retried.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Keeps the exchange info response in `dir`, one file per environment,
+    /// and serves it from there until it is `ttl` old.
+    pub fn with_exchange_info_cache(mut self, dir: impl AsRef<Path>, ttl: Duration) -> Self {
+        let file = match self.credentials.environment {
+            Environment::Testnet => "exchange_info_testnet.json",
+            Environment::Mainnet => "exchange_info_mainnet.json",
+        };
+        self.exchange_info_cache = Some(ResponseCache::new(dir.as_ref().join(file), ttl));
+        self
+    }
+
+    /// Also fetches the top of the order book in `get_market_data`, filling
+    /// in `MarketData::mid_price`.
+    pub fn with_order_book_pricing(mut self, enabled: bool) -> Self {
+        self.order_book_pricing = enabled;
+        self
+    }
+
+    pub fn order_book_pricing(&self) -> bool {
+        self.order_book_pricing
+    }
+
+    /// Logs every request and its response under `AUDIT_TARGET`, with the
+    /// signature and API key masked.
+    pub fn with_audit_log(mut self, enabled: bool) -> Self {
+        self.audit_log = enabled;
+        self
+    }
+
+    /// Points the client at a different REST endpoint, e.g. a local mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Time source for request timestamps and `MarketData::timestamp`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn timestamp(&self) -> u64 {
+        self.clock
+            .now_ms()
+            .saturating_add_signed(self.time_offset_ms.load(Ordering::Relaxed))
     }
 
     fn sign(&self, query: &str) -> String {
-        let mut mac =
-            HmacSha256::new_from_slice(self.credentials.secret_key.as_bytes()).unwrap();
+        let mut mac = HmacSha256::new_from_slice(self.credentials.secret_key.as_bytes())
+            .expect("HMAC accepts keys of any length");
         mac.update(query.as_bytes());
         hex::encode(mac.finalize().into_bytes())
     }
 
     fn build_signed_query(&self, params: &[(&str, String)]) -> String {
-        let timestamp = Self::timestamp().to_string();
+        let timestamp = self.timestamp().to_string();
         let mut all_params: Vec<(&str, String)> = params.to_vec();
         all_params.push(("timestamp", timestamp));
 
@@ -62,75 +209,306 @@ impl BinanceClient {
         format!("{}&signature={}", query, signature)
     }
 
-    #[instrument(skip(self))]
-    pub async fn get_account_info(&self) -> Result<AccountInfo> {
-        let query = self.build_signed_query(&[]);
-        let url = format!("{}/api/v3/account?{}", self.base_url, query);
+    /// Builds an authenticated request. Called once per attempt so every
+    /// retry carries a fresh timestamp and signature.
+    fn signed_request(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> RequestBuilder {
+        let query = self.build_signed_query(params);
+
+        self.client
+            .request(method, format!("{}{}?{}", self.base_url, path, query))
+            .header("X-MBX-APIKEY", &self.credentials.api_key)
+    }
 
-        debug!("Fetching account info");
+    /// Builds a request that carries the API key but no signature, as the
+    /// user data stream endpoints expect.
+    fn keyed_request(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.base_url, path))
+            .query(params)
+            .header("X-MBX-APIKEY", &self.credentials.api_key)
+    }
+
+    /// Sends the request produced by `build`, retrying transient failures per
+    /// `policy`, and returns the body of the first successful response. A
+    /// request rejected for its timestamp (-1021) is re-sent straight away
+    /// after re-syncing with Binance's clock.
+    async fn send<F>(
+        &self,
+        context: &'static str,
+        weight: u32,
+        policy: RetryPolicy,
+        build: F,
+    ) -> Result<String, BinanceError>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        retry_with_backoff(policy, context, || async {
+            match self.send_once(context, weight, &build).await {
+                Err(BinanceError::TimestampOutOfSync { msg }) => {
+                    warn!("{} request out of sync ({}), re-syncing time", context, msg);
+                    self.sync_time().await?;
+                    self.send_once(context, weight, &build).await
+                }
+                result => result,
+            }
+        })
+        .await
+    }
+
+    /// Sends the request produced by `build` once and returns the body of a
+    /// successful response. It is built only once the rate limiter lets it
+    /// through, so a long wait can't outlast a signed request's recvWindow.
+    async fn send_once<F>(
+        &self,
+        context: &'static str,
+        weight: u32,
+        build: F,
+    ) -> Result<String, BinanceError>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        self.rate_limiter.acquire(weight).await;
+
+        let request = build()
+            .build()
+            .map_err(|e| BinanceError::request(context, e))?;
+        if self.audit_log {
+            info!(target: AUDIT_TARGET, "{}", audit_request_line(&request));
+        }
 
         let response = self
             .client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self.credentials.api_key)
-            .send()
+            .execute(request)
             .await
-            .context("Failed to send account info request")?;
+            .map_err(|e| BinanceError::request(context, e))?;
 
         let status = response.status();
-        let text = response.text().await?;
+        let text = response
+            .text()
+            .await
+            .map_err(|e| BinanceError::request(context, e))?;
+        if self.audit_log {
+            info!(target: AUDIT_TARGET, "{} response {}: {}", context, status, text);
+        }
 
         if !status.is_success() {
-            anyhow::bail!("Account info request failed: {} - {}", status, text);
+            return Err(BinanceError::from_response(status.as_u16(), &text));
         }
 
-        serde_json::from_str(&text).context("Failed to parse account info response")
+        Ok(text)
+    }
+
+    /// Measures Binance's clock against ours and stamps signed requests
+    /// with the difference from then on. Returns the offset in
+    /// milliseconds.
+    pub async fn sync_time(&self) -> Result<i64, BinanceError> {
+        let url = format!("{}/api/v3/time", self.base_url);
+        // Timed from after any rate limit wait, when the request goes out
+        let sent_at = AtomicU64::new(0);
+        let text = self
+            .send_once("server time", WEIGHT_SERVER_TIME, || {
+                sent_at.store(self.clock.now_ms(), Ordering::Relaxed);
+                self.client.get(&url)
+            })
+            .await?;
+        let received_at = self.clock.now_ms();
+
+        let time: ServerTime = parse_response("server time", &text)?;
+        let offset = clock_skew_ms(sent_at.into_inner(), time.server_time, received_at);
+        debug!("Server time is {} ms off the local clock", offset);
+        self.time_offset_ms.store(offset, Ordering::Relaxed);
+        Ok(offset)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_account_info(&self) -> Result<AccountInfo, BinanceError> {
+        debug!("Fetching account info");
+
+        let text = self
+            .send("account info", WEIGHT_ACCOUNT, self.retry_policy, || {
+                self.signed_request(Method::GET, "/api/v3/account", &[])
+            })
+            .await?;
+
+        parse_response("account info", &text)
+    }
+
+    /// Binance's current time, in milliseconds since the Unix epoch.
+    #[instrument(skip(self))]
+    pub async fn get_server_time(&self) -> Result<u64, BinanceError> {
+        let url = format!("{}/api/v3/time", self.base_url);
+        let text = self
+            .send("server time", WEIGHT_SERVER_TIME, self.retry_policy, || {
+                self.client.get(&url)
+            })
+            .await?;
+
+        let time: ServerTime = parse_response("server time", &text)?;
+        Ok(time.server_time)
     }
 
+    /// Verifies the credentials against the account endpoint and measures
+    /// how far the local clock is from Binance's. Bad keys come back as
+    /// errors; what the key may do is left to `CredentialCheck::problems`.
     #[instrument(skip(self))]
-    pub async fn get_ticker_price(&self, symbol: &str) -> Result<TickerPrice> {
+    pub async fn check_credentials(&self) -> Result<CredentialCheck, BinanceError> {
+        let account = self.get_account_info().await?;
+
+        let sent_at = self.clock.now_ms();
+        let server_time = self.get_server_time().await?;
+        let received_at = self.clock.now_ms();
+
+        Ok(CredentialCheck::new(
+            &account,
+            clock_skew_ms(sent_at, server_time, received_at),
+        ))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_ticker_price(&self, symbol: &str) -> Result<TickerPrice, BinanceError> {
         let url = format!("{}/api/v3/ticker/price?symbol={}", self.base_url, symbol);
 
         debug!("Fetching ticker price for {}", symbol);
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send ticker price request")?;
+        let text = self
+            .send(
+                "ticker price",
+                WEIGHT_TICKER_PRICE,
+                self.retry_policy,
+                || self.client.get(&url),
+            )
+            .await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        parse_response("ticker price", &text)
+    }
 
-        if !status.is_success() {
-            anyhow::bail!("Ticker price request failed: {} - {}", status, text);
+    /// Total value of the account's free and locked balances in `quote`;
+    /// see `value_balances`.
+    #[instrument(skip(self))]
+    pub async fn portfolio_value(&self, quote: &str) -> Result<Decimal, BinanceError> {
+        let account = self.get_account_info().await?;
+        let prices: HashMap<String, Decimal> = self
+            .get_all_ticker_prices()
+            .await?
+            .into_iter()
+            .map(|ticker| {
+                let price = ticker.price_decimal();
+                (ticker.symbol, price)
+            })
+            .collect();
+
+        let (total, unpriced) = value_balances(&account.balances, quote, &prices);
+        for balance in unpriced {
+            warn!(
+                "No {}{} pair, leaving {} {} out of portfolio value",
+                balance.asset,
+                quote,
+                balance.total(),
+                balance.asset
+            );
         }
+        Ok(total)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_order_book(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<OrderBook, BinanceError> {
+        let url = format!(
+            "{}/api/v3/depth?symbol={}&limit={}",
+            self.base_url, symbol, limit
+        );
+
+        debug!("Fetching order book for {}", symbol);
 
-        serde_json::from_str(&text).context("Failed to parse ticker price response")
+        let text = self
+            .send("order book", depth_weight(limit), self.retry_policy, || {
+                self.client.get(&url)
+            })
+            .await?;
+
+        parse_response("order book", &text)
     }
 
     #[instrument(skip(self))]
-    pub async fn get_all_ticker_prices(&self) -> Result<Vec<TickerPrice>> {
+    pub async fn get_all_ticker_prices(&self) -> Result<Vec<TickerPrice>, BinanceError> {
         let url = format!("{}/api/v3/ticker/price", self.base_url);
 
         debug!("Fetching all ticker prices");
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send all ticker prices request")?;
+        let text = self
+            .send(
+                "all ticker prices",
+                WEIGHT_ALL_TICKER_PRICES,
+                self.retry_policy,
+                || self.client.get(&url),
+            )
+            .await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        parse_response("all ticker prices", &text)
+    }
 
-        if !status.is_success() {
-            anyhow::bail!("All ticker prices request failed: {} - {}", status, text);
-        }
+    #[instrument(skip(self))]
+    pub async fn get_24h_ticker(&self, symbol: &str) -> Result<Ticker24h, BinanceError> {
+        let url = format!("{}/api/v3/ticker/24hr?symbol={}", self.base_url, symbol);
 
-        serde_json::from_str(&text).context("Failed to parse all ticker prices response")
+        debug!("Fetching 24h ticker for {}", symbol);
+
+        let text = self
+            .send("24h ticker", WEIGHT_TICKER_24H, self.retry_policy, || {
+                self.client.get(&url)
+            })
+            .await?;
+
+        parse_response("24h ticker", &text)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_all_24h_tickers(&self) -> Result<Vec<Ticker24h>, BinanceError> {
+        let url = format!("{}/api/v3/ticker/24hr", self.base_url);
+
+        debug!("Fetching 24h tickers for all symbols");
+
+        let text = self
+            .send(
+                "all 24h tickers",
+                WEIGHT_ALL_TICKERS_24H,
+                self.retry_policy,
+                || self.client.get(&url),
+            )
+            .await?;
+
+        parse_response("all 24h tickers", &text)
+    }
+
+    /// The `top_n` trading symbols quoted in `quote_asset`, most traded
+    /// (by 24h quote volume) first.
+    pub async fn discover_symbols(
+        &self,
+        quote_asset: &str,
+        top_n: usize,
+    ) -> Result<Vec<String>, BinanceError> {
+        let exchange_info = self.get_exchange_info().await?;
+        let tickers = self.get_all_24h_tickers().await?;
+
+        Ok(select_top_symbols(
+            &exchange_info,
+            &tickers,
+            quote_asset,
+            top_n,
+        ))
     }
 
     #[instrument(skip(self))]
@@ -139,7 +517,7 @@ impl BinanceClient {
         symbol: &str,
         interval: &str,
         limit: u32,
-    ) -> Result<Vec<Kline>> {
+    ) -> Result<Vec<Kline>, BinanceError> {
         let url = format!(
             "{}/api/v3/klines?symbol={}&interval={}&limit={}",
             self.base_url, symbol, interval, limit
@@ -147,53 +525,82 @@ impl BinanceClient {
 
         debug!("Fetching {} klines for {} at {} interval", limit, symbol, interval);
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send klines request")?;
+        let text = self
+            .send("klines", WEIGHT_KLINES, self.retry_policy, || {
+                self.client.get(&url)
+            })
+            .await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        parse_klines(&text)
+    }
 
-        if !status.is_success() {
-            anyhow::bail!("Klines request failed: {} - {}", status, text);
+    /// Every kline of `interval` opening between `start_time` and `end_time`
+    /// (milliseconds, inclusive), oldest first. Pages through the history
+    /// `MAX_KLINES_PER_REQUEST` candles at a time, each page counting against
+    /// the rate limit, so it reaches past the single-request cap.
+    pub async fn get_klines_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<Kline>, BinanceError> {
+        let mut klines: Vec<Kline> = Vec::new();
+        let mut page_start = start_time;
+
+        while page_start <= end_time {
+            let url = format!(
+                "{}/api/v3/klines?symbol={}&interval={}&startTime={}&endTime={}&limit={}",
+                self.base_url, symbol, interval, page_start, end_time, MAX_KLINES_PER_REQUEST
+            );
+            debug!(
+                "Fetching {} klines for {} from {} to {}",
+                interval, symbol, page_start, end_time
+            );
+
+            let text = self
+                .send("klines", WEIGHT_KLINES, self.retry_policy, || {
+                    self.client.get(&url)
+                })
+                .await?;
+            let page = parse_klines(&text)?;
+
+            let Some(last_open) = page.last().map(|k| k.open_time) else {
+                break;
+            };
+            let full_page = page.len() >= MAX_KLINES_PER_REQUEST as usize;
+
+            // Pages may overlap by a candle; keep each open time once
+            for kline in page {
+                if klines
+                    .last()
+                    .is_none_or(|prev| kline.open_time > prev.open_time)
+                {
+                    klines.push(kline);
+                }
+            }
+
+            if !full_page {
+                break;
+            }
+            page_start = last_open + 1;
         }
 
-        // Binance returns klines as arrays of arrays
-        let raw: Vec<Vec<serde_json::Value>> =
-            serde_json::from_str(&text).context("Failed to parse klines response")?;
-
-        let klines = raw
-            .into_iter()
-            .map(|k| Kline {
-                open_time: k[0].as_u64().unwrap_or(0),
-                open: k[1].as_str().unwrap_or("0").to_string(),
-                high: k[2].as_str().unwrap_or("0").to_string(),
-                low: k[3].as_str().unwrap_or("0").to_string(),
-                close: k[4].as_str().unwrap_or("0").to_string(),
-                volume: k[5].as_str().unwrap_or("0").to_string(),
-                close_time: k[6].as_u64().unwrap_or(0),
-                quote_asset_volume: k[7].as_str().unwrap_or("0").to_string(),
-                number_of_trades: k[8].as_u64().unwrap_or(0),
-                taker_buy_base_asset_volume: k[9].as_str().unwrap_or("0").to_string(),
-                taker_buy_quote_asset_volume: k[10].as_str().unwrap_or("0").to_string(),
-            })
-            .collect();
-
         Ok(klines)
     }
 
-    #[instrument(skip(self))]
-    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse> {
+    fn order_params(order: &OrderRequest) -> Vec<(&'static str, String)> {
         let mut params = vec![
             ("symbol", order.symbol.clone()),
             ("side", order.side.to_string()),
             ("type", order.order_type.to_string()),
-            ("quantity", order.quantity.to_string()),
         ];
 
+        match &order.quote_order_qty {
+            Some(quote_qty) => params.push(("quoteOrderQty", quote_qty.to_string())),
+            None => params.push(("quantity", order.quantity.to_string())),
+        }
+
         if let Some(price) = &order.price {
             params.push(("price", price.to_string()));
         }
@@ -206,133 +613,1671 @@ impl BinanceClient {
             params.push(("stopPrice", stop_price.to_string()));
         }
 
-        let query = self.build_signed_query(&params);
-        let url = format!("{}/api/v3/order?{}", self.base_url, query);
+        if let Some(client_order_id) = &order.client_order_id {
+            params.push(("newClientOrderId", client_order_id.clone()));
+        }
+
+        params
+    }
+
+    #[instrument(skip(self))]
+    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, BinanceError> {
+        let params = Self::order_params(order);
 
         debug!("Placing order: {:?}", order);
 
-        let response = self
-            .client
-            .post(&url)
-            .header("X-MBX-APIKEY", &self.credentials.api_key)
-            .send()
-            .await
-            .context("Failed to send order request")?;
+        let send = || {
+            self.send("order", WEIGHT_ORDER, self.retry_policy.at_most(0), || {
+                self.signed_request(Method::POST, "/api/v3/order", &params)
+            })
+        };
 
-        let status = response.status();
-        let text = response.text().await?;
+        // A failed request may still have placed, and even filled, the
+        // order. Without a client id to look it up by, it is resent once
+        let Some(client_order_id) = &order.client_order_id else {
+            let text = retry_with_backoff(self.retry_policy.at_most(1), "order", send).await?;
+            return parse_response("order", &text);
+        };
 
-        if !status.is_success() {
-            anyhow::bail!("Order request failed: {} - {}", status, text);
-        }
+        // With one, each resend first checks the order didn't arrive. An
+        // order under the same id from before the first attempt is an
+        // earlier one, long filled, not this one
+        let first_attempt_at = self.timestamp();
+        let attempted = AtomicBool::new(false);
+        retry_with_backoff(self.retry_policy, "order", || async {
+            if attempted.swap(true, Ordering::SeqCst) {
+                match self
+                    .get_order_by_client_id(&order.symbol, client_order_id)
+                    .await
+                {
+                    Ok(placed) if placed.time >= first_attempt_at => {
+                        info!(
+                            "Order {} was placed before the failure, not resending",
+                            client_order_id
+                        );
+                        return Ok(placed.into());
+                    }
+                    Ok(_) => debug!(
+                        "Order {} found is from before this request, resending",
+                        client_order_id
+                    ),
+                    Err(BinanceError::UnknownOrder { .. }) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            parse_response("order", &send().await?)
+        })
+        .await
+    }
+
+    /// Runs `order` through the exchange's validation (filters, balance,
+    /// signature) without sending it to the matching engine.
+    #[instrument(skip(self))]
+    pub async fn place_test_order(&self, order: &OrderRequest) -> Result<(), BinanceError> {
+        let params = Self::order_params(order);
+
+        debug!("Testing order: {:?}", order);
+
+        self.send("test order", WEIGHT_TEST_ORDER, self.retry_policy, || {
+            self.signed_request(Method::POST, "/api/v3/order/test", &params)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn place_oco_order(
+        &self,
+        order: &OcoOrderRequest,
+    ) -> Result<OcoOrderResponse, BinanceError> {
+        let params = vec![
+            ("symbol", order.symbol.clone()),
+            ("side", order.side.to_string()),
+            ("quantity", order.quantity.to_string()),
+            ("price", order.price.to_string()),
+            ("stopPrice", order.stop_price.to_string()),
+            ("stopLimitPrice", order.stop_limit_price.to_string()),
+            (
+                "stopLimitTimeInForce",
+                order.stop_limit_time_in_force.to_string(),
+            ),
+        ];
 
-        serde_json::from_str(&text).context("Failed to parse order response")
+        debug!("Placing OCO order: {:?}", order);
+
+        let text = self
+            .send(
+                "OCO order",
+                WEIGHT_OCO_ORDER,
+                self.retry_policy.at_most(1),
+                || self.signed_request(Method::POST, "/api/v3/order/oco", &params),
+            )
+            .await?;
+
+        parse_response("OCO order", &text)
     }
 
     #[instrument(skip(self))]
-    pub async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<OpenOrder>> {
+    pub async fn get_open_orders(
+        &self,
+        symbol: Option<&str>,
+    ) -> Result<Vec<OpenOrder>, BinanceError> {
         let params: Vec<(&str, String)> = if let Some(s) = symbol {
             vec![("symbol", s.to_string())]
         } else {
             vec![]
         };
 
-        let query = self.build_signed_query(&params);
-        let url = format!("{}/api/v3/openOrders?{}", self.base_url, query);
-
+        let weight = if symbol.is_some() {
+            WEIGHT_OPEN_ORDERS_SYMBOL
+        } else {
+            WEIGHT_OPEN_ORDERS_ALL
+        };
         debug!("Fetching open orders for {:?}", symbol);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self.credentials.api_key)
-            .send()
-            .await
-            .context("Failed to send open orders request")?;
+        let text = self
+            .send("open orders", weight, self.retry_policy, || {
+                self.signed_request(Method::GET, "/api/v3/openOrders", &params)
+            })
+            .await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        parse_response("open orders", &text)
+    }
 
-        if !status.is_success() {
-            anyhow::bail!("Open orders request failed: {} - {}", status, text);
-        }
+    #[instrument(skip(self))]
+    pub async fn get_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<QueryOrderResponse, BinanceError> {
+        let params = vec![
+            ("symbol", symbol.to_string()),
+            ("orderId", order_id.to_string()),
+        ];
+
+        debug!("Querying order {} for {}", order_id, symbol);
+
+        let text = self
+            .send("query order", WEIGHT_QUERY_ORDER, self.retry_policy, || {
+                self.signed_request(Method::GET, "/api/v3/order", &params)
+            })
+            .await?;
 
-        serde_json::from_str(&text).context("Failed to parse open orders response")
+        parse_response("query order", &text)
     }
 
+    /// Like `get_order`, by the client order id the order was placed with.
     #[instrument(skip(self))]
-    pub async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<CancelOrderResponse> {
+    pub async fn get_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<QueryOrderResponse, BinanceError> {
         let params = vec![
             ("symbol", symbol.to_string()),
-            ("orderId", order_id.to_string()),
+            ("origClientOrderId", client_order_id.to_string()),
         ];
 
-        let query = self.build_signed_query(&params);
-        let url = format!("{}/api/v3/order?{}", self.base_url, query);
+        debug!("Querying order {} for {}", client_order_id, symbol);
+
+        let text = self
+            .send("query order", WEIGHT_QUERY_ORDER, self.retry_policy, || {
+                self.signed_request(Method::GET, "/api/v3/order", &params)
+            })
+            .await?;
+
+        parse_response("query order", &text)
+    }
+
+    /// Orders on `symbol` in any status, filled and cancelled included,
+    /// oldest first, up to `limit` (at most 1000) of the most recent ones.
+    #[instrument(skip(self))]
+    pub async fn get_all_orders(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<Vec<OpenOrder>, BinanceError> {
+        let params = vec![
+            ("symbol", symbol.to_string()),
+            ("limit", limit.min(1000).to_string()),
+        ];
+
+        debug!("Fetching order history for {}", symbol);
+
+        let text = self
+            .send("all orders", WEIGHT_ALL_ORDERS, self.retry_policy, || {
+                self.signed_request(Method::GET, "/api/v3/allOrders", &params)
+            })
+            .await?;
+
+        parse_response("all orders", &text)
+    }
+
+    /// Fills on `symbol` for this account, oldest first, up to `limit`
+    /// (at most 1000) of the most recent ones.
+    #[instrument(skip(self))]
+    pub async fn get_my_trades(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<Vec<MyTrade>, BinanceError> {
+        let params = vec![
+            ("symbol", symbol.to_string()),
+            ("limit", limit.min(1000).to_string()),
+        ];
+
+        debug!("Fetching trades for {}", symbol);
+
+        let text = self
+            .send("my trades", WEIGHT_MY_TRADES, self.retry_policy, || {
+                self.signed_request(Method::GET, "/api/v3/myTrades", &params)
+            })
+            .await?;
+
+        parse_response("my trades", &text)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn cancel_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<CancelOrderResponse, BinanceError> {
+        let params = vec![
+            ("symbol", symbol.to_string()),
+            ("orderId", order_id.to_string()),
+        ];
 
         debug!("Cancelling order {} for {}", order_id, symbol);
 
-        let response = self
-            .client
-            .delete(&url)
-            .header("X-MBX-APIKEY", &self.credentials.api_key)
-            .send()
-            .await
-            .context("Failed to send cancel order request")?;
+        let text = self
+            .send(
+                "cancel order",
+                WEIGHT_CANCEL_ORDER,
+                self.retry_policy,
+                || self.signed_request(Method::DELETE, "/api/v3/order", &params),
+            )
+            .await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        parse_response("cancel order", &text)
+    }
 
-        if !status.is_success() {
-            anyhow::bail!("Cancel order request failed: {} - {}", status, text);
+    /// Cancels every open order on `symbol` in one request. Legs of OCO
+    /// lists are returned as individual cancellations; having nothing to
+    /// cancel is not an error.
+    #[instrument(skip(self))]
+    pub async fn cancel_all_open_orders(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<CancelOrderResponse>, BinanceError> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Cancelled {
+            #[serde(rename_all = "camelCase")]
+            List {
+                order_reports: Vec<CancelOrderResponse>,
+            },
+            Order(CancelOrderResponse),
+        }
+
+        let params = vec![("symbol", symbol.to_string())];
+
+        debug!("Cancelling all open orders for {}", symbol);
+
+        let result = self
+            .send(
+                "cancel all orders",
+                WEIGHT_CANCEL_ALL_ORDERS,
+                self.retry_policy,
+                || self.signed_request(Method::DELETE, "/api/v3/openOrders", &params),
+            )
+            .await;
+
+        let text = match result {
+            Ok(text) => text,
+            // Binance answers -2011 rather than an empty list when nothing is open
+            Err(BinanceError::UnknownOrder { .. }) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let cancelled: Vec<Cancelled> = parse_response("cancel all orders", &text)?;
+
+        Ok(cancelled
+            .into_iter()
+            .flat_map(|entry| match entry {
+                Cancelled::List { order_reports } => order_reports,
+                Cancelled::Order(order) => vec![order],
+            })
+            .collect())
+    }
+
+    /// Opens a user data stream and returns its listen key. The key expires
+    /// after 60 minutes unless kept alive.
+    #[instrument(skip(self))]
+    pub async fn create_listen_key(&self) -> Result<String, BinanceError> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ListenKey {
+            listen_key: String,
+        }
+
+        debug!("Creating user data stream listen key");
+
+        let text = self
+            .send(
+                "create listen key",
+                WEIGHT_USER_DATA_STREAM,
+                self.retry_policy,
+                || self.keyed_request(Method::POST, "/api/v3/userDataStream", &[]),
+            )
+            .await?;
+
+        let key: ListenKey = parse_response("create listen key", &text)?;
+        Ok(key.listen_key)
+    }
+
+    /// Extends a listen key's validity by another 60 minutes.
+    #[instrument(skip(self, listen_key))]
+    pub async fn keepalive_listen_key(&self, listen_key: &str) -> Result<(), BinanceError> {
+        let params = vec![("listenKey", listen_key.to_string())];
+
+        debug!("Keeping user data stream alive");
+
+        self.send(
+            "keepalive listen key",
+            WEIGHT_USER_DATA_STREAM,
+            self.retry_policy,
+            || self.keyed_request(Method::PUT, "/api/v3/userDataStream", &params),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, listen_key))]
+    pub async fn close_listen_key(&self, listen_key: &str) -> Result<(), BinanceError> {
+        let params = vec![("listenKey", listen_key.to_string())];
+
+        debug!("Closing user data stream");
+
+        self.send(
+            "close listen key",
+            WEIGHT_USER_DATA_STREAM,
+            self.retry_policy,
+            || self.keyed_request(Method::DELETE, "/api/v3/userDataStream", &params),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Exchange info, from the on-disk cache when one is configured and fresh.
+    #[instrument(skip(self))]
+    pub async fn get_exchange_info(&self) -> Result<ExchangeInfo, BinanceError> {
+        if let Some(cache) = &self.exchange_info_cache {
+            if let Some(text) = cache.load().await {
+                match serde_json::from_str(&text) {
+                    Ok(info) => {
+                        debug!("Using cached exchange info");
+                        return Ok(info);
+                    }
+                    Err(e) => warn!("Ignoring unreadable exchange info cache: {}", e),
+                }
+            }
         }
 
-        serde_json::from_str(&text).context("Failed to parse cancel order response")
+        self.refresh_exchange_info().await
     }
 
+    /// Fetches exchange info from the API, bypassing and then rewriting the
+    /// on-disk cache.
     #[instrument(skip(self))]
-    pub async fn get_exchange_info(&self) -> Result<ExchangeInfo> {
+    pub async fn refresh_exchange_info(&self) -> Result<ExchangeInfo, BinanceError> {
         let url = format!("{}/api/v3/exchangeInfo", self.base_url);
 
         debug!("Fetching exchange info");
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send exchange info request")?;
+        let text = self
+            .send(
+                "exchange info",
+                WEIGHT_EXCHANGE_INFO,
+                self.retry_policy,
+                || self.client.get(&url),
+            )
+            .await?;
 
-        let status = response.status();
-        let text = response.text().await?;
+        let info = parse_response("exchange info", &text)?;
 
-        if !status.is_success() {
-            anyhow::bail!("Exchange info request failed: {} - {}", status, text);
+        if let Some(cache) = &self.exchange_info_cache {
+            cache.store(&text).await;
         }
 
-        serde_json::from_str(&text).context("Failed to parse exchange info response")
+        Ok(info)
     }
 
-    pub async fn get_market_data(&self, symbol: &str, kline_limit: u32) -> Result<MarketData> {
+    pub async fn get_market_data(
+        &self,
+        symbol: &str,
+        interval: &str,
+        kline_limit: u32,
+    ) -> Result<MarketData, BinanceError> {
         let ticker = self.get_ticker_price(symbol).await?;
-        let klines = self.get_klines(symbol, "1h", kline_limit).await?;
+        let klines = self.get_klines(symbol, interval, kline_limit).await?;
+        let mid_price = if self.order_book_pricing {
+            self.get_order_book(symbol, ORDER_BOOK_PRICING_DEPTH)
+                .await?
+                .mid_price()
+        } else {
+            None
+        };
 
         Ok(MarketData {
             symbol: symbol.to_string(),
             current_price: ticker.price_decimal(),
             klines,
-            timestamp: Self::timestamp(),
+            timestamp: self.timestamp(),
+            mid_price,
         })
     }
 }
 
+/// Parses a klines response, which Binance sends as arrays of arrays.
+fn parse_klines(text: &str) -> Result<Vec<Kline>, BinanceError> {
+    let raw: Vec<Vec<serde_json::Value>> = parse_response("klines", text)?;
+
+    Ok(raw
+        .into_iter()
+        .map(|k| Kline {
+            open_time: k[0].as_u64().unwrap_or(0),
+            open: k[1].as_str().unwrap_or("0").to_string(),
+            high: k[2].as_str().unwrap_or("0").to_string(),
+            low: k[3].as_str().unwrap_or("0").to_string(),
+            close: k[4].as_str().unwrap_or("0").to_string(),
+            volume: k[5].as_str().unwrap_or("0").to_string(),
+            close_time: k[6].as_u64().unwrap_or(0),
+            quote_asset_volume: k[7].as_str().unwrap_or("0").to_string(),
+            number_of_trades: k[8].as_u64().unwrap_or(0),
+            taker_buy_base_asset_volume: k[9].as_str().unwrap_or("0").to_string(),
+            taker_buy_quote_asset_volume: k[10].as_str().unwrap_or("0").to_string(),
+        })
+        .collect())
+}
+
+/// Request weight of `GET /api/v3/depth`, which scales with `limit`.
+fn depth_weight(limit: u32) -> u32 {
+    match limit {
+        0..=100 => 5,
+        101..=500 => 25,
+        501..=1000 => 50,
+        _ => 250,
+    }
+}
+
+/// Total value of `balances` in `quote`, converting each asset through its
+/// `<ASSET><QUOTE>` price in `prices`. Assets without such a pair are left
+/// out and returned alongside, for the caller to report as loudly as it
+/// needs to.
+pub fn value_balances<'a>(
+    balances: &'a [Balance],
+    quote: &str,
+    prices: &HashMap<String, Decimal>,
+) -> (Decimal, Vec<&'a Balance>) {
+    let mut total = Decimal::ZERO;
+    let mut unpriced = Vec::new();
+    for balance in balances {
+        let amount = balance.total();
+        if amount.is_zero() {
+            continue;
+        }
+
+        if balance.asset == quote {
+            total += amount;
+            continue;
+        }
+
+        match prices.get(&format!("{}{}", balance.asset, quote)) {
+            Some(price) => total += amount * price,
+            None => unpriced.push(balance),
+        }
+    }
+
+    (total, unpriced)
+}
+
+/// Symbols in `exchange_info` that are trading and quoted in `quote_asset`,
+/// ranked by 24h quote volume and cut to `top_n`. Symbols without a ticker
+/// are left out.
+fn select_top_symbols(
+    exchange_info: &ExchangeInfo,
+    tickers: &[Ticker24h],
+    quote_asset: &str,
+    top_n: usize,
+) -> Vec<String> {
+    let volumes: HashMap<&str, Decimal> = tickers
+        .iter()
+        .map(|t| (t.symbol.as_str(), t.quote_volume_decimal()))
+        .collect();
+
+    let mut candidates: Vec<(&str, Decimal)> = exchange_info
+        .symbols
+        .iter()
+        .filter(|s| s.is_trading() && s.is_spot_trading_allowed && s.quote_asset == quote_asset)
+        .filter_map(|s| Some((s.symbol.as_str(), *volumes.get(s.symbol.as_str())?)))
+        .collect();
+    candidates.sort_by_key(|&(_, volume)| std::cmp::Reverse(volume));
+
+    candidates
+        .into_iter()
+        .take(top_n)
+        .map(|(symbol, _)| symbol.to_string())
+        .collect()
+}
+
+/// One line describing `request` for the audit log: method, URL and
+/// headers, with the `signature` parameter and the API key masked.
+fn audit_request_line(request: &reqwest::Request) -> String {
+    let mut url = request.url().clone();
+    let masked: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == "signature" {
+                "***".to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    if !masked.is_empty() {
+        url.query_pairs_mut().clear().extend_pairs(masked);
+    }
+
+    let headers: Vec<String> = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == "X-MBX-APIKEY" {
+                "***"
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect();
+
+    format!("{} {} [{}]", request.method(), url, headers.join(", "))
+}
+
+/// Binance's time minus ours, for a server time fetched between local
+/// times `sent_at` and `received_at`.
+fn clock_skew_ms(sent_at: u64, server_time: u64, received_at: u64) -> i64 {
+    // Binance stamped the response somewhere between send and receive
+    let local_time = sent_at + received_at.saturating_sub(sent_at) / 2;
+    server_time as i64 - local_time as i64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
+    use crate::config::Environment;
+    use mockito::Matcher;
+    use rust_decimal_macros::dec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn create_client(server_url: &str) -> BinanceClient {
+        let credentials = ExchangeCredentials {
+            api_key: "test_key".to_string(),
+            secret_key: "test_secret".to_string(),
+            environment: Environment::Testnet,
+        };
+
+        BinanceClient::new(credentials, &ClientConfig::default())
+            .unwrap()
+            .with_base_url(server_url)
+            .with_retry_policy(RetryPolicy::new(3, Duration::from_millis(1)))
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_configured_proxy() {
+        let mut proxy = mockito::Server::new_async().await;
+        let mock = proxy
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .match_header("host", "api.binance.invalid")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "30000.00"}"#)
+            .create_async()
+            .await;
+
+        let config = ClientConfig {
+            timeout_secs: 5,
+            proxy_url: Some(proxy.url()),
+        };
+        let client = BinanceClient::new(
+            ExchangeCredentials {
+                api_key: "test_key".to_string(),
+                secret_key: "test_secret".to_string(),
+                environment: Environment::Testnet,
+            },
+            &config,
+        )
+        .unwrap()
+        .with_base_url("http://api.binance.invalid");
+
+        let ticker = client.get_ticker_price("BTCUSDT").await.unwrap();
+
+        assert_eq!(ticker.price, "30000.00");
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_invalid_proxy_url_is_rejected() {
+        let config = ClientConfig {
+            timeout_secs: 30,
+            proxy_url: Some("not a proxy".to_string()),
+        };
+        let credentials = ExchangeCredentials {
+            api_key: "test_key".to_string(),
+            secret_key: "test_secret".to_string(),
+            environment: Environment::Testnet,
+        };
+
+        let err = BinanceClient::new(credentials, &config).err().unwrap();
+
+        assert_eq!(err.to_string(), "Invalid proxy URL");
+    }
+
+    #[test]
+    fn test_rejects_unusable_credentials() {
+        let credentials = |api_key: &str, secret_key: &str| ExchangeCredentials {
+            api_key: api_key.to_string(),
+            secret_key: secret_key.to_string(),
+            environment: Environment::Testnet,
+        };
+
+        let err = BinanceClient::new(credentials("key", ""), &ClientConfig::default())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Binance secret key is empty");
+
+        let err = BinanceClient::new(credentials("", "secret"), &ClientConfig::default())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Binance API key is empty");
+
+        let err = BinanceClient::new(credentials("key", "secret\n"), &ClientConfig::default())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Binance secret key has leading or trailing whitespace"
+        );
+    }
 
     #[test]
     fn test_timestamp() {
-        let ts = BinanceClient::timestamp();
+        let ts = create_client("http://localhost").timestamp();
         assert!(ts > 1700000000000); // Should be after Nov 2023
     }
+
+    #[test]
+    fn test_signed_query_timestamp_comes_from_clock() {
+        let clock = Arc::new(MockClock::new("2023-11-14T22:13:20Z".parse().unwrap()));
+        let client = create_client("http://localhost").with_clock(clock.clone());
+
+        let query = client.build_signed_query(&[("symbol", "BTCUSDT".to_string())]);
+        assert!(query.starts_with("symbol=BTCUSDT&timestamp=1700000000000&signature="));
+
+        clock.advance(Duration::from_secs(1));
+        let query = client.build_signed_query(&[("symbol", "BTCUSDT".to_string())]);
+        assert!(query.starts_with("symbol=BTCUSDT&timestamp=1700000001000&signature="));
+    }
+
+    #[test]
+    fn test_audit_line_masks_signature_and_api_key() {
+        let client = create_client("http://localhost");
+        let request = client
+            .signed_request(
+                Method::GET,
+                "/api/v3/order",
+                &[("symbol", "BTCUSDT".to_string())],
+            )
+            .build()
+            .unwrap();
+        let signature = request
+            .url()
+            .query_pairs()
+            .find(|(name, _)| name == "signature")
+            .unwrap()
+            .1
+            .into_owned();
+
+        let line = audit_request_line(&request);
+
+        assert!(line.starts_with("GET http://localhost/api/v3/order?symbol=BTCUSDT&timestamp="));
+        assert!(line.contains("signature=***"), "{}", line);
+        assert!(!line.contains(&signature), "{}", line);
+        assert!(line.contains("x-mbx-apikey: ***"), "{}", line);
+        assert!(!line.contains("test_key"), "{}", line);
+    }
+
+    #[test]
+    fn test_quote_market_order_sends_quote_order_qty() {
+        let order = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(50.5));
+        let params = BinanceClient::order_params(&order);
+
+        assert!(params.contains(&("quoteOrderQty", "50.5".to_string())));
+        assert!(!params.iter().any(|(k, _)| *k == "quantity"));
+
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.01));
+        let params = BinanceClient::order_params(&order);
+
+        assert!(params.contains(&("quantity", "0.01".to_string())));
+        assert!(!params.iter().any(|(k, _)| *k == "quoteOrderQty"));
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_then_succeeds() {
+        let mut server = mockito::Server::new_async().await;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+
+        // Fails twice with 503, then serves the ticker
+        let mock = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_status_code_from_request(move |_| {
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    503
+                } else {
+                    200
+                }
+            })
+            .with_body(r#"{"symbol":"BTCUSDT","price":"50000.00"}"#)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let ticker = create_client(&server.url())
+            .get_ticker_price("BTCUSDT")
+            .await
+            .unwrap();
+
+        assert_eq!(ticker.price_decimal(), dec!(50000.00));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_place_order_retried_at_most_once() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", Matcher::Regex("^/api/v3/order".to_string()))
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.001));
+        let result = create_client(&server.url()).place_order(&order).await;
+
+        assert!(matches!(
+            result,
+            Err(BinanceError::Http { status: 503, .. })
+        ));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_place_order_with_client_id_resends_it_while_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v3/order")
+            .match_query(Matcher::UrlEncoded(
+                "newClientOrderId".to_string(),
+                "intent-1".to_string(),
+            ))
+            .with_status(503)
+            .expect(4)
+            .create_async()
+            .await;
+        let lookup = server
+            .mock("GET", "/api/v3/order")
+            .match_query(Matcher::UrlEncoded(
+                "origClientOrderId".to_string(),
+                "intent-1".to_string(),
+            ))
+            .with_status(400)
+            .with_body(r#"{"code": -2013, "msg": "Order does not exist."}"#)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.001))
+            .with_client_order_id("intent-1");
+        let result = create_client(&server.url()).place_order(&order).await;
+
+        assert!(matches!(
+            result,
+            Err(BinanceError::Http { status: 503, .. })
+        ));
+        mock.assert_async().await;
+        lookup.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_place_order_with_client_id_not_resent_once_it_filled() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v3/order")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let lookup = server
+            .mock("GET", "/api/v3/order")
+            .match_query(Matcher::UrlEncoded(
+                "origClientOrderId".to_string(),
+                "intent-1".to_string(),
+            ))
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 42, "clientOrderId": "intent-1",
+                    "price": "0.00", "origQty": "0.001", "executedQty": "0.001",
+                    "cummulativeQuoteQty": "50", "status": "FILLED",
+                    "timeInForce": "GTC", "type": "MARKET", "side": "BUY",
+                    "time": 1700000000001, "updateTime": 1700000000002}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let clock = Arc::new(MockClock::new("2023-11-14T22:13:20Z".parse().unwrap()));
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.001))
+            .with_client_order_id("intent-1");
+        let response = create_client(&server.url())
+            .with_clock(clock)
+            .place_order(&order)
+            .await
+            .unwrap();
+
+        assert_eq!(response.order_id, 42);
+        assert_eq!(response.status, "FILLED");
+        assert_eq!(response.executed_qty, "0.001");
+        mock.assert_async().await;
+        lookup.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_place_order_with_client_id_resent_past_an_earlier_order_under_it() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v3/order")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .expect(4)
+            .create_async()
+            .await;
+        // Filled before this request was first sent
+        let lookup = server
+            .mock("GET", "/api/v3/order")
+            .match_query(Matcher::UrlEncoded(
+                "origClientOrderId".to_string(),
+                "intent-1".to_string(),
+            ))
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 42, "clientOrderId": "intent-1",
+                    "price": "0.00", "origQty": "0.001", "executedQty": "0.001",
+                    "cummulativeQuoteQty": "50", "status": "FILLED",
+                    "timeInForce": "GTC", "type": "MARKET", "side": "BUY",
+                    "time": 1699999990000, "updateTime": 1699999990001}"#,
+            )
+            .expect(3)
+            .create_async()
+            .await;
+
+        let clock = Arc::new(MockClock::new("2023-11-14T22:13:20Z".parse().unwrap()));
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.001))
+            .with_client_order_id("intent-1");
+        let result = create_client(&server.url())
+            .with_clock(clock)
+            .place_order(&order)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BinanceError::Http { status: 503, .. })
+        ));
+        mock.assert_async().await;
+        lookup.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_place_test_order_accepted() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "POST",
+                Matcher::Regex("^/api/v3/order/test\\?symbol=BTCUSDT&".to_string()),
+            )
+            .with_body("{}")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.001));
+        create_client(&server.url())
+            .place_test_order(&order)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_place_test_order_filter_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", Matcher::Regex("^/api/v3/order/test".to_string()))
+            .with_status(400)
+            .with_body(r#"{"code":-1013,"msg":"Filter failure: LOT_SIZE"}"#)
+            .create_async()
+            .await;
+
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.0000001));
+        let result = create_client(&server.url()).place_test_order(&order).await;
+
+        match result {
+            Err(BinanceError::Other { code, msg }) => {
+                assert_eq!(code, -1013);
+                assert!(msg.contains("LOT_SIZE"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_order() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                Matcher::Regex("^/api/v3/order\\?symbol=BTCUSDT&orderId=42&".to_string()),
+            )
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 42, "clientOrderId": "c42",
+                    "price": "0.00", "origQty": "0.5", "executedQty": "0.2",
+                    "cummulativeQuoteQty": "6000.4", "status": "PARTIALLY_FILLED",
+                    "timeInForce": "GTC", "type": "MARKET", "side": "BUY",
+                    "time": 1, "updateTime": 2}"#,
+            )
+            .create_async()
+            .await;
+
+        let order = create_client(&server.url())
+            .get_order("BTCUSDT", 42)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(order.status, "PARTIALLY_FILLED");
+        assert_eq!(order.executed_qty, "0.2");
+        assert_eq!(order.cummulative_quote_qty, "6000.4");
+    }
+
+    #[tokio::test]
+    async fn test_get_all_orders_and_net_position() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                Matcher::Regex("^/api/v3/allOrders\\?symbol=BTCUSDT&limit=1000&".to_string()),
+            )
+            .with_body(
+                r#"[
+                    {"symbol": "BTCUSDT", "orderId": 10, "clientOrderId": "a",
+                     "price": "0.00", "origQty": "0.2", "executedQty": "0.2",
+                     "cummulativeQuoteQty": "6000", "status": "FILLED",
+                     "timeInForce": "GTC", "type": "MARKET", "side": "BUY",
+                     "time": 1000, "updateTime": 1000},
+                    {"symbol": "BTCUSDT", "orderId": 11, "clientOrderId": "b",
+                     "price": "29000", "origQty": "0.1", "executedQty": "0",
+                     "cummulativeQuoteQty": "0", "status": "CANCELED",
+                     "timeInForce": "GTC", "type": "LIMIT", "side": "BUY",
+                     "time": 2000, "updateTime": 2500},
+                    {"symbol": "BTCUSDT", "orderId": 12, "clientOrderId": "c",
+                     "price": "33000", "origQty": "0.2", "executedQty": "0.1",
+                     "cummulativeQuoteQty": "3300", "status": "PARTIALLY_FILLED",
+                     "timeInForce": "GTC", "type": "LIMIT", "side": "BUY",
+                     "time": 3000, "updateTime": 3500},
+                    {"symbol": "BTCUSDT", "orderId": 13, "clientOrderId": "d",
+                     "price": "0.00", "origQty": "0.15", "executedQty": "0.15",
+                     "cummulativeQuoteQty": "5100", "status": "FILLED",
+                     "timeInForce": "GTC", "type": "MARKET", "side": "SELL",
+                     "time": 4000, "updateTime": 4000}
+                ]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let orders = create_client(&server.url())
+            .get_all_orders("BTCUSDT", 5000)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        let statuses: Vec<&str> = orders.iter().map(|o| o.status.as_str()).collect();
+        assert_eq!(
+            statuses,
+            ["FILLED", "CANCELED", "PARTIALLY_FILLED", "FILLED"]
+        );
+        assert_eq!(orders[2].executed_qty_decimal(), dec!(0.1));
+
+        // 0.3 BTC bought for 9300, half of it sold: the cancelled order adds
+        // nothing and the sale keeps the average entry
+        let held = CostBasis::from_orders(&orders);
+        assert_eq!(held.quantity, dec!(0.15));
+        assert_eq!(held.cost, dec!(4650));
+        assert_eq!(held.average_price(), Some(dec!(31000)));
+    }
+
+    #[tokio::test]
+    async fn test_get_my_trades_and_cost_basis() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                Matcher::Regex("^/api/v3/myTrades\\?symbol=BTCUSDT&limit=500&".to_string()),
+            )
+            .with_body(
+                r#"[
+                    {"symbol": "BTCUSDT", "id": 1, "orderId": 10, "orderListId": -1,
+                     "price": "30000", "qty": "0.1", "quoteQty": "3000",
+                     "commission": "0.0001", "commissionAsset": "BTC", "time": 1000,
+                     "isBuyer": true, "isMaker": false, "isBestMatch": true},
+                    {"symbol": "BTCUSDT", "id": 2, "orderId": 11, "orderListId": -1,
+                     "price": "32000", "qty": "0.1", "quoteQty": "3200",
+                     "commission": "3.2", "commissionAsset": "USDT", "time": 2000,
+                     "isBuyer": true, "isMaker": true, "isBestMatch": true},
+                    {"symbol": "BTCUSDT", "id": 3, "orderId": 12, "orderListId": -1,
+                     "price": "35000", "qty": "0.0999", "quoteQty": "3496.5",
+                     "commission": "3.4965", "commissionAsset": "USDT", "time": 3000,
+                     "isBuyer": false, "isMaker": false, "isBestMatch": true}
+                ]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let trades = create_client(&server.url())
+            .get_my_trades("BTCUSDT", 500)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(trades.len(), 3);
+        assert_eq!(trades[0].order_id, 10);
+        assert_eq!(trades[0].commission_asset, "BTC");
+        assert!(trades[1].is_buyer);
+        assert!(!trades[2].is_buyer);
+
+        // Buys: 0.0999 BTC for 3000, then 0.1 BTC for 3203.2
+        let bought = CostBasis::from_trades(&trades[..2], "BTC", "USDT");
+        assert_eq!(bought.quantity, dec!(0.1999));
+        assert_eq!(bought.cost, dec!(6203.2));
+        assert_eq!(bought.average_price().unwrap().round_dp(2), dec!(31031.52));
+
+        // Selling half keeps the average entry price
+        let held = CostBasis::from_trades(&trades, "BTC", "USDT");
+        assert_eq!(held.quantity, dec!(0.1));
+        assert_eq!(held.average_price().unwrap().round_dp(2), dec!(31031.52));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_open_orders() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "DELETE",
+                Matcher::Regex("^/api/v3/openOrders\\?symbol=BTCUSDT&".to_string()),
+            )
+            .with_body(
+                r#"[
+                    {"symbol": "BTCUSDT", "origClientOrderId": "o1", "orderId": 1,
+                     "orderListId": -1, "clientOrderId": "c1", "price": "30000",
+                     "origQty": "0.1", "executedQty": "0", "cummulativeQuoteQty": "0",
+                     "status": "CANCELED", "timeInForce": "GTC", "type": "LIMIT",
+                     "side": "BUY"},
+                    {"symbol": "BTCUSDT", "origClientOrderId": "o2", "orderId": 2,
+                     "orderListId": -1, "clientOrderId": "c2", "price": "31000",
+                     "origQty": "0.1", "executedQty": "0", "cummulativeQuoteQty": "0",
+                     "status": "CANCELED", "timeInForce": "GTC", "type": "LIMIT",
+                     "side": "SELL"},
+                    {"orderListId": 7, "contingencyType": "OCO",
+                     "listStatusType": "ALL_DONE", "listOrderStatus": "ALL_DONE",
+                     "listClientOrderId": "l7", "transactionTime": 0, "symbol": "BTCUSDT",
+                     "orders": [
+                        {"symbol": "BTCUSDT", "orderId": 3, "clientOrderId": "c3"},
+                        {"symbol": "BTCUSDT", "orderId": 4, "clientOrderId": "c4"}
+                     ],
+                     "orderReports": [
+                        {"symbol": "BTCUSDT", "origClientOrderId": "o3", "orderId": 3,
+                         "orderListId": 7, "clientOrderId": "c3", "status": "CANCELED",
+                         "type": "STOP_LOSS_LIMIT", "side": "SELL"},
+                        {"symbol": "BTCUSDT", "origClientOrderId": "o4", "orderId": 4,
+                         "orderListId": 7, "clientOrderId": "c4", "status": "CANCELED",
+                         "type": "LIMIT_MAKER", "side": "SELL"}
+                     ]}
+                ]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let cancelled = create_client(&server.url())
+            .cancel_all_open_orders("BTCUSDT")
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(
+            cancelled.iter().map(|c| c.order_id).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert!(cancelled.iter().all(|c| c.status == "CANCELED"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_open_orders_with_nothing_open() {
+        let mut server = mockito::Server::new_async().await;
+        let _empty = server
+            .mock(
+                "DELETE",
+                Matcher::Regex("^/api/v3/openOrders\\?symbol=BTCUSDT&".to_string()),
+            )
+            .with_body("[]")
+            .create_async()
+            .await;
+        let _unknown = server
+            .mock(
+                "DELETE",
+                Matcher::Regex("^/api/v3/openOrders\\?symbol=ETHUSDT&".to_string()),
+            )
+            .with_status(400)
+            .with_body(r#"{"code":-2011,"msg":"Unknown order sent."}"#)
+            .create_async()
+            .await;
+
+        let client = create_client(&server.url());
+        assert!(client
+            .cancel_all_open_orders("BTCUSDT")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(client
+            .cancel_all_open_orders("ETHUSDT")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_value_converts_balances_to_quote() {
+        let mut server = mockito::Server::new_async().await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(
+                r#"{
+                    "makerCommission": 10, "takerCommission": 10,
+                    "buyerCommission": 0, "sellerCommission": 0,
+                    "canTrade": true, "canWithdraw": false, "canDeposit": false,
+                    "updateTime": 0, "accountType": "SPOT",
+                    "balances": [
+                        {"asset": "USDT", "free": "100.5", "locked": "0"},
+                        {"asset": "BTC", "free": "0.01", "locked": "0.01"},
+                        {"asset": "ETH", "free": "0.5", "locked": "0"},
+                        {"asset": "SOL", "free": "0", "locked": "0"},
+                        {"asset": "OBSCURE", "free": "1000", "locked": "0"}
+                    ]
+                }"#,
+            )
+            .create_async()
+            .await;
+        let prices = server
+            .mock("GET", "/api/v3/ticker/price")
+            .with_body(
+                r#"[
+                    {"symbol": "BTCUSDT", "price": "30000"},
+                    {"symbol": "ETHUSDT", "price": "2000"},
+                    {"symbol": "ETHBTC", "price": "0.066"},
+                    {"symbol": "SOLUSDT", "price": "20"}
+                ]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let value = create_client(&server.url())
+            .portfolio_value("USDT")
+            .await
+            .unwrap();
+
+        prices.assert_async().await;
+        // 100.5 USDT + 0.02 BTC * 30000 + 0.5 ETH * 2000; OBSCURE has no pair
+        assert_eq!(value, dec!(1700.5));
+    }
+
+    /// Hourly klines opening at hours `hours`, closing at 100 + the hour.
+    fn hourly_klines_json(hours: std::ops::Range<u64>) -> String {
+        let klines: Vec<String> = hours
+            .map(|h| {
+                format!(
+                    r#"[{}, "1", "1", "1", "{}", "10", {}, "10", 5, "5", "5", "0"]"#,
+                    h * 3600000,
+                    100 + h,
+                    (h + 1) * 3600000 - 1
+                )
+            })
+            .collect();
+        format!("[{}]", klines.join(","))
+    }
+
+    #[tokio::test]
+    async fn test_get_klines_range_stitches_pages() {
+        let mut server = mockito::Server::new_async().await;
+        let end_time: u64 = 1010 * 3600000;
+        let first = server
+            .mock(
+                "GET",
+                format!(
+                    "/api/v3/klines?symbol=BTCUSDT&interval=1h&startTime=0&endTime={}&limit=1000",
+                    end_time
+                )
+                .as_str(),
+            )
+            .with_body(hourly_klines_json(0..1000))
+            .expect(1)
+            .create_async()
+            .await;
+        // The second page repeats the last candle of the first
+        let second = server
+            .mock(
+                "GET",
+                format!(
+                    "/api/v3/klines?symbol=BTCUSDT&interval=1h&startTime={}&endTime={}&limit=1000",
+                    999 * 3600000u64 + 1,
+                    end_time
+                )
+                .as_str(),
+            )
+            .with_body(hourly_klines_json(999..1005))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let klines = create_client(&server.url())
+            .get_klines_range("BTCUSDT", "1h", 0, end_time)
+            .await
+            .unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(klines.len(), 1005);
+        assert!(klines
+            .iter()
+            .enumerate()
+            .all(|(h, k)| k.open_time == h as u64 * 3600000));
+        assert_eq!(klines[1004].close_decimal(), dec!(1104));
+    }
+
+    const DEPTH_JSON: &str = r#"{
+        "lastUpdateId": 1027024,
+        "bids": [["30000.10", "0.5"], ["29999.90", "1.2"]],
+        "asks": [["30000.50", "0.3"], ["30001.00", "2.0"]]
+    }"#;
+
+    #[tokio::test]
+    async fn test_get_order_book_and_mid_price() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v3/depth?symbol=BTCUSDT&limit=5")
+            .with_body(DEPTH_JSON)
+            .create_async()
+            .await;
+
+        let book = create_client(&server.url())
+            .get_order_book("BTCUSDT", 5)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(book.last_update_id, 1027024);
+        assert_eq!(book.bids.len(), 2);
+        assert_eq!(book.best_bid(), Some(dec!(30000.10)));
+        assert_eq!(book.best_ask(), Some(dec!(30000.50)));
+        assert_eq!(book.mid_price(), Some(dec!(30000.30)));
+
+        let empty: OrderBook =
+            serde_json::from_str(r#"{"lastUpdateId": 1, "bids": [], "asks": [["1", "1"]]}"#)
+                .unwrap();
+        assert_eq!(empty.mid_price(), None);
+    }
+
+    #[tokio::test]
+    async fn test_market_data_mid_price_only_with_order_book_pricing() {
+        let mut server = mockito::Server::new_async().await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "29990.00"}"#)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body("[]")
+            .create_async()
+            .await;
+        let depth = server
+            .mock("GET", Matcher::Regex("^/api/v3/depth".to_string()))
+            .with_body(DEPTH_JSON)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_client(&server.url());
+        let data = client.get_market_data("BTCUSDT", "1h", 10).await.unwrap();
+        assert_eq!(data.mid_price, None);
+        assert_eq!(data.valuation_price(), dec!(29990.00));
+
+        let client = client.with_order_book_pricing(true);
+        let data = client.get_market_data("BTCUSDT", "1h", 10).await.unwrap();
+        depth.assert_async().await;
+        assert_eq!(data.current_price, dec!(29990.00));
+        assert_eq!(data.valuation_price(), dec!(30000.30));
+    }
+
+    #[tokio::test]
+    async fn test_listen_key_lifecycle() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/api/v3/userDataStream")
+            .match_header("X-MBX-APIKEY", "test_key")
+            .with_body(r#"{"listenKey": "pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1"}"#)
+            .create_async()
+            .await;
+        let keepalive = server
+            .mock("PUT", "/api/v3/userDataStream")
+            .match_query(Matcher::UrlEncoded("listenKey".into(), "lk".into()))
+            .with_body("{}")
+            .create_async()
+            .await;
+        let close = server
+            .mock("DELETE", "/api/v3/userDataStream")
+            .match_query(Matcher::UrlEncoded("listenKey".into(), "lk".into()))
+            .with_body("{}")
+            .create_async()
+            .await;
+
+        let client = create_client(&server.url());
+        let key = client.create_listen_key().await.unwrap();
+        assert!(key.starts_with("pqia91ma"));
+
+        client.keepalive_listen_key("lk").await.unwrap();
+        client.close_listen_key("lk").await.unwrap();
+
+        create.assert_async().await;
+        keepalive.assert_async().await;
+        close.assert_async().await;
+    }
+
+    const EXCHANGE_INFO_JSON: &str =
+        r#"{"timezone": "UTC", "serverTime": 1700000000000, "symbols": []}"#;
+
+    fn cache_dir(test: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("cryptobot-{}-{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_exchange_info_served_from_cache_within_ttl() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_body(EXCHANGE_INFO_JSON)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let dir = cache_dir("exchange-info-fresh");
+        let client =
+            create_client(&server.url()).with_exchange_info_cache(&dir, Duration::from_secs(3600));
+
+        client.get_exchange_info().await.unwrap();
+        assert!(dir.join("exchange_info_testnet.json").exists());
+
+        // Served from disk
+        let info = client.get_exchange_info().await.unwrap();
+        assert_eq!(info.server_time, 1700000000000);
+
+        // A forced refresh goes back to the API
+        client.refresh_exchange_info().await.unwrap();
+
+        mock.assert_async().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exchange_info_refetched_past_ttl() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_body(EXCHANGE_INFO_JSON)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let dir = cache_dir("exchange-info-stale");
+        let client =
+            create_client(&server.url()).with_exchange_info_cache(&dir, Duration::from_secs(3600));
+
+        client.get_exchange_info().await.unwrap();
+
+        // Age the cache file past the TTL
+        let file = std::fs::File::options()
+            .write(true)
+            .open(dir.join("exchange_info_testnet.json"))
+            .unwrap();
+        file.set_modified(std::time::SystemTime::now() - Duration::from_secs(7200))
+            .unwrap();
+
+        client.get_exchange_info().await.unwrap();
+
+        mock.assert_async().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_credentials_flags_disabled_trading() {
+        let mut server = mockito::Server::new_async().await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(
+                r#"{
+                    "makerCommission": 10, "takerCommission": 10,
+                    "buyerCommission": 0, "sellerCommission": 0,
+                    "canTrade": false, "canWithdraw": true, "canDeposit": true,
+                    "updateTime": 0, "accountType": "SPOT",
+                    "balances": [], "permissions": ["SPOT"]
+                }"#,
+            )
+            .create_async()
+            .await;
+        // 250ms ahead of the mock clock
+        let _time = server
+            .mock("GET", "/api/v3/time")
+            .with_body(r#"{"serverTime": 1700000000250}"#)
+            .create_async()
+            .await;
+
+        let clock = Arc::new(MockClock::new("2023-11-14T22:13:20Z".parse().unwrap()));
+        let check = create_client(&server.url())
+            .with_clock(clock)
+            .check_credentials()
+            .await
+            .unwrap();
+
+        assert!(!check.can_trade);
+        assert_eq!(check.permissions, ["SPOT"]);
+        assert_eq!(check.clock_skew_ms, 250);
+        let problems = check.problems();
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(
+            problems[0].contains("trading is disabled"),
+            "{:?}",
+            problems
+        );
+        assert_eq!(check.warnings().len(), 1);
+
+        let ready = CredentialCheck {
+            can_trade: true,
+            can_withdraw: false,
+            ..check.clone()
+        };
+        assert!(ready.problems().is_empty());
+        assert!(ready.warnings().is_empty());
+
+        // A drifting clock fails the check on its own
+        let drifting = CredentialCheck {
+            clock_skew_ms: -1500,
+            ..ready
+        };
+        assert_eq!(drifting.problems().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resyncs_time_and_resends_after_timestamp_error() {
+        let mut server = mockito::Server::new_async().await;
+        // Binance is 5s ahead of the mock clock and rejects anything stamped
+        // more than a second behind it
+        let time = server
+            .mock("GET", "/api/v3/time")
+            .with_body(r#"{"serverTime": 1700000005000}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let in_sync = |request: &mockito::Request| {
+            let timestamp: u64 = request
+                .path_and_query()
+                .split(['?', '&'])
+                .find_map(|param| param.strip_prefix("timestamp="))
+                .unwrap()
+                .parse()
+                .unwrap();
+            timestamp >= 1700000004000
+        };
+        let account_mock = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_status_code_from_request(move |request| if in_sync(request) { 200 } else { 400 })
+            .with_body_from_request(move |request| {
+                if in_sync(request) {
+                    br#"{"makerCommission": 10, "takerCommission": 10,
+                        "buyerCommission": 0, "sellerCommission": 0,
+                        "canTrade": true, "canWithdraw": false, "canDeposit": true,
+                        "updateTime": 0, "accountType": "SPOT", "balances": []}"#
+                        .to_vec()
+                } else {
+                    br#"{"code":-1021,"msg":"Timestamp for this request is outside of the recvWindow."}"#
+                        .to_vec()
+                }
+            })
+            .expect(2)
+            .create_async()
+            .await;
+
+        // Without retries, only the re-sync can get the request through
+        let clock = Arc::new(MockClock::new("2023-11-14T22:13:20Z".parse().unwrap()));
+        let client = create_client(&server.url())
+            .with_clock(clock)
+            .with_retry_policy(RetryPolicy::new(0, Duration::from_millis(1)));
+
+        let account = client.get_account_info().await.unwrap();
+
+        assert!(account.can_trade);
+        assert_eq!(client.timestamp(), 1700000005000);
+        time.assert_async().await;
+        account_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_rejections() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_status(401)
+            .with_body(r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let result = create_client(&server.url()).get_account_info().await;
+
+        assert!(matches!(result, Err(BinanceError::InvalidSignature { .. })));
+        mock.assert_async().await;
+    }
+
+    fn ticker_24h_json(symbol: &str, quote_volume: &str) -> String {
+        format!(
+            r#"{{"symbol": "{}", "priceChange": "-512.30", "priceChangePercent": "-1.695",
+                "weightedAvgPrice": "30012.4", "prevClosePrice": "30220.1",
+                "lastPrice": "29707.8", "openPrice": "30220.1", "highPrice": "30480",
+                "lowPrice": "29550.25", "volume": "25431.9", "quoteVolume": "{}",
+                "openTime": 1699913600000, "closeTime": 1700000000000, "count": 1048576}}"#,
+            symbol, quote_volume
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_24h_ticker() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v3/ticker/24hr?symbol=BTCUSDT")
+            .with_body(ticker_24h_json("BTCUSDT", "763270391.2"))
+            .create_async()
+            .await;
+
+        let ticker = create_client(&server.url())
+            .get_24h_ticker("BTCUSDT")
+            .await
+            .unwrap();
+
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert_eq!(ticker.price_change_pct_decimal(), dec!(-1.695));
+        assert_eq!(ticker.high_decimal(), dec!(30480));
+        assert_eq!(ticker.low_decimal(), dec!(29550.25));
+        assert_eq!(ticker.volume_decimal(), dec!(25431.9));
+        assert_eq!(ticker.quote_volume_decimal(), dec!(763270391.2));
+        assert_eq!(ticker.weighted_avg_price_decimal(), dec!(30012.4));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_discover_symbols_ranks_trading_pairs_by_quote_volume() {
+        let mut server = mockito::Server::new_async().await;
+        let symbol = |symbol: &str, base: &str, quote: &str, status: &str| {
+            format!(
+                r#"{{"symbol": "{}", "status": "{}", "baseAsset": "{}", "quoteAsset": "{}",
+                    "baseAssetPrecision": 8, "quotePrecision": 8}}"#,
+                symbol, status, base, quote
+            )
+        };
+        let symbols = [
+            symbol("BTCUSDT", "BTC", "USDT", "TRADING"),
+            symbol("ETHUSDT", "ETH", "USDT", "TRADING"),
+            symbol("SOLUSDT", "SOL", "USDT", "TRADING"),
+            symbol("LUNAUSDT", "LUNA", "USDT", "BREAK"),
+            symbol("ETHBTC", "ETH", "BTC", "TRADING"),
+            symbol("NEWUSDT", "NEW", "USDT", "TRADING"),
+        ];
+        let _info = server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_body(format!(
+                r#"{{"timezone": "UTC", "serverTime": 0, "symbols": [{}]}}"#,
+                symbols.join(",")
+            ))
+            .create_async()
+            .await;
+        // NEWUSDT has no ticker yet
+        let _tickers = server
+            .mock("GET", "/api/v3/ticker/24hr")
+            .with_body(format!(
+                "[{}]",
+                [
+                    ticker_24h_json("BTCUSDT", "900000000.5"),
+                    ticker_24h_json("ETHUSDT", "400000000"),
+                    ticker_24h_json("SOLUSDT", "950000000"),
+                    ticker_24h_json("LUNAUSDT", "999999999999"),
+                    ticker_24h_json("ETHBTC", "999999999999"),
+                ]
+                .join(",")
+            ))
+            .create_async()
+            .await;
+
+        let client = create_client(&server.url());
+
+        assert_eq!(
+            client.discover_symbols("USDT", 2).await.unwrap(),
+            vec!["SOLUSDT", "BTCUSDT"]
+        );
+        assert_eq!(
+            client.discover_symbols("USDT", 10).await.unwrap(),
+            vec!["SOLUSDT", "BTCUSDT", "ETHUSDT"]
+        );
+        assert_eq!(
+            client.discover_symbols("BTC", 10).await.unwrap(),
+            vec!["ETHBTC"]
+        );
+    }
 }
@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// An on-disk copy of a REST response body, reused until it is `ttl` old.
+#[derive(Debug, Clone)]
+pub(crate) struct ResponseCache {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(path: impl AsRef<Path>, ttl: Duration) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            ttl,
+        }
+    }
+
+    /// The cached body, if the file exists and is younger than the TTL.
+    pub(crate) async fn load(&self) -> Option<String> {
+        let modified = tokio::fs::metadata(&self.path)
+            .await
+            .ok()?
+            .modified()
+            .ok()?;
+        let age = modified.elapsed().unwrap_or_default();
+        if age >= self.ttl {
+            debug!("Cache {} is stale ({:?} old)", self.path.display(), age);
+            return None;
+        }
+
+        tokio::fs::read_to_string(&self.path).await.ok()
+    }
+
+    /// Replaces the cached body. Failures are logged, not returned: a cache
+    /// that can't be written only costs a refetch next time.
+    pub(crate) async fn store(&self, body: &str) {
+        if let Some(dir) = self.path.parent() {
+            if let Err(e) = tokio::fs::create_dir_all(dir).await {
+                warn!("Failed to create cache directory {}: {}", dir.display(), e);
+                return;
+            }
+        }
+
+        if let Err(e) = tokio::fs::write(&self.path, body).await {
+            warn!("Failed to write cache {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use super::models::AccountInfo;
+
+/// Furthest the local clock may be from Binance's. Signed requests stamped
+/// more than a second ahead of the server are rejected with -1021.
+pub const MAX_CLOCK_SKEW_MS: i64 = 1000;
+
+/// What `BinanceClient::check_credentials` found out about the API key and
+/// the local clock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialCheck {
+    pub can_trade: bool,
+    pub can_withdraw: bool,
+    /// Account permissions such as "SPOT" or "MARGIN"
+    pub permissions: Vec<String>,
+    /// Binance's time minus ours, in milliseconds
+    pub clock_skew_ms: i64,
+}
+
+impl CredentialCheck {
+    pub fn new(account: &AccountInfo, clock_skew_ms: i64) -> Self {
+        Self {
+            can_trade: account.can_trade,
+            can_withdraw: account.can_withdraw,
+            permissions: account.permissions.clone(),
+            clock_skew_ms,
+        }
+    }
+
+    /// Reasons the bot can't trade with this key and clock; empty when it
+    /// can.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.can_trade {
+            problems.push("trading is disabled for this account or API key".to_string());
+        }
+        // Older responses leave permissions out entirely
+        if !self.permissions.is_empty() && !self.permissions.iter().any(|p| p == "SPOT") {
+            problems.push(format!(
+                "account has no SPOT permission (has {})",
+                self.permissions.join(", ")
+            ));
+        }
+        if self.clock_skew_ms.abs() > MAX_CLOCK_SKEW_MS {
+            problems.push(format!(
+                "local clock is {} ms off Binance's, more than the {} ms allowed",
+                self.clock_skew_ms, MAX_CLOCK_SKEW_MS
+            ));
+        }
+        problems
+    }
+
+    /// Settings that don't stop trading but shouldn't be left that way.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.can_withdraw {
+            warnings.push(
+                "API key can withdraw funds; a trading bot's key doesn't need to".to_string(),
+            );
+        }
+        warnings
+    }
+}
@@ -0,0 +1,224 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Errors returned by `BinanceClient`. API rejections are parsed from Binance's
+/// `{"code": ..., "msg": ...}` error body so callers can react to the cause.
+#[derive(Error, Debug)]
+pub enum BinanceError {
+    #[error("Rate limited by Binance ({code}): {msg}")]
+    RateLimited { code: i64, msg: String },
+
+    #[error("Invalid API key or signature ({code}): {msg}")]
+    InvalidSignature { code: i64, msg: String },
+
+    #[error("Insufficient balance: {msg}")]
+    InsufficientBalance { msg: String },
+
+    #[error("Request timestamp outside the receive window: {msg}")]
+    TimestampOutOfSync { msg: String },
+
+    #[error("Unknown order ({code}): {msg}")]
+    UnknownOrder { code: i64, msg: String },
+
+    #[error("Binance error {code}: {msg}")]
+    Other { code: i64, msg: String },
+
+    #[error("HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+
+    #[error("Failed to send {context} request: {source}")]
+    Request {
+        context: &'static str,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("Failed to parse {context} response: {source}; body: {body}")]
+    Parse {
+        context: &'static str,
+        #[source]
+        source: serde_json::Error,
+        /// Start of the response body, cut at `PARSE_ERROR_BODY_CHARS`
+        body: String,
+    },
+}
+
+/// How much of an unparseable response body a `Parse` error keeps.
+const PARSE_ERROR_BODY_CHARS: usize = 200;
+
+/// Deserializes a response body as `T`, keeping the start of the body in
+/// the error so an unexpected shape can be seen in the logs.
+pub(crate) fn parse_response<T: DeserializeOwned>(
+    context: &'static str,
+    text: &str,
+) -> Result<T, BinanceError> {
+    serde_json::from_str(text).map_err(|e| BinanceError::parse(context, e, text))
+}
+
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    code: i64,
+    msg: String,
+}
+
+impl BinanceError {
+    /// Builds an error from a non-2xx response, using the Binance error code
+    /// when the body has one and the HTTP status otherwise.
+    pub fn from_response(status: u16, body: &str) -> Self {
+        match serde_json::from_str::<ApiErrorBody>(body) {
+            Ok(ApiErrorBody { code, msg }) => Self::from_code(code, msg),
+            // 429 and the 418 IP ban don't always carry a JSON body
+            Err(_) if status == 429 || status == 418 => Self::RateLimited {
+                code: 0,
+                msg: body.to_string(),
+            },
+            Err(_) => Self::Http {
+                status,
+                body: body.to_string(),
+            },
+        }
+    }
+
+    fn from_code(code: i64, msg: String) -> Self {
+        match code {
+            -1003 | -1015 => Self::RateLimited { code, msg },
+            -1021 => Self::TimestampOutOfSync { msg },
+            -1022 | -2014 | -2015 => Self::InvalidSignature { code, msg },
+            // -2010 covers every new-order rejection, only some are balance related
+            -2010 if msg.to_lowercase().contains("insufficient balance") => {
+                Self::InsufficientBalance { msg }
+            }
+            -2011 | -2013 => Self::UnknownOrder { code, msg },
+            _ => Self::Other { code, msg },
+        }
+    }
+
+    pub(crate) fn request(context: &'static str, source: reqwest::Error) -> Self {
+        Self::Request { context, source }
+    }
+
+    pub(crate) fn parse(context: &'static str, source: serde_json::Error, body: &str) -> Self {
+        let mut snippet: String = body.chars().take(PARSE_ERROR_BODY_CHARS).collect();
+        if snippet.len() < body.len() {
+            snippet.push_str("...");
+        }
+
+        Self::Parse {
+            context,
+            source,
+            body: snippet,
+        }
+    }
+
+    /// Whether the same request may succeed if tried again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            // A retry is signed again with a fresh timestamp
+            Self::RateLimited { .. } | Self::TimestampOutOfSync { .. } => true,
+            Self::Http { status, .. } => *status >= 500,
+            Self::Request { source, .. } => source.is_timeout() || source.is_connect(),
+            Self::InvalidSignature { .. }
+            | Self::InsufficientBalance { .. }
+            | Self::UnknownOrder { .. }
+            | Self::Other { .. }
+            | Self::Parse { .. } => false,
+        }
+    }
+
+    /// Whether nothing will succeed until an operator steps in, e.g. a
+    /// revoked API key.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::InvalidSignature { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::TickerPrice;
+
+    #[test]
+    fn test_maps_error_codes_to_variants() {
+        let err = BinanceError::from_response(
+            429,
+            r#"{"code":-1003,"msg":"Too many requests; current limit is 1200 request weight per 1 MINUTE."}"#,
+        );
+        assert!(matches!(err, BinanceError::RateLimited { code: -1003, .. }));
+        assert!(err.is_retryable());
+
+        let err = BinanceError::from_response(
+            400,
+            r#"{"code":-1022,"msg":"Signature for this request is not valid."}"#,
+        );
+        assert!(matches!(err, BinanceError::InvalidSignature { .. }));
+        assert!(!err.is_retryable());
+        assert!(err.is_fatal());
+
+        let err = BinanceError::from_response(
+            400,
+            r#"{"code":-2010,"msg":"Account has insufficient balance for requested action."}"#,
+        );
+        assert!(matches!(err, BinanceError::InsufficientBalance { .. }));
+
+        let err = BinanceError::from_response(
+            400,
+            r#"{"code":-1021,"msg":"Timestamp for this request is outside of the recvWindow."}"#,
+        );
+        assert!(matches!(err, BinanceError::TimestampOutOfSync { .. }));
+        assert!(err.is_retryable());
+
+        let err = BinanceError::from_response(400, r#"{"code":-2011,"msg":"Unknown order sent."}"#);
+        assert!(matches!(
+            err,
+            BinanceError::UnknownOrder { code: -2011, .. }
+        ));
+    }
+
+    #[test]
+    fn test_other_order_rejections_keep_code() {
+        let err = BinanceError::from_response(
+            400,
+            r#"{"code":-2010,"msg":"Order would immediately trigger."}"#,
+        );
+        match err {
+            BinanceError::Other { code, msg } => {
+                assert_eq!(code, -2010);
+                assert_eq!(msg, "Order would immediately trigger.");
+            }
+            other => panic!("unexpected variant: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_non_json_bodies_fall_back_to_status() {
+        let err = BinanceError::from_response(502, "<html>Bad Gateway</html>");
+        assert!(matches!(err, BinanceError::Http { status: 502, .. }));
+        assert!(err.is_retryable());
+
+        let err = BinanceError::from_response(418, "");
+        assert!(matches!(err, BinanceError::RateLimited { .. }));
+
+        let err = BinanceError::from_response(404, "Not Found");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_parse_errors_quote_the_body() {
+        let body = r#"{"code":-1121,"msg":"Invalid symbol."}"#;
+        let err = parse_response::<TickerPrice>("ticker price", body).unwrap_err();
+        assert!(matches!(err, BinanceError::Parse { .. }));
+        assert!(!err.is_retryable());
+        let message = err.to_string();
+        assert!(message.contains("ticker price"), "{}", message);
+        assert!(message.contains(body), "{}", message);
+
+        // Long bodies are cut short
+        let body = format!("<html>{}</html>", "x".repeat(1000));
+        let err = parse_response::<TickerPrice>("ticker price", &body).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("<html>xxx"), "{}", message);
+        assert!(message.ends_with("..."), "{}", message);
+        assert!(!message.contains("</html>"), "{}", message);
+    }
+}
@@ -0,0 +1,505 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::error::{parse_response, BinanceError};
+use super::models::*;
+use super::r#trait::Exchange;
+
+/// In-memory exchange for unit tests. Every order fills immediately and in
+/// full at the symbol's current price, moving the account balances, and is
+/// recorded so tests can inspect what was sent. Like Binance, it rejects a
+/// client order id in use by an open order, which, as orders fill at once,
+/// it never has. Clones share state, so a
+/// test can keep a handle on an exchange it gave to the engine.
+#[derive(Clone, Default)]
+pub(crate) struct MockExchange {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    balances: HashMap<String, Decimal>,
+    market_data: HashMap<String, MarketData>,
+    symbols: Vec<SymbolInfo>,
+    orders: Vec<(OrderResponse, OrderRequest)>,
+    trading_disabled: bool,
+    account_fetches: usize,
+    /// Base asset held by open orders, by symbol
+    locked: HashMap<String, Decimal>,
+}
+
+impl MockExchange {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn with_balance(self, asset: &str, amount: Decimal) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .balances
+            .insert(asset.to_string(), amount);
+        self
+    }
+
+    /// Lists `symbol` as trading `base` for `quote`.
+    pub(crate) fn with_symbol(self, symbol: &str, base: &str, quote: &str) -> Self {
+        self.state.lock().unwrap().symbols.push(SymbolInfo {
+            symbol: symbol.to_string(),
+            status: "TRADING".to_string(),
+            base_asset: base.to_string(),
+            quote_asset: quote.to_string(),
+            base_asset_precision: 8,
+            quote_precision: 8,
+            is_spot_trading_allowed: true,
+            filters: Vec::new(),
+        });
+        self
+    }
+
+    /// Gives a listed symbol a LOT_SIZE filter.
+    pub(crate) fn with_lot_size(self, symbol: &str, min_qty: &str, step_size: &str) -> Self {
+        for info in &mut self.state.lock().unwrap().symbols {
+            if info.symbol == symbol {
+                info.filters.push(SymbolFilter::LotSize {
+                    min_qty: min_qty.to_string(),
+                    step_size: step_size.to_string(),
+                });
+            }
+        }
+        self
+    }
+
+    /// Gives a listed symbol a NOTIONAL filter.
+    pub(crate) fn with_min_notional(self, symbol: &str, min_notional: &str) -> Self {
+        for info in &mut self.state.lock().unwrap().symbols {
+            if info.symbol == symbol {
+                info.filters.push(SymbolFilter::Notional {
+                    min_notional: min_notional.to_string(),
+                });
+            }
+        }
+        self
+    }
+
+    /// Closes a listed symbol to spot trading, as for margin-only pairs.
+    pub(crate) fn without_spot_trading(self, symbol: &str) -> Self {
+        for info in &mut self.state.lock().unwrap().symbols {
+            if info.symbol == symbol {
+                info.is_spot_trading_allowed = false;
+            }
+        }
+        self
+    }
+
+    /// Reports the account as unable to trade, like a read-only API key.
+    pub(crate) fn with_trading_disabled(self) -> Self {
+        self.state.lock().unwrap().trading_disabled = true;
+        self
+    }
+
+    /// Locks `amount` of a listed symbol's base asset in an open order, on
+    /// top of the free balance, until the symbol's orders are cancelled.
+    pub(crate) fn with_locked(self, symbol: &str, amount: Decimal) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .locked
+            .insert(symbol.to_string(), amount);
+        self
+    }
+
+    /// How many times the account has been fetched.
+    pub(crate) fn account_fetches(&self) -> usize {
+        self.state.lock().unwrap().account_fetches
+    }
+
+    /// Changes a listed symbol's trading status, e.g. to "HALT".
+    pub(crate) fn with_status(self, symbol: &str, status: &str) -> Self {
+        for info in &mut self.state.lock().unwrap().symbols {
+            if info.symbol == symbol {
+                info.status = status.to_string();
+            }
+        }
+        self
+    }
+
+    /// Sets hourly candles closing at `closes` and a current price equal to
+    /// the last close.
+    pub(crate) fn set_closes(&self, symbol: &str, closes: &[Decimal]) {
+        let klines = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Kline {
+                open_time: i as u64 * 3600000,
+                open: close.to_string(),
+                high: close.to_string(),
+                low: close.to_string(),
+                close: close.to_string(),
+                volume: "100".to_string(),
+                close_time: (i as u64 + 1) * 3600000,
+                quote_asset_volume: "10000".to_string(),
+                number_of_trades: 100,
+                taker_buy_base_asset_volume: "50".to_string(),
+                taker_buy_quote_asset_volume: "5000".to_string(),
+            })
+            .collect();
+
+        self.state.lock().unwrap().market_data.insert(
+            symbol.to_string(),
+            MarketData {
+                symbol: symbol.to_string(),
+                current_price: closes.last().copied().unwrap_or_default(),
+                klines,
+                timestamp: 0,
+                mid_price: None,
+            },
+        );
+    }
+
+    pub(crate) fn balance(&self, asset: &str) -> Decimal {
+        self.state
+            .lock()
+            .unwrap()
+            .balances
+            .get(asset)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Orders placed so far, oldest first.
+    pub(crate) fn orders(&self) -> Vec<OrderRequest> {
+        self.state
+            .lock()
+            .unwrap()
+            .orders
+            .iter()
+            .map(|(_, order)| order.clone())
+            .collect()
+    }
+
+    fn market_data(&self, symbol: &str) -> Result<MarketData, BinanceError> {
+        self.state
+            .lock()
+            .unwrap()
+            .market_data
+            .get(symbol)
+            .cloned()
+            .ok_or_else(|| invalid_symbol(symbol))
+    }
+}
+
+fn invalid_symbol(symbol: &str) -> BinanceError {
+    BinanceError::Other {
+        code: -1121,
+        msg: format!("Invalid symbol {}", symbol),
+    }
+}
+
+fn unknown_order() -> BinanceError {
+    BinanceError::UnknownOrder {
+        code: -2013,
+        msg: "Order does not exist.".to_string(),
+    }
+}
+
+#[async_trait]
+impl Exchange for MockExchange {
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    async fn get_account_info(&self) -> Result<AccountInfo, BinanceError> {
+        let mut state = self.state.lock().unwrap();
+        state.account_fetches += 1;
+        let mut locked: HashMap<&str, Decimal> = HashMap::new();
+        for (symbol, amount) in &state.locked {
+            if let Some(info) = state.symbols.iter().find(|s| &s.symbol == symbol) {
+                *locked.entry(info.base_asset.as_str()).or_default() += amount;
+            }
+        }
+        let mut balances: Vec<Balance> = state
+            .balances
+            .iter()
+            .map(|(asset, amount)| Balance {
+                asset: asset.clone(),
+                free: amount.to_string(),
+                locked: locked
+                    .get(asset.as_str())
+                    .copied()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+            .collect();
+        balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+
+        Ok(AccountInfo {
+            maker_commission: 0,
+            taker_commission: 0,
+            buyer_commission: 0,
+            seller_commission: 0,
+            can_trade: !state.trading_disabled,
+            can_withdraw: false,
+            can_deposit: false,
+            update_time: 0,
+            account_type: "SPOT".to_string(),
+            balances,
+            permissions: vec!["SPOT".to_string()],
+        })
+    }
+
+    async fn get_ticker_price(&self, symbol: &str) -> Result<TickerPrice, BinanceError> {
+        let market_data = self.market_data(symbol)?;
+        Ok(TickerPrice {
+            symbol: symbol.to_string(),
+            price: market_data.current_price.to_string(),
+        })
+    }
+
+    async fn get_all_ticker_prices(&self) -> Result<Vec<TickerPrice>, BinanceError> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .market_data
+            .values()
+            .map(|market_data| TickerPrice {
+                symbol: market_data.symbol.clone(),
+                price: market_data.current_price.to_string(),
+            })
+            .collect())
+    }
+
+    /// Statistics over the whole kline history rather than 24 hours.
+    async fn get_24h_ticker(&self, symbol: &str) -> Result<Ticker24h, BinanceError> {
+        let market_data = self.market_data(symbol)?;
+        let closes = market_data.close_prices();
+        let open = closes.first().copied().unwrap_or_default();
+        let change_pct = if open.is_zero() {
+            Decimal::ZERO
+        } else {
+            (market_data.current_price - open) / open * Decimal::ONE_HUNDRED
+        };
+        let volume: Decimal = market_data
+            .klines
+            .iter()
+            .map(|k| k.volume.parse::<Decimal>().unwrap_or_default())
+            .sum();
+        let quote_volume: Decimal = market_data
+            .klines
+            .iter()
+            .map(|k| k.quote_asset_volume.parse::<Decimal>().unwrap_or_default())
+            .sum();
+
+        Ok(Ticker24h {
+            symbol: symbol.to_string(),
+            price_change_pct: change_pct.round_dp(3).to_string(),
+            high: closes.iter().max().copied().unwrap_or_default().to_string(),
+            low: closes.iter().min().copied().unwrap_or_default().to_string(),
+            volume: volume.to_string(),
+            quote_volume: quote_volume.to_string(),
+            weighted_avg_price: market_data.current_price.to_string(),
+        })
+    }
+
+    async fn get_order_book(&self, symbol: &str, _limit: u32) -> Result<OrderBook, BinanceError> {
+        let price = self.market_data(symbol)?.current_price;
+        let book = serde_json::json!({
+            "lastUpdateId": 1,
+            "bids": [[price.to_string(), "1000"]],
+            "asks": [[price.to_string(), "1000"]],
+        });
+        parse_response("order book", &book.to_string())
+    }
+
+    /// The candles set with `set_closes`, whatever `interval` asks for.
+    async fn get_market_data(
+        &self,
+        symbol: &str,
+        _interval: &str,
+        kline_limit: u32,
+    ) -> Result<MarketData, BinanceError> {
+        let mut market_data = self.market_data(symbol)?;
+        let skip = market_data
+            .klines
+            .len()
+            .saturating_sub(kline_limit as usize);
+        market_data.klines.drain(..skip);
+        Ok(market_data)
+    }
+
+    async fn get_exchange_info(&self) -> Result<ExchangeInfo, BinanceError> {
+        Ok(ExchangeInfo {
+            timezone: "UTC".to_string(),
+            server_time: 0,
+            symbols: self.state.lock().unwrap().symbols.clone(),
+        })
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, BinanceError> {
+        let price = self.market_data(&order.symbol)?.current_price;
+        let mut state = self.state.lock().unwrap();
+        if order.client_order_id.is_some()
+            && state.orders.iter().any(|(response, placed)| {
+                placed.client_order_id == order.client_order_id
+                    && matches!(response.status.as_str(), "NEW" | "PARTIALLY_FILLED")
+            })
+        {
+            return Err(BinanceError::Other {
+                code: -2010,
+                msg: "Duplicate order sent.".to_string(),
+            });
+        }
+
+        let info = state
+            .symbols
+            .iter()
+            .find(|s| s.symbol == order.symbol)
+            .cloned()
+            .ok_or_else(|| invalid_symbol(&order.symbol))?;
+
+        let quantity = match order.quote_order_qty {
+            Some(quote_qty) => quote_qty / price,
+            None => order.quantity,
+        };
+        let quote_qty = quantity * price;
+
+        let (spent_asset, spent, received_asset, received) = match order.side {
+            OrderSide::Buy => (&info.quote_asset, quote_qty, &info.base_asset, quantity),
+            OrderSide::Sell => (&info.base_asset, quantity, &info.quote_asset, quote_qty),
+        };
+        let available = state.balances.get(spent_asset).copied().unwrap_or_default();
+        if available < spent {
+            return Err(BinanceError::InsufficientBalance {
+                msg: "Account has insufficient balance for requested action.".to_string(),
+            });
+        }
+        *state.balances.entry(spent_asset.clone()).or_default() -= spent;
+        *state.balances.entry(received_asset.clone()).or_default() += received;
+
+        let order_id = state.orders.len() as u64 + 1;
+        let response = OrderResponse {
+            symbol: order.symbol.clone(),
+            order_id,
+            client_order_id: order
+                .client_order_id
+                .clone()
+                .unwrap_or_else(|| format!("mock{}", order_id)),
+            transact_time: 0,
+            price: price.to_string(),
+            orig_qty: quantity.to_string(),
+            executed_qty: quantity.to_string(),
+            cummulative_quote_qty: quote_qty.to_string(),
+            status: "FILLED".to_string(),
+            time_in_force: "GTC".to_string(),
+            order_type: order.order_type.to_string(),
+            side: order.side.to_string(),
+        };
+        state.orders.push((response.clone(), order.clone()));
+        Ok(response)
+    }
+
+    async fn place_test_order(&self, order: &OrderRequest) -> Result<(), BinanceError> {
+        self.market_data(&order.symbol).map(|_| ())
+    }
+
+    async fn place_oco_order(
+        &self,
+        _order: &OcoOrderRequest,
+    ) -> Result<OcoOrderResponse, BinanceError> {
+        Err(BinanceError::Other {
+            code: -1000,
+            msg: "OCO orders are not supported by the mock exchange".to_string(),
+        })
+    }
+
+    async fn get_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<QueryOrderResponse, BinanceError> {
+        let state = self.state.lock().unwrap();
+        let (response, _) = state
+            .orders
+            .iter()
+            .find(|(r, _)| r.symbol == symbol && r.order_id == order_id)
+            .ok_or_else(unknown_order)?;
+
+        Ok(QueryOrderResponse {
+            symbol: response.symbol.clone(),
+            order_id: response.order_id,
+            client_order_id: response.client_order_id.clone(),
+            price: response.price.clone(),
+            orig_qty: response.orig_qty.clone(),
+            executed_qty: response.executed_qty.clone(),
+            cummulative_quote_qty: response.cummulative_quote_qty.clone(),
+            status: response.status.clone(),
+            time_in_force: response.time_in_force.clone(),
+            order_type: response.order_type.clone(),
+            side: response.side.clone(),
+            time: 0,
+            update_time: 0,
+        })
+    }
+
+    async fn get_open_orders(&self, _symbol: Option<&str>) -> Result<Vec<OpenOrder>, BinanceError> {
+        // Orders fill on placement, so nothing is ever left open
+        Ok(Vec::new())
+    }
+
+    async fn get_all_orders(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<Vec<OpenOrder>, BinanceError> {
+        let state = self.state.lock().unwrap();
+        let orders: Vec<OpenOrder> = state
+            .orders
+            .iter()
+            .filter(|(r, _)| r.symbol == symbol)
+            .map(|(r, _)| OpenOrder {
+                symbol: r.symbol.clone(),
+                order_id: r.order_id,
+                client_order_id: r.client_order_id.clone(),
+                price: r.price.clone(),
+                orig_qty: r.orig_qty.clone(),
+                executed_qty: r.executed_qty.clone(),
+                cummulative_quote_qty: r.cummulative_quote_qty.clone(),
+                status: r.status.clone(),
+                time_in_force: r.time_in_force.clone(),
+                order_type: r.order_type.clone(),
+                side: r.side.clone(),
+                time: 0,
+                update_time: 0,
+            })
+            .collect();
+
+        let skip = orders.len().saturating_sub(limit as usize);
+        Ok(orders.into_iter().skip(skip).collect())
+    }
+
+    async fn cancel_order(
+        &self,
+        _symbol: &str,
+        _order_id: u64,
+    ) -> Result<CancelOrderResponse, BinanceError> {
+        Err(unknown_order())
+    }
+
+    async fn cancel_all_open_orders(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<CancelOrderResponse>, BinanceError> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(amount) = state.locked.remove(symbol) {
+            let base_asset = state
+                .symbols
+                .iter()
+                .find(|s| s.symbol == symbol)
+                .map(|s| s.base_asset.clone())
+                .ok_or_else(|| invalid_symbol(symbol))?;
+            *state.balances.entry(base_asset).or_default() += amount;
+        }
+        Ok(Vec::new())
+    }
+}
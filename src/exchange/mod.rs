@@ -1,7 +1,25 @@
 mod binance;
+mod cache;
+mod check;
+mod error;
+#[cfg(test)]
+pub(crate) mod mock;
 mod models;
+mod rate_limit;
+mod retry;
+mod r#trait;
 mod websocket;
 
-pub use binance::BinanceClient;
+pub use binance::{
+    value_balances, BinanceClient, AUDIT_TARGET, DEFAULT_KLINE_INTERVAL, KLINE_INTERVALS,
+    MAX_KLINES_PER_REQUEST, ORDER_BOOK_PRICING_DEPTH,
+};
+pub use check::{CredentialCheck, MAX_CLOCK_SKEW_MS};
+pub use error::BinanceError;
 pub use models::*;
-pub use websocket::BinanceWebSocket;
+pub use r#trait::Exchange;
+pub use rate_limit::{RateLimiter, DEFAULT_WEIGHT_PER_MINUTE};
+pub use retry::{
+    retry_with_backoff, RetryPolicy, DEFAULT_MAX_RETRIES, DEFAULT_RETRY_BASE_DELAY_MS,
+};
+pub use websocket::{BinanceWebSocket, ReconnectPolicy, StreamMode, WsMessage};
@@ -1,4 +1,4 @@
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -14,6 +14,16 @@ pub struct AccountInfo {
     pub update_time: u64,
     pub account_type: String,
     pub balances: Vec<Balance>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+impl AccountInfo {
+    /// Taker commission as a fraction of the order value; Binance reports it
+    /// in basis points.
+    pub fn taker_fee_rate(&self) -> Decimal {
+        Decimal::new(self.taker_commission, 4)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -49,6 +59,49 @@ impl TickerPrice {
     }
 }
 
+/// Rolling 24 hour statistics for a symbol, from `GET /api/v3/ticker/24hr`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ticker24h {
+    pub symbol: String,
+    #[serde(rename = "priceChangePercent")]
+    pub price_change_pct: String,
+    #[serde(rename = "highPrice")]
+    pub high: String,
+    #[serde(rename = "lowPrice")]
+    pub low: String,
+    /// In the base asset
+    pub volume: String,
+    pub quote_volume: String,
+    pub weighted_avg_price: String,
+}
+
+impl Ticker24h {
+    pub fn price_change_pct_decimal(&self) -> Decimal {
+        self.price_change_pct.parse().unwrap_or_default()
+    }
+
+    pub fn high_decimal(&self) -> Decimal {
+        self.high.parse().unwrap_or_default()
+    }
+
+    pub fn low_decimal(&self) -> Decimal {
+        self.low.parse().unwrap_or_default()
+    }
+
+    pub fn volume_decimal(&self) -> Decimal {
+        self.volume.parse().unwrap_or_default()
+    }
+
+    pub fn quote_volume_decimal(&self) -> Decimal {
+        self.quote_volume.parse().unwrap_or_default()
+    }
+
+    pub fn weighted_avg_price_decimal(&self) -> Decimal {
+        self.weighted_avg_price.parse().unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Kline {
@@ -83,7 +136,7 @@ impl Kline {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum OrderSide {
     Buy,
@@ -152,6 +205,16 @@ pub struct OrderRequest {
     pub price: Option<Decimal>,
     pub time_in_force: Option<TimeInForce>,
     pub stop_price: Option<Decimal>,
+    /// Quote asset amount to spend (buy) or receive (sell); sent instead of
+    /// `quantity` when set
+    pub quote_order_qty: Option<Decimal>,
+    /// Sent as `newClientOrderId`; Binance rejects a second open order with
+    /// the same id, which makes resending the request safe
+    pub client_order_id: Option<String>,
+    /// Only ever shrinks a position, never opens or flips one. Spot has no
+    /// such order flag, so it's enforced here via `clamp_to_held` rather
+    /// than sent to Binance
+    pub reduce_only: bool,
 }
 
 impl OrderRequest {
@@ -164,6 +227,25 @@ impl OrderRequest {
             price: None,
             time_in_force: None,
             stop_price: None,
+            quote_order_qty: None,
+            client_order_id: None,
+            reduce_only: false,
+        }
+    }
+
+    /// Market order sized in the quote asset, e.g. "buy 50 USDT of BTC".
+    pub fn market_quote(symbol: &str, side: OrderSide, quote_order_qty: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            side,
+            order_type: OrderType::Market,
+            quantity: Decimal::ZERO,
+            price: None,
+            time_in_force: None,
+            stop_price: None,
+            quote_order_qty: Some(quote_order_qty),
+            client_order_id: None,
+            reduce_only: false,
         }
     }
 
@@ -176,7 +258,32 @@ impl OrderRequest {
             price: Some(price),
             time_in_force: Some(TimeInForce::Gtc),
             stop_price: None,
+            quote_order_qty: None,
+            client_order_id: None,
+            reduce_only: false,
+        }
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    pub fn with_reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    /// Caps a reduce-only order at `held`, the quantity of the position on
+    /// the other side: a sell at the base asset held, a buy at the size of
+    /// the short it covers. Returns whether the quantity was cut; orders
+    /// without the flag are left alone.
+    pub fn clamp_to_held(&mut self, held: Decimal) -> bool {
+        if !self.reduce_only || self.quantity <= held {
+            return false;
         }
+        self.quantity = held.max(Decimal::ZERO);
+        true
     }
 }
 
@@ -190,6 +297,9 @@ pub struct OrderResponse {
     pub price: String,
     pub orig_qty: String,
     pub executed_qty: String,
+    /// Quote asset spent or received so far; absent from ACK responses
+    #[serde(default)]
+    pub cummulative_quote_qty: String,
     pub status: String,
     pub time_in_force: String,
     #[serde(rename = "type")]
@@ -197,6 +307,99 @@ pub struct OrderResponse {
     pub side: String,
 }
 
+/// Current state of a single order, from `GET /api/v3/order`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryOrderResponse {
+    pub symbol: String,
+    pub order_id: u64,
+    pub client_order_id: String,
+    pub price: String,
+    pub orig_qty: String,
+    pub executed_qty: String,
+    pub cummulative_quote_qty: String,
+    pub status: String,
+    pub time_in_force: String,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    pub side: String,
+    pub time: u64,
+    pub update_time: u64,
+}
+
+impl From<QueryOrderResponse> for OrderResponse {
+    fn from(order: QueryOrderResponse) -> Self {
+        Self {
+            symbol: order.symbol,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            transact_time: order.time,
+            price: order.price,
+            orig_qty: order.orig_qty,
+            executed_qty: order.executed_qty,
+            cummulative_quote_qty: order.cummulative_quote_qty,
+            status: order.status,
+            time_in_force: order.time_in_force,
+            order_type: order.order_type,
+            side: order.side,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OcoOrderRequest {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub stop_price: Decimal,
+    pub stop_limit_price: Decimal,
+    pub stop_limit_time_in_force: TimeInForce,
+}
+
+impl OcoOrderRequest {
+    /// Builds a SELL OCO that closes a long position: a take-profit limit at
+    /// `take_profit_price` and a stop-limit triggered at `stop_price`.
+    pub fn exit_long(
+        symbol: &str,
+        quantity: Decimal,
+        take_profit_price: Decimal,
+        stop_price: Decimal,
+        stop_limit_price: Decimal,
+    ) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            side: OrderSide::Sell,
+            quantity,
+            price: take_profit_price,
+            stop_price,
+            stop_limit_price,
+            stop_limit_time_in_force: TimeInForce::Gtc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcoOrderResponse {
+    pub order_list_id: i64,
+    pub contingency_type: String,
+    pub list_status_type: String,
+    pub list_order_status: String,
+    pub list_client_order_id: String,
+    pub transaction_time: u64,
+    pub symbol: String,
+    pub orders: Vec<OcoOrderLeg>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcoOrderLeg {
+    pub symbol: String,
+    pub order_id: u64,
+    pub client_order_id: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenOrder {
@@ -206,6 +409,9 @@ pub struct OpenOrder {
     pub price: String,
     pub orig_qty: String,
     pub executed_qty: String,
+    /// Quote amount filled so far
+    #[serde(default)]
+    pub cummulative_quote_qty: String,
     pub status: String,
     pub time_in_force: String,
     #[serde(rename = "type")]
@@ -215,6 +421,16 @@ pub struct OpenOrder {
     pub update_time: u64,
 }
 
+impl OpenOrder {
+    pub fn executed_qty_decimal(&self) -> Decimal {
+        self.executed_qty.parse().unwrap_or_default()
+    }
+
+    pub fn cummulative_quote_qty_decimal(&self) -> Decimal {
+        self.cummulative_quote_qty.parse().unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CancelOrderResponse {
@@ -224,18 +440,152 @@ pub struct CancelOrderResponse {
     pub status: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MyTrade {
+    pub id: u64,
+    pub order_id: u64,
+    pub price: String,
+    pub qty: String,
+    pub commission: String,
+    pub commission_asset: String,
+    pub is_buyer: bool,
+    pub time: u64,
+}
+
+impl MyTrade {
+    pub fn price_decimal(&self) -> Decimal {
+        self.price.parse().unwrap_or_default()
+    }
+
+    pub fn qty_decimal(&self) -> Decimal {
+        self.qty.parse().unwrap_or_default()
+    }
+
+    pub fn commission_decimal(&self) -> Decimal {
+        self.commission.parse().unwrap_or_default()
+    }
+}
+
+/// Net holding built up from a symbol's fills and what it cost in the quote
+/// asset, using average cost: sells release cost in proportion to the
+/// quantity sold.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CostBasis {
+    pub quantity: Decimal,
+    pub cost: Decimal,
+}
+
+impl CostBasis {
+    /// Replays `trades` in order. Commissions paid in the base asset shrink
+    /// the quantity received and those paid in the quote asset add to the
+    /// cost; commissions in any other asset (e.g. BNB) are not counted.
+    pub fn from_trades(trades: &[MyTrade], base_asset: &str, quote_asset: &str) -> Self {
+        let mut trades: Vec<&MyTrade> = trades.iter().collect();
+        trades.sort_by_key(|t| t.id);
+
+        let mut basis = Self::default();
+        for trade in trades {
+            let qty = trade.qty_decimal();
+            let commission = trade.commission_decimal();
+            let base_fee = if trade.commission_asset == base_asset {
+                commission
+            } else {
+                Decimal::ZERO
+            };
+            let quote_fee = if trade.commission_asset == quote_asset {
+                commission
+            } else {
+                Decimal::ZERO
+            };
+
+            if trade.is_buyer {
+                basis.quantity += qty - base_fee;
+                basis.cost += qty * trade.price_decimal() + quote_fee;
+            } else if basis.quantity > Decimal::ZERO {
+                let sold = (qty + base_fee).min(basis.quantity);
+                basis.cost -= basis.cost * sold / basis.quantity;
+                basis.quantity -= sold;
+            }
+        }
+
+        basis
+    }
+
+    /// Replays the filled part of `orders`, as returned by the all-orders
+    /// endpoint, in the order they were placed. Order history doesn't show
+    /// commissions, so none are counted.
+    pub fn from_orders(orders: &[OpenOrder]) -> Self {
+        let mut orders: Vec<&OpenOrder> = orders.iter().collect();
+        orders.sort_by_key(|o| (o.time, o.order_id));
+
+        let mut basis = Self::default();
+        for order in orders {
+            let qty = order.executed_qty_decimal();
+            if order.side == "BUY" {
+                basis.quantity += qty;
+                basis.cost += order.cummulative_quote_qty_decimal();
+            } else if basis.quantity > Decimal::ZERO {
+                let sold = qty.min(basis.quantity);
+                basis.cost -= basis.cost * sold / basis.quantity;
+                basis.quantity -= sold;
+            }
+        }
+
+        basis
+    }
+
+    /// Cost per unit held; `None` when nothing is held.
+    pub fn average_price(&self) -> Option<Decimal> {
+        (self.quantity > Decimal::ZERO).then(|| self.cost / self.quantity)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MarketData {
     pub symbol: String,
     pub current_price: Decimal,
     pub klines: Vec<Kline>,
     pub timestamp: u64,
+    /// Midpoint of the best bid and ask, when order book pricing is enabled
+    pub mid_price: Option<Decimal>,
 }
 
 impl MarketData {
     pub fn close_prices(&self) -> Vec<Decimal> {
         self.klines.iter().map(|k| k.close_decimal()).collect()
     }
+
+    /// Price to value positions at: the book mid when known, since the last
+    /// trade can be stale on illiquid pairs.
+    pub fn valuation_price(&self) -> Decimal {
+        self.mid_price.unwrap_or(self.current_price)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBook {
+    pub last_update_id: u64,
+    /// `(price, quantity)` levels, best (highest) first
+    pub bids: Vec<(String, String)>,
+    /// `(price, quantity)` levels, best (lowest) first
+    pub asks: Vec<(String, String)>,
+}
+
+impl OrderBook {
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.first().and_then(|(price, _)| price.parse().ok())
+    }
+
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.first().and_then(|(price, _)| price.parse().ok())
+    }
+
+    /// Midpoint of the best bid and ask; `None` if either side is empty.
+    pub fn mid_price(&self) -> Option<Decimal> {
+        Some((self.best_bid()? + self.best_ask()?) / Decimal::TWO)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -250,6 +600,148 @@ pub struct WsTickerUpdate {
     pub close_price: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsKlineUpdate {
+    #[serde(rename = "e")]
+    pub event_type: String,
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "k")]
+    pub kline: WsKline,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsKline {
+    #[serde(rename = "t")]
+    pub open_time: u64,
+    #[serde(rename = "T")]
+    pub close_time: u64,
+    #[serde(rename = "i")]
+    pub interval: String,
+    #[serde(rename = "o")]
+    pub open: String,
+    #[serde(rename = "h")]
+    pub high: String,
+    #[serde(rename = "l")]
+    pub low: String,
+    #[serde(rename = "c")]
+    pub close: String,
+    #[serde(rename = "v")]
+    pub volume: String,
+    #[serde(rename = "n")]
+    pub number_of_trades: u64,
+    #[serde(rename = "x")]
+    pub is_closed: bool,
+    #[serde(rename = "q")]
+    pub quote_asset_volume: String,
+    #[serde(rename = "V")]
+    pub taker_buy_base_asset_volume: String,
+    #[serde(rename = "Q")]
+    pub taker_buy_quote_asset_volume: String,
+}
+
+impl From<&WsKline> for Kline {
+    fn from(kline: &WsKline) -> Self {
+        Self {
+            open_time: kline.open_time,
+            open: kline.open.clone(),
+            high: kline.high.clone(),
+            low: kline.low.clone(),
+            close: kline.close.clone(),
+            volume: kline.volume.clone(),
+            close_time: kline.close_time,
+            quote_asset_volume: kline.quote_asset_volume.clone(),
+            number_of_trades: kline.number_of_trades,
+            taker_buy_base_asset_volume: kline.taker_buy_base_asset_volume.clone(),
+            taker_buy_quote_asset_volume: kline.taker_buy_quote_asset_volume.clone(),
+        }
+    }
+}
+
+impl WsKline {
+    pub fn to_kline(&self) -> Kline {
+        Kline {
+            open_time: self.open_time,
+            open: self.open.clone(),
+            high: self.high.clone(),
+            low: self.low.clone(),
+            close: self.close.clone(),
+            volume: self.volume.clone(),
+            close_time: self.close_time,
+            quote_asset_volume: self.quote_asset_volume.clone(),
+            number_of_trades: self.number_of_trades,
+            taker_buy_base_asset_volume: self.taker_buy_base_asset_volume.clone(),
+            taker_buy_quote_asset_volume: self.taker_buy_quote_asset_volume.clone(),
+        }
+    }
+}
+
+/// An `executionReport` event from the user data stream: an order was
+/// placed, filled, cancelled, rejected or expired.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsExecutionReport {
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+    #[serde(rename = "S")]
+    pub side: String,
+    #[serde(rename = "o")]
+    pub order_type: String,
+    #[serde(rename = "q")]
+    pub quantity: String,
+    #[serde(rename = "p")]
+    pub price: String,
+    /// Why this report was sent: NEW, TRADE, CANCELED, REJECTED, EXPIRED, ...
+    #[serde(rename = "x")]
+    pub execution_type: String,
+    #[serde(rename = "X")]
+    pub order_status: String,
+    #[serde(rename = "r")]
+    pub reject_reason: String,
+    #[serde(rename = "i")]
+    pub order_id: u64,
+    #[serde(rename = "l")]
+    pub last_executed_qty: String,
+    #[serde(rename = "z")]
+    pub cumulative_filled_qty: String,
+    #[serde(rename = "L")]
+    pub last_executed_price: String,
+    #[serde(rename = "n")]
+    pub commission: String,
+    #[serde(rename = "N")]
+    pub commission_asset: Option<String>,
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+    #[serde(rename = "Z")]
+    pub cumulative_quote_qty: String,
+}
+
+/// An `outboundAccountPosition` event: the balances that changed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsAccountUpdate {
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "u")]
+    pub last_update_time: u64,
+    #[serde(rename = "B")]
+    pub balances: Vec<WsBalance>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsBalance {
+    #[serde(rename = "a")]
+    pub asset: String,
+    #[serde(rename = "f")]
+    pub free: String,
+    #[serde(rename = "l")]
+    pub locked: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExchangeInfo {
@@ -267,4 +759,102 @@ pub struct SymbolInfo {
     pub quote_asset: String,
     pub base_asset_precision: u32,
     pub quote_precision: u32,
+    /// False for symbols only open to margin trading
+    #[serde(default = "default_spot_trading_allowed")]
+    pub is_spot_trading_allowed: bool,
+    #[serde(default)]
+    pub filters: Vec<SymbolFilter>,
+}
+
+fn default_spot_trading_allowed() -> bool {
+    true
+}
+
+impl SymbolInfo {
+    /// Whether the symbol is open for trading, rather than halted
+    /// ("HALT", "BREAK"), not yet listed ("PRE_TRADING") or the like.
+    pub fn is_trading(&self) -> bool {
+        self.status == "TRADING"
+    }
+
+    /// Step every order price must be a multiple of, from the PRICE_FILTER.
+    /// Binance sends 0 when the filter is disabled.
+    pub fn tick_size(&self) -> Option<Decimal> {
+        self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::PriceFilter { tick_size } => tick_size
+                .parse::<Decimal>()
+                .ok()
+                .filter(|tick| *tick > Decimal::ZERO),
+            _ => None,
+        })
+    }
+
+    /// Smallest quantity an order may be for, from the LOT_SIZE filter.
+    pub fn min_qty(&self) -> Option<Decimal> {
+        self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::LotSize { min_qty, .. } => min_qty.parse().ok(),
+            _ => None,
+        })
+    }
+
+    /// Smallest order value in the quote asset, from the NOTIONAL or
+    /// MIN_NOTIONAL filter.
+    pub fn min_notional(&self) -> Option<Decimal> {
+        self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::MinNotional { min_notional }
+            | SymbolFilter::Notional { min_notional } => min_notional.parse().ok(),
+            _ => None,
+        })
+    }
+
+    /// Step every order quantity must be a multiple of, from the LOT_SIZE
+    /// filter; `None` when the filter is missing or disabled.
+    pub fn step_size(&self) -> Option<Decimal> {
+        self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::LotSize { step_size, .. } => step_size
+                .parse::<Decimal>()
+                .ok()
+                .filter(|step| *step > Decimal::ZERO),
+            _ => None,
+        })
+    }
+
+    /// `price` snapped to the nearest multiple of the tick size, or unchanged
+    /// when the symbol has none.
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        match self.tick_size() {
+            Some(tick) => {
+                let ticks = (price / tick)
+                    .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero);
+                (ticks * tick).round_dp(tick.normalize().scale())
+            }
+            None => price,
+        }
+    }
+
+    /// `quantity` rounded down to a multiple of the step size, so an order
+    /// never asks for more than was budgeted. `None` without a step size.
+    pub fn floor_quantity(&self, quantity: Decimal) -> Option<Decimal> {
+        let step = self.step_size()?;
+        let steps = (quantity / step).floor();
+        Some((steps * step).round_dp(step.normalize().scale()))
+    }
+}
+
+/// Trading rule from a symbol's `filters` in exchange info. Only the ones
+/// the bot applies itself are parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "filterType")]
+pub enum SymbolFilter {
+    #[serde(rename = "PRICE_FILTER", rename_all = "camelCase")]
+    PriceFilter { tick_size: String },
+    #[serde(rename = "LOT_SIZE", rename_all = "camelCase")]
+    LotSize { min_qty: String, step_size: String },
+    /// Older symbols' minimum order value
+    #[serde(rename = "MIN_NOTIONAL", rename_all = "camelCase")]
+    MinNotional { min_notional: String },
+    #[serde(rename = "NOTIONAL", rename_all = "camelCase")]
+    Notional { min_notional: String },
+    #[serde(other)]
+    Other,
 }
@@ -0,0 +1,105 @@
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use tracing::debug;
+
+/// Binance's default REQUEST_WEIGHT budget for spot REST endpoints
+pub const DEFAULT_WEIGHT_PER_MINUTE: u32 = 1200;
+
+/// Token bucket sized to a per-minute request-weight budget. Callers await
+/// until enough weight is available instead of getting an error.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(weight_per_minute: u32) -> Self {
+        let capacity = weight_per_minute.max(1) as f64;
+
+        Self {
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn weight_per_minute(&self) -> u32 {
+        self.capacity as u32
+    }
+
+    pub async fn acquire(&self, weight: u32) {
+        // A single request can never need more than the whole bucket
+        let weight = (weight as f64).min(self.capacity);
+
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+                state.last_refill = now;
+
+                if state.tokens >= weight {
+                    state.tokens -= weight;
+                    return;
+                }
+
+                Duration::from_secs_f64((weight - state.tokens) / self.refill_per_sec)
+            };
+
+            debug!("Request weight budget exhausted, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_within_budget_are_immediate() {
+        let limiter = RateLimiter::new(60);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire(20).await;
+        }
+
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_over_budget_are_spaced_out() {
+        // 60 weight per minute refills at 1 weight per second
+        let limiter = RateLimiter::new(60);
+        let start = Instant::now();
+
+        let mut completed_at = Vec::new();
+        for _ in 0..6 {
+            limiter.acquire(20).await;
+            completed_at.push(start.elapsed());
+        }
+
+        // The first three drain the bucket, each later call waits for 20 weight
+        assert_eq!(completed_at[2], Duration::ZERO);
+        for pair in completed_at[2..].windows(2) {
+            let gap = pair[1] - pair[0];
+            assert!(
+                gap >= Duration::from_millis(19_900),
+                "gap too short: {:?}",
+                gap
+            );
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use rand::Rng;
+use std::future::Future;
+use tokio::time::Duration;
+use tracing::warn;
+
+use super::error::BinanceError;
+
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+pub const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 250;
+
+/// How many times, and how patiently, a failed request is retried.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_RETRIES,
+            Duration::from_millis(DEFAULT_RETRY_BASE_DELAY_MS),
+        )
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, base_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+        }
+    }
+
+    /// The same policy with no more than `max_retries` retries.
+    pub fn at_most(self, max_retries: u32) -> Self {
+        Self {
+            max_retries: self.max_retries.min(max_retries),
+            ..self
+        }
+    }
+
+    /// Exponential delay before retry number `attempt` (0-based), jittered
+    /// to between half and all of the nominal delay.
+    fn delay(&self, attempt: u32) -> Duration {
+        let nominal = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        nominal.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+/// Runs `op` until it succeeds, fails with a non-retryable error, or the
+/// policy's retries are used up.
+pub async fn retry_with_backoff<T, F, Fut>(
+    policy: RetryPolicy,
+    context: &str,
+    mut op: F,
+) -> Result<T, BinanceError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, BinanceError>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match op().await {
+            Err(e) if e.is_retryable() && attempt < policy.max_retries => {
+                let delay = policy.delay(attempt);
+                attempt += 1;
+                warn!(
+                    "{} request failed ({}), retry {}/{} in {:?}",
+                    context, e, attempt, policy.max_retries, delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn server_error() -> BinanceError {
+        BinanceError::from_response(503, "Service Unavailable")
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_until_success() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(3, Duration::from_millis(100));
+
+        let result = retry_with_backoff(policy, "test", || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(server_error())
+            } else {
+                Ok(42)
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_after_max_retries() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(2, Duration::from_millis(100));
+
+        let result: Result<(), _> = retry_with_backoff(policy, "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(server_error())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_does_not_retry_fatal_errors() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<(), _> = retry_with_backoff(RetryPolicy::default(), "test", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(BinanceError::from_response(
+                400,
+                r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}"#,
+            ))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_delay_grows_exponentially_with_jitter() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100));
+
+        for attempt in 0..4 {
+            let nominal = Duration::from_millis(100 * 2u64.pow(attempt));
+            let delay = policy.delay(attempt);
+            assert!(delay >= nominal / 2 && delay <= nominal, "{:?}", delay);
+        }
+    }
+}
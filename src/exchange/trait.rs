@@ -0,0 +1,170 @@
+use async_trait::async_trait;
+
+use super::binance::BinanceClient;
+use super::error::BinanceError;
+use super::models::*;
+
+/// The venue operations the trading engine relies on. Other exchanges plug
+/// in by implementing this and mapping their failures onto `BinanceError`'s
+/// variants, which the engine uses to decide what to retry.
+#[async_trait]
+pub trait Exchange: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn get_account_info(&self) -> Result<AccountInfo, BinanceError>;
+
+    async fn get_ticker_price(&self, symbol: &str) -> Result<TickerPrice, BinanceError>;
+
+    async fn get_all_ticker_prices(&self) -> Result<Vec<TickerPrice>, BinanceError>;
+
+    async fn get_24h_ticker(&self, symbol: &str) -> Result<Ticker24h, BinanceError>;
+
+    async fn get_order_book(&self, symbol: &str, limit: u32) -> Result<OrderBook, BinanceError>;
+
+    /// Whether `get_market_data` fills in `MarketData::mid_price` and
+    /// slippage should be checked against the order book.
+    fn order_book_pricing(&self) -> bool {
+        false
+    }
+
+    /// Current price and the last `kline_limit` candles of `interval`.
+    async fn get_market_data(
+        &self,
+        symbol: &str,
+        interval: &str,
+        kline_limit: u32,
+    ) -> Result<MarketData, BinanceError>;
+
+    async fn get_exchange_info(&self) -> Result<ExchangeInfo, BinanceError>;
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, BinanceError>;
+
+    /// Validates `order` without executing it.
+    async fn place_test_order(&self, order: &OrderRequest) -> Result<(), BinanceError>;
+
+    async fn place_oco_order(
+        &self,
+        order: &OcoOrderRequest,
+    ) -> Result<OcoOrderResponse, BinanceError>;
+
+    async fn get_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<QueryOrderResponse, BinanceError>;
+
+    async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<OpenOrder>, BinanceError>;
+
+    /// Orders on `symbol` in any status, oldest first, up to `limit` of the
+    /// most recent ones.
+    async fn get_all_orders(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<Vec<OpenOrder>, BinanceError>;
+
+    async fn cancel_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<CancelOrderResponse, BinanceError>;
+
+    async fn cancel_all_open_orders(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<CancelOrderResponse>, BinanceError>;
+}
+
+#[async_trait]
+impl Exchange for BinanceClient {
+    fn name(&self) -> &str {
+        "Binance"
+    }
+
+    async fn get_account_info(&self) -> Result<AccountInfo, BinanceError> {
+        BinanceClient::get_account_info(self).await
+    }
+
+    async fn get_ticker_price(&self, symbol: &str) -> Result<TickerPrice, BinanceError> {
+        BinanceClient::get_ticker_price(self, symbol).await
+    }
+
+    async fn get_all_ticker_prices(&self) -> Result<Vec<TickerPrice>, BinanceError> {
+        BinanceClient::get_all_ticker_prices(self).await
+    }
+
+    async fn get_24h_ticker(&self, symbol: &str) -> Result<Ticker24h, BinanceError> {
+        BinanceClient::get_24h_ticker(self, symbol).await
+    }
+
+    async fn get_order_book(&self, symbol: &str, limit: u32) -> Result<OrderBook, BinanceError> {
+        BinanceClient::get_order_book(self, symbol, limit).await
+    }
+
+    fn order_book_pricing(&self) -> bool {
+        BinanceClient::order_book_pricing(self)
+    }
+
+    async fn get_market_data(
+        &self,
+        symbol: &str,
+        interval: &str,
+        kline_limit: u32,
+    ) -> Result<MarketData, BinanceError> {
+        BinanceClient::get_market_data(self, symbol, interval, kline_limit).await
+    }
+
+    async fn get_exchange_info(&self) -> Result<ExchangeInfo, BinanceError> {
+        BinanceClient::get_exchange_info(self).await
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, BinanceError> {
+        BinanceClient::place_order(self, order).await
+    }
+
+    async fn place_test_order(&self, order: &OrderRequest) -> Result<(), BinanceError> {
+        BinanceClient::place_test_order(self, order).await
+    }
+
+    async fn place_oco_order(
+        &self,
+        order: &OcoOrderRequest,
+    ) -> Result<OcoOrderResponse, BinanceError> {
+        BinanceClient::place_oco_order(self, order).await
+    }
+
+    async fn get_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<QueryOrderResponse, BinanceError> {
+        BinanceClient::get_order(self, symbol, order_id).await
+    }
+
+    async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<OpenOrder>, BinanceError> {
+        BinanceClient::get_open_orders(self, symbol).await
+    }
+
+    async fn get_all_orders(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<Vec<OpenOrder>, BinanceError> {
+        BinanceClient::get_all_orders(self, symbol, limit).await
+    }
+
+    async fn cancel_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+    ) -> Result<CancelOrderResponse, BinanceError> {
+        BinanceClient::cancel_order(self, symbol, order_id).await
+    }
+
+    async fn cancel_all_open_orders(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<CancelOrderResponse>, BinanceError> {
+        BinanceClient::cancel_all_open_orders(self, symbol).await
+    }
+}
@@ -1,20 +1,83 @@
 use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, warn};
 
 use crate::config::Environment;
 
-use super::models::WsTickerUpdate;
+use super::binance::BinanceClient;
+use super::models::{WsAccountUpdate, WsExecutionReport, WsKlineUpdate, WsTickerUpdate};
+
+/// Binance drops connections that stay silent for too long, so the client
+/// pings on its own instead of relying on server pings.
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Listen keys expire after 60 minutes without a keepalive.
+const LISTEN_KEY_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// How long to wait between reconnects. The delay doubles after each
+/// consecutive failed attempt up to `max_delay` and starts over from
+/// `base_delay` once a connection succeeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Consecutive attempts after which the stream gives up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    pub fn new(base_delay: Duration, max_delay: Duration, max_attempts: Option<u32>) -> Self {
+        Self {
+            base_delay,
+            max_delay,
+            max_attempts,
+        }
+    }
+
+    /// Delay before reconnect attempt `attempt` (0-based) since the last
+    /// successful connection.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60), None)
+    }
+}
+
+/// How a connection asks for its streams. Combined connections carry any
+/// number of streams and wrap each payload as `{"stream": ..., "data": ...}`;
+/// raw connections carry a single stream and send its payloads bare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StreamMode {
+    /// Used for single-stream subscriptions only; more than one stream is
+    /// always combined
+    Raw,
+    #[default]
+    Combined,
+}
 
 pub struct BinanceWebSocket {
     environment: Environment,
+    ping_interval: Duration,
+    reconnect: ReconnectPolicy,
+    stream_mode: StreamMode,
 }
 
 #[derive(Debug, Clone)]
 pub enum WsMessage {
     Ticker(WsTickerUpdate),
+    Kline(Box<WsKlineUpdate>),
+    ExecutionReport(Box<WsExecutionReport>),
+    BalanceUpdate(WsAccountUpdate),
     Connected,
     Disconnected,
     Error(String),
@@ -22,82 +85,201 @@ pub enum WsMessage {
 
 impl BinanceWebSocket {
     pub fn new(environment: Environment) -> Self {
-        Self { environment }
+        Self {
+            environment,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            reconnect: ReconnectPolicy::default(),
+            stream_mode: StreamMode::default(),
+        }
+    }
+
+    pub fn with_ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.ping_interval = ping_interval;
+        self
+    }
+
+    pub fn with_reconnect_policy(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    pub fn with_stream_mode(mut self, stream_mode: StreamMode) -> Self {
+        self.stream_mode = stream_mode;
+        self
     }
 
     pub async fn subscribe_tickers(
         &self,
         symbols: Vec<String>,
     ) -> Result<mpsc::Receiver<WsMessage>> {
-        let (tx, rx) = mpsc::channel(100);
-
         let streams: Vec<String> = symbols
             .iter()
             .map(|s| format!("{}@ticker", s.to_lowercase()))
             .collect();
 
-        let stream_param = streams.join("/");
-        let ws_url = format!("{}/stream?streams={}", self.environment.ws_url(), stream_param);
+        self.subscribe_streams(streams).await
+    }
 
-        info!("Connecting to WebSocket: {}", ws_url);
+    pub async fn subscribe_klines(
+        &self,
+        symbols: Vec<String>,
+        interval: &str,
+    ) -> Result<mpsc::Receiver<WsMessage>> {
+        let streams: Vec<String> = symbols
+            .iter()
+            .map(|s| format!("{}@kline_{}", s.to_lowercase(), interval))
+            .collect();
+
+        self.subscribe_streams(streams).await
+    }
+
+    /// Like `subscribe_klines`, but with each symbol on its own candle
+    /// interval, all over one connection.
+    pub async fn subscribe_klines_by_symbol(
+        &self,
+        intervals: &[(String, String)],
+    ) -> Result<mpsc::Receiver<WsMessage>> {
+        let streams: Vec<String> = intervals
+            .iter()
+            .map(|(symbol, interval)| format!("{}@kline_{}", symbol.to_lowercase(), interval))
+            .collect();
+
+        self.subscribe_streams(streams).await
+    }
+
+    /// Streams order updates and balance changes for the account behind
+    /// `client`'s API key. The listen key is kept alive every 30 minutes and
+    /// closed once the returned receiver is dropped.
+    pub async fn subscribe_user_data(
+        &self,
+        client: Arc<BinanceClient>,
+    ) -> Result<mpsc::Receiver<WsMessage>> {
+        let listen_key = client
+            .create_listen_key()
+            .await
+            .context("Failed to create listen key")?;
+
+        let (tx, rx) = mpsc::channel(100);
+        self.connect(vec![listen_key.clone()], tx.clone());
 
-        let tx_clone = tx.clone();
         tokio::spawn(async move {
-            if let Err(e) = Self::run_websocket(ws_url, tx_clone).await {
-                error!("WebSocket error: {}", e);
+            let mut keepalive = tokio::time::interval_at(
+                tokio::time::Instant::now() + LISTEN_KEY_KEEPALIVE_INTERVAL,
+                LISTEN_KEY_KEEPALIVE_INTERVAL,
+            );
+
+            loop {
+                tokio::select! {
+                    _ = keepalive.tick() => {
+                        if let Err(e) = client.keepalive_listen_key(&listen_key).await {
+                            warn!("Failed to keep user data stream alive: {}", e);
+                        }
+                    }
+                    _ = tx.closed() => break,
+                }
+            }
+
+            if let Err(e) = client.close_listen_key(&listen_key).await {
+                warn!("Failed to close user data stream: {}", e);
             }
         });
 
         Ok(rx)
     }
 
-    async fn run_websocket(url: String, tx: mpsc::Sender<WsMessage>) -> Result<()> {
+    async fn subscribe_streams(&self, streams: Vec<String>) -> Result<mpsc::Receiver<WsMessage>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.connect(streams, tx);
+        Ok(rx)
+    }
+
+    fn stream_url(&self, streams: &[String]) -> String {
+        match (self.stream_mode, streams) {
+            (StreamMode::Raw, [stream]) => format!("{}/ws/{}", self.environment.ws_url(), stream),
+            _ => format!(
+                "{}/stream?streams={}",
+                self.environment.ws_url(),
+                streams.join("/")
+            ),
+        }
+    }
+
+    fn connect(&self, streams: Vec<String>, tx: mpsc::Sender<WsMessage>) {
+        let ws_url = self.stream_url(&streams);
+
+        info!("Connecting to WebSocket: {}", ws_url);
+
+        let ping_interval = self.ping_interval;
+        let reconnect = self.reconnect;
+        tokio::spawn(async move {
+            if let Err(e) = Self::run_websocket(ws_url, tx, ping_interval, reconnect).await {
+                error!("WebSocket error: {}", e);
+            }
+        });
+    }
+
+    async fn run_websocket(
+        url: String,
+        tx: mpsc::Sender<WsMessage>,
+        ping_interval: Duration,
+        reconnect: ReconnectPolicy,
+    ) -> Result<()> {
+        let mut attempts = 0;
+
         loop {
             match connect_async(&url).await {
                 Ok((ws_stream, _)) => {
                     info!("WebSocket connected");
+                    attempts = 0;
                     let _ = tx.send(WsMessage::Connected).await;
 
                     let (mut write, mut read) = ws_stream.split();
 
-                    // Ping task to keep connection alive
-                    let ping_tx = tx.clone();
-                    tokio::spawn(async move {
-                        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
-                        loop {
-                            interval.tick().await;
-                            debug!("Sending WebSocket ping");
-                            // Note: we can't send ping from here without shared write access
-                            // In production, you'd use Arc<Mutex<>> or a proper actor pattern
-                        }
-                        #[allow(unreachable_code)]
-                        let _ = ping_tx; // Suppress unused warning
-                    });
-
-                    while let Some(msg_result) = read.next().await {
-                        match msg_result {
-                            Ok(Message::Text(text)) => {
-                                if let Err(e) = Self::handle_message(&text, &tx).await {
-                                    warn!("Failed to handle message: {}", e);
+                    // Ping on our own schedule to keep the connection alive
+                    let mut ping_timer = tokio::time::interval_at(
+                        tokio::time::Instant::now() + ping_interval,
+                        ping_interval,
+                    );
+
+                    loop {
+                        tokio::select! {
+                            _ = ping_timer.tick() => {
+                                debug!("Sending WebSocket ping");
+                                if let Err(e) = write.send(Message::Ping(Vec::new())).await {
+                                    warn!("Failed to send WebSocket ping: {}", e);
+                                    break;
                                 }
                             }
-                            Ok(Message::Ping(data)) => {
-                                debug!("Received ping, sending pong");
-                                if write.send(Message::Pong(data)).await.is_err() {
+                            msg = read.next() => {
+                                let Some(msg_result) = msg else {
                                     break;
+                                };
+
+                                match msg_result {
+                                    Ok(Message::Text(text)) => {
+                                        if let Err(e) = Self::handle_message(&text, &tx).await {
+                                            warn!("Failed to handle message: {}", e);
+                                        }
+                                    }
+                                    Ok(Message::Ping(data)) => {
+                                        debug!("Received ping, sending pong");
+                                        if write.send(Message::Pong(data)).await.is_err() {
+                                            break;
+                                        }
+                                    }
+                                    Ok(Message::Close(_)) => {
+                                        info!("WebSocket closed by server");
+                                        let _ = tx.send(WsMessage::Disconnected).await;
+                                        break;
+                                    }
+                                    Err(e) => {
+                                        error!("WebSocket error: {}", e);
+                                        let _ = tx.send(WsMessage::Error(e.to_string())).await;
+                                        break;
+                                    }
+                                    _ => {}
                                 }
                             }
-                            Ok(Message::Close(_)) => {
-                                info!("WebSocket closed by server");
-                                let _ = tx.send(WsMessage::Disconnected).await;
-                                break;
-                            }
-                            Err(e) => {
-                                error!("WebSocket error: {}", e);
-                                let _ = tx.send(WsMessage::Error(e.to_string())).await;
-                                break;
-                            }
-                            _ => {}
                         }
                     }
                 }
@@ -107,31 +289,90 @@ impl BinanceWebSocket {
                 }
             }
 
-            // Reconnect after delay
-            warn!("WebSocket disconnected, reconnecting in 5 seconds...");
+            if reconnect.max_attempts.is_some_and(|max| attempts >= max) {
+                error!("WebSocket giving up after {} reconnect attempts", attempts);
+                let _ = tx
+                    .send(WsMessage::Error(format!(
+                        "Gave up reconnecting after {} attempts",
+                        attempts
+                    )))
+                    .await;
+                return Ok(());
+            }
+
+            let delay = reconnect.delay(attempts);
+            attempts += 1;
+
+            warn!(
+                "WebSocket disconnected, reconnecting in {:?} (attempt {})",
+                delay, attempts
+            );
             let _ = tx.send(WsMessage::Disconnected).await;
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            tokio::time::sleep(delay).await;
         }
     }
 
+    /// Forwards one frame from either kind of connection: combined frames
+    /// are unwrapped first, and raw ones are the payload itself.
     async fn handle_message(text: &str, tx: &mpsc::Sender<WsMessage>) -> Result<()> {
-        #[derive(serde::Deserialize)]
-        struct StreamWrapper {
-            stream: String,
-            data: serde_json::Value,
-        }
+        let frame: serde_json::Value =
+            serde_json::from_str(text).context("Failed to parse stream message")?;
+        let data = match frame {
+            serde_json::Value::Object(mut fields) if fields.contains_key("stream") => fields
+                .remove("data")
+                .context("Combined stream message without data")?,
+            raw => raw,
+        };
 
-        let wrapper: StreamWrapper =
-            serde_json::from_str(text).context("Failed to parse stream wrapper")?;
+        // Payloads name their event type; stream names don't help on raw
+        // connections, and user data streams are named after the listen key
+        match data.get("e").and_then(|e| e.as_str()) {
+            Some("24hrTicker") => {
+                let ticker: WsTickerUpdate =
+                    serde_json::from_value(data).context("Failed to parse ticker update")?;
 
-        if wrapper.stream.ends_with("@ticker") {
-            let ticker: WsTickerUpdate =
-                serde_json::from_value(wrapper.data).context("Failed to parse ticker update")?;
+                debug!("Ticker update: {} = {}", ticker.symbol, ticker.close_price);
+                tx.send(WsMessage::Ticker(ticker))
+                    .await
+                    .context("Failed to send ticker to channel")?;
+            }
+            Some("kline") => {
+                let kline: WsKlineUpdate =
+                    serde_json::from_value(data).context("Failed to parse kline update")?;
 
-            debug!("Ticker update: {} = {}", ticker.symbol, ticker.close_price);
-            tx.send(WsMessage::Ticker(ticker))
-                .await
-                .context("Failed to send ticker to channel")?;
+                debug!(
+                    "Kline update: {} {} close={} closed={}",
+                    kline.symbol, kline.kline.interval, kline.kline.close, kline.kline.is_closed
+                );
+                tx.send(WsMessage::Kline(Box::new(kline)))
+                    .await
+                    .context("Failed to send kline to channel")?;
+            }
+            Some("executionReport") => {
+                let report: WsExecutionReport =
+                    serde_json::from_value(data).context("Failed to parse execution report")?;
+
+                debug!(
+                    "Execution report: {} order {} {} ({} filled)",
+                    report.symbol,
+                    report.order_id,
+                    report.order_status,
+                    report.cumulative_filled_qty
+                );
+                tx.send(WsMessage::ExecutionReport(Box::new(report)))
+                    .await
+                    .context("Failed to send execution report to channel")?;
+            }
+            Some("outboundAccountPosition") => {
+                let update: WsAccountUpdate =
+                    serde_json::from_value(data).context("Failed to parse account update")?;
+
+                debug!("Balance update for {} assets", update.balances.len());
+                tx.send(WsMessage::BalanceUpdate(update))
+                    .await
+                    .context("Failed to send balance update to channel")?;
+            }
+            _ => {}
         }
 
         Ok(())
@@ -146,5 +387,254 @@ mod tests {
     fn test_websocket_creation() {
         let ws = BinanceWebSocket::new(Environment::Testnet);
         assert_eq!(ws.environment, Environment::Testnet);
+        assert_eq!(ws.ping_interval, DEFAULT_PING_INTERVAL);
+        assert_eq!(ws.reconnect, ReconnectPolicy::default());
+    }
+
+    #[test]
+    fn test_reconnect_delay_doubles_up_to_cap() {
+        let policy = ReconnectPolicy::new(Duration::from_secs(1), Duration::from_secs(10), None);
+
+        assert_eq!(policy.delay(0), Duration::from_secs(1));
+        assert_eq!(policy.delay(1), Duration::from_secs(2));
+        assert_eq!(policy.delay(3), Duration::from_secs(8));
+        assert_eq!(policy.delay(4), Duration::from_secs(10));
+        assert_eq!(policy.delay(100), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_stream_url_per_mode() {
+        let streams = vec!["btcusdt@ticker".to_string(), "ethusdt@ticker".to_string()];
+
+        let combined = BinanceWebSocket::new(Environment::Mainnet);
+        assert_eq!(
+            combined.stream_url(&streams[..1]),
+            "wss://stream.binance.com:9443/stream?streams=btcusdt@ticker"
+        );
+
+        let raw = BinanceWebSocket::new(Environment::Mainnet).with_stream_mode(StreamMode::Raw);
+        assert_eq!(
+            raw.stream_url(&streams[..1]),
+            "wss://stream.binance.com:9443/ws/btcusdt@ticker"
+        );
+        // A raw connection carries only one stream
+        assert_eq!(
+            raw.stream_url(&streams),
+            "wss://stream.binance.com:9443/stream?streams=btcusdt@ticker/ethusdt@ticker"
+        );
+    }
+
+    /// A URL nothing listens on, so every connection attempt is refused.
+    async fn refused_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("ws://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnect_backs_off_and_gives_up_after_max_attempts() {
+        let url = refused_url().await;
+        let policy = ReconnectPolicy::new(
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+            Some(3),
+        );
+
+        let (tx, mut rx) = mpsc::channel(100);
+        let client = tokio::spawn(BinanceWebSocket::run_websocket(
+            url,
+            tx,
+            Duration::from_secs(30),
+            policy,
+        ));
+
+        let mut failures = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            if let WsMessage::Error(e) = msg {
+                failures.push((tokio::time::Instant::now(), e));
+            }
+        }
+
+        // The channel closes once the task stops
+        client.await.unwrap().unwrap();
+
+        // Initial attempt plus three reconnects, then the terminal error
+        assert_eq!(failures.len(), 5);
+        assert_eq!(failures[4].1, "Gave up reconnecting after 3 attempts");
+
+        let gaps: Vec<Duration> = failures
+            .windows(2)
+            .map(|pair| pair[1].0 - pair[0].0)
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(300),
+                Duration::ZERO,
+            ]
+        );
+    }
+
+    const TICKER_PAYLOAD: &str = r#"{
+        "e": "24hrTicker", "E": 1700000000000, "s": "BTCUSDT", "p": "150.00",
+        "P": "0.41", "w": "37000.00", "x": "36900.00", "c": "37050.50", "Q": "0.01",
+        "b": "37050.00", "B": "1.2", "a": "37051.00", "A": "0.8", "o": "36900.50",
+        "h": "37100.00", "l": "36800.00", "v": "1500.5", "q": "55500000.00",
+        "O": 1699913600000, "C": 1700000000000, "F": 1, "L": 1000, "n": 1000
+    }"#;
+
+    async fn handled_ticker(frame: &str) -> WsTickerUpdate {
+        let (tx, mut rx) = mpsc::channel(10);
+        BinanceWebSocket::handle_message(frame, &tx).await.unwrap();
+
+        match rx.recv().await {
+            Some(WsMessage::Ticker(ticker)) => ticker,
+            other => panic!("expected ticker message, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_combined_stream_ticker() {
+        let frame = format!(
+            r#"{{"stream": "btcusdt@ticker", "data": {}}}"#,
+            TICKER_PAYLOAD
+        );
+
+        let ticker = handled_ticker(&frame).await;
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert_eq!(ticker.close_price, "37050.50");
+    }
+
+    #[tokio::test]
+    async fn test_handle_raw_stream_ticker() {
+        let ticker = handled_ticker(TICKER_PAYLOAD).await;
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert_eq!(ticker.event_time, 1700000000000);
+        assert_eq!(ticker.close_price, "37050.50");
+    }
+
+    #[tokio::test]
+    async fn test_handle_kline_message() {
+        let frame = r#"{
+            "stream": "btcusdt@kline_1m",
+            "data": {
+                "e": "kline", "E": 1700000060000, "s": "BTCUSDT",
+                "k": {
+                    "t": 1700000000000, "T": 1700000059999, "s": "BTCUSDT", "i": "1m",
+                    "f": 100, "L": 200, "o": "37000.00", "c": "37050.50",
+                    "h": "37080.00", "l": "36990.00", "v": "12.5", "n": 101,
+                    "x": true, "q": "463000.00", "V": "6.1", "Q": "226000.00", "B": "0"
+                }
+            }
+        }"#;
+
+        let (tx, mut rx) = mpsc::channel(10);
+        BinanceWebSocket::handle_message(frame, &tx).await.unwrap();
+
+        match rx.recv().await {
+            Some(WsMessage::Kline(update)) => {
+                assert_eq!(update.symbol, "BTCUSDT");
+                assert_eq!(update.kline.interval, "1m");
+                assert!(update.kline.is_closed);
+
+                let kline = update.kline.to_kline();
+                assert_eq!(kline.close_decimal(), rust_decimal_macros::dec!(37050.50));
+                assert_eq!(kline.close_time, 1700000059999);
+            }
+            other => panic!("expected kline message, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_execution_report_message() {
+        let frame = r#"{
+            "stream": "pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1",
+            "data": {
+                "e": "executionReport", "E": 1499405658658, "s": "ETHBTC",
+                "c": "mUvoqJxFIILMdfAW5iGSOW", "S": "BUY", "o": "LIMIT", "f": "GTC",
+                "q": "1.00000000", "p": "0.10264410", "P": "0.00000000", "F": "0.00000000",
+                "g": -1, "C": "", "x": "TRADE", "X": "PARTIALLY_FILLED", "r": "NONE",
+                "i": 4293153, "l": "0.40000000", "z": "0.40000000", "L": "0.10264000",
+                "n": "0.00040000", "N": "ETH", "T": 1499405658657, "t": 281, "I": 8641984,
+                "w": false, "m": false, "M": true, "O": 1499405658657,
+                "Z": "0.04105600", "Y": "0.04105600", "Q": "0.00000000",
+                "W": 1499405658657, "V": "NONE"
+            }
+        }"#;
+
+        let (tx, mut rx) = mpsc::channel(10);
+        BinanceWebSocket::handle_message(frame, &tx).await.unwrap();
+
+        match rx.recv().await {
+            Some(WsMessage::ExecutionReport(report)) => {
+                assert_eq!(report.symbol, "ETHBTC");
+                assert_eq!(report.order_id, 4293153);
+                assert_eq!(report.execution_type, "TRADE");
+                assert_eq!(report.order_status, "PARTIALLY_FILLED");
+                assert_eq!(report.last_executed_qty, "0.40000000");
+                assert_eq!(report.last_executed_price, "0.10264000");
+                assert_eq!(report.cumulative_quote_qty, "0.04105600");
+                assert_eq!(report.commission_asset.as_deref(), Some("ETH"));
+            }
+            other => panic!("expected execution report, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_account_position_message() {
+        let frame = r#"{
+            "stream": "pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1",
+            "data": {
+                "e": "outboundAccountPosition", "E": 1564034571105, "u": 1564034571073,
+                "B": [
+                    {"a": "ETH", "f": "10000.000000", "l": "0.000000"},
+                    {"a": "USDT", "f": "9950.500000", "l": "49.500000"}
+                ]
+            }
+        }"#;
+
+        let (tx, mut rx) = mpsc::channel(10);
+        BinanceWebSocket::handle_message(frame, &tx).await.unwrap();
+
+        match rx.recv().await {
+            Some(WsMessage::BalanceUpdate(update)) => {
+                assert_eq!(update.last_update_time, 1564034571073);
+                assert_eq!(update.balances.len(), 2);
+                assert_eq!(update.balances[1].asset, "USDT");
+                assert_eq!(update.balances[1].locked, "49.500000");
+            }
+            other => panic!("expected balance update, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_ping() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let (tx, _rx) = mpsc::channel(100);
+        let client = tokio::spawn(BinanceWebSocket::run_websocket(
+            url,
+            tx,
+            Duration::from_millis(50),
+            ReconnectPolicy::default(),
+        ));
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut server = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+        let ping = tokio::time::timeout(Duration::from_secs(2), async {
+            while let Some(Ok(msg)) = server.next().await {
+                if msg.is_ping() {
+                    return true;
+                }
+            }
+            false
+        })
+        .await;
+
+        client.abort();
+        assert_eq!(ping.ok(), Some(true), "client never sent a ping");
     }
 }
@@ -1,5 +1,11 @@
+#[cfg(feature = "api")]
+pub mod api;
+pub mod clock;
 pub mod config;
 pub mod exchange;
+pub mod logging;
+pub mod metrics;
+pub mod notify;
 pub mod risk;
 pub mod strategy;
 pub mod trading;
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+use crate::config::LoggingConfig;
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Installs the global subscriber described by `config`. The returned guard
+/// flushes the log file when dropped, so keep it alive until exit.
+pub fn init(config: &LoggingConfig) -> Result<Option<WorkerGuard>> {
+    let (subscriber, guard) = subscriber(config)?;
+    subscriber.init();
+    Ok(guard)
+}
+
+/// Builds the subscriber: stdout in the configured format, plus a daily
+/// rolling file at `file_path` when `file_enabled` is set.
+pub fn subscriber(
+    config: &LoggingConfig,
+) -> Result<(impl Subscriber + Send + Sync, Option<WorkerGuard>)> {
+    let json = match config.format.to_lowercase().as_str() {
+        "text" => false,
+        "json" => true,
+        other => anyhow::bail!("Unsupported logging format: {}", other),
+    };
+
+    let mut layers = vec![format_layer(std::io::stdout, json, true)];
+
+    let guard = if config.file_enabled {
+        let path = Path::new(&config.file_path);
+        let file_name = path
+            .file_name()
+            .with_context(|| format!("Invalid log file path: {}", config.file_path))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(file_name.to_string_lossy())
+            .build(dir)
+            .with_context(|| format!("Failed to open log file {}", config.file_path))?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+
+        layers.push(format_layer(writer, json, false));
+        Some(guard)
+    } else {
+        None
+    };
+
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("cryptobot=info"));
+
+    Ok((Registry::default().with(layers).with(filter), guard))
+}
+
+fn format_layer<W>(writer: W, json: bool, ansi: bool) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+
+    if json {
+        layer.json().boxed()
+    } else {
+        layer.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logging_config(format: &str, file_path: Option<&Path>) -> LoggingConfig {
+        LoggingConfig {
+            level: "info".to_string(),
+            format: format.to_string(),
+            file_enabled: file_path.is_some(),
+            file_path: file_path
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn test_json_subscriber_builds() {
+        let (json, guard) = subscriber(&logging_config("json", None)).unwrap();
+        assert!(guard.is_none());
+
+        tracing::subscriber::with_default(json, || {
+            tracing::info!(symbol = "BTCUSDT", "json logging works");
+        });
+
+        assert!(subscriber(&logging_config("xml", None)).is_err());
+    }
+
+    #[test]
+    fn test_file_layer_writes_to_configured_path() {
+        let dir = std::env::temp_dir().join(format!("cryptobot-logging-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let config = logging_config("json", Some(&dir.join("bot.log")));
+        let (file_logger, guard) = subscriber(&config).unwrap();
+
+        tracing::subscriber::with_default(file_logger, || {
+            tracing::info!("written to the log file");
+        });
+        // Flushes the background writer
+        drop(guard);
+
+        let file = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .starts_with("bot.log")
+            })
+            .expect("no log file written");
+        let contents = std::fs::read_to_string(file).unwrap();
+
+        assert!(contents.contains("written to the log file"));
+        assert!(contents.trim_start().starts_with('{'));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
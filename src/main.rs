@@ -1,14 +1,20 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
+use rust_decimal::Decimal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use cryptobot::{
-    config::{AppConfig, ExchangeCredentials},
-    exchange::BinanceClient,
+    config::{AppConfig, ExchangeCredentials, StrategyConfig},
+    exchange::{BinanceClient, BinanceWebSocket, RetryPolicy},
+    logging, metrics,
     risk::RiskManager,
-    strategy::{SmaCrossoverStrategy, Strategy},
-    trading::TradingEngine,
+    strategy::{
+        BollingerStrategy, CombinationMode, CompositeStrategy, DcaStrategy, FilteredStrategy,
+        KeltnerStrategy, MaCrossoverStrategy, MaType, MacdStrategy, Strategy, WithInterval,
+    },
+    trading::{PaperWallet, StdinApprover, TradingEngine, TwapExecutor},
 };
 
 #[derive(Parser, Debug)]
@@ -30,25 +36,37 @@ struct Args {
     /// Run once and exit (useful for testing)
     #[arg(long)]
     once: bool,
+
+    /// Check the API credentials, permissions and clock skew, then exit
+    #[arg(long)]
+    check: bool,
+
+    /// Print the recent signals and indicator values when the bot stops
+    #[arg(long)]
+    dump_signals: bool,
+
+    /// Place live orders without asking, even with require_confirmation set
+    #[arg(long)]
+    yes: bool,
+
+    /// Print what one trading cycle would do with the current market data
+    /// and balances, then exit without placing any orders
+    #[arg(long)]
+    plan: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "cryptobot=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     let args = Args::parse();
 
-    info!("Starting Cryptobot...");
-
     // Load configuration
     let config = AppConfig::load_from_path(&args.config)?;
+    config.validate().context("Invalid configuration")?;
+
+    // Initialize logging; the guard flushes the log file on exit
+    let _log_guard = logging::init(&config.logging)?;
+
+    info!("Starting Cryptobot...");
     info!("Configuration loaded from {}", args.config);
 
     // Load credentials from environment
@@ -72,12 +90,33 @@ async fn main() -> Result<()> {
         warn!("Paper trading mode enabled - no real orders will be placed");
     }
 
+    if config.metrics.enabled {
+        metrics::install(&config.metrics)?;
+    }
+
     // Initialize exchange client
-    let client = BinanceClient::new(credentials.clone())?;
+    let mut client = BinanceClient::new(credentials.clone(), &config.exchange.client)?
+        .with_rate_limit(config.exchange.request_weight_per_minute)
+        .with_retry_policy(RetryPolicy::new(
+            config.exchange.max_retries,
+            Duration::from_millis(config.exchange.retry_base_delay_ms),
+        ))
+        .with_order_book_pricing(config.exchange.order_book_pricing)
+        .with_audit_log(config.exchange.audit_log);
+    if let Some(dir) = &config.exchange.exchange_info_cache_dir {
+        client = client.with_exchange_info_cache(
+            dir,
+            Duration::from_secs(config.exchange.exchange_info_cache_ttl_secs),
+        );
+    }
+
+    if args.check {
+        return check_credentials(&client).await;
+    }
 
     // Test connection by fetching account info
     info!("Testing connection to Binance...");
-    match client.get_account_info().await {
+    let account_fee_rate = match client.get_account_info().await {
         Ok(account) => {
             info!("Connected successfully!");
             info!(
@@ -89,46 +128,428 @@ async fn main() -> Result<()> {
                         || b.locked.parse::<f64>().unwrap_or(0.0) > 0.0)
                     .collect::<Vec<_>>()
             );
+            let quote = config.trading.equity_quote_asset();
+            match client.portfolio_value(quote).await {
+                Ok(value) => info!("Portfolio value: {} {}", value.round_dp(2), quote),
+                Err(e) => warn!("Could not value portfolio: {}", e),
+            }
+            account.taker_fee_rate()
         }
         Err(e) => {
             tracing::error!("Failed to connect to Binance: {}", e);
             return Err(e.into());
         }
-    }
+    };
+
+    let symbols = if config.exchange.symbols.is_empty() {
+        let symbols = client
+            .discover_symbols(
+                &config.exchange.discover_quote_asset,
+                config.exchange.discover_symbols,
+            )
+            .await
+            .context("Failed to discover symbols")?;
+        anyhow::ensure!(
+            !symbols.is_empty(),
+            "No trading {} pairs found to discover",
+            config.exchange.discover_quote_asset
+        );
+        info!("Discovered symbols by 24h volume: {:?}", symbols);
+        symbols
+    } else {
+        config.exchange.symbols.clone()
+    };
 
     // Initialize risk manager
-    let risk_manager = RiskManager::new(
+    let mut risk_manager = RiskManager::new(
         config.risk.max_position_pct,
         config.risk.max_daily_loss_pct,
         config.risk.max_open_positions,
+    )
+    .with_exit_levels(
+        config.risk.default_stop_loss_pct,
+        config.risk.default_take_profit_pct,
+        config.risk.stop_limit_offset_pct,
     );
 
+    risk_manager = risk_manager
+        .with_symbol_overrides(config.risk.symbol_overrides.clone())
+        .with_min_order_notional(config.trading.min_order_notional)
+        .with_stable_quote_assets(config.trading.stable_quote_assets.iter().cloned().collect());
+    if let Some(trailing_stop) = config.risk.trailing_stop {
+        risk_manager = risk_manager.with_trailing_stop(trailing_stop);
+    }
+    if let Some(atr_sizing) = config.risk.atr_sizing {
+        risk_manager = risk_manager.with_atr_sizing(atr_sizing);
+    }
+    if let Some(position_scaling) = config.risk.position_scaling {
+        risk_manager = risk_manager.with_position_scaling(position_scaling);
+    }
+    if let Some(max_drawdown_pct) = config.risk.max_drawdown_pct {
+        risk_manager = risk_manager.with_max_drawdown(max_drawdown_pct);
+    }
+    if let Some(max_order_notional) = config.risk.max_order_notional {
+        risk_manager = risk_manager.with_max_order_notional(max_order_notional);
+    }
+    // Paper fills pay the simulated fee, live ones the account's taker rate
+    let fee_rate = config.risk.fee_rate.unwrap_or(if paper_trading {
+        config.trading.paper_fee_pct / Decimal::ONE_HUNDRED
+    } else {
+        account_fee_rate
+    });
+    info!("Fee rate: {}%", fee_rate * Decimal::ONE_HUNDRED);
+    risk_manager = risk_manager.with_fee_rate(fee_rate);
+
+    let daily_reset_tz: chrono_tz::Tz = config
+        .risk
+        .daily_reset_timezone
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid daily_reset_timezone: {}", e))?;
+
+    if let Some(path) = &config.trading.state_path {
+        if Path::new(path).exists() {
+            let state = risk_manager.load_state(Path::new(path), daily_reset_tz)?;
+            info!(
+                "Restored {} position(s) and a {}% daily loss saved at {} from {}",
+                state.positions.len(),
+                risk_manager.current_daily_loss(),
+                state.saved_at,
+                path
+            );
+        }
+    }
+
     // Initialize strategy
-    let strategy: Box<dyn Strategy> = Box::new(SmaCrossoverStrategy::new(
-        config.strategy.sma_crossover.short_period,
-        config.strategy.sma_crossover.long_period,
-        config.strategy.sma_crossover.min_signal_strength,
+    let mut strategy = build_strategy(&config.strategy.default, &config.strategy)?;
+    if config.strategy.rsi.filter {
+        let rsi = &config.strategy.rsi;
+        strategy = Box::new(FilteredStrategy::new(
+            strategy,
+            rsi.period,
+            rsi.oversold_threshold,
+            rsi.overbought_threshold,
+        ));
+    }
+    let strategy = Box::new(WithInterval::new(
+        strategy,
+        config.strategy.kline_interval(&config.strategy.default),
     ));
 
     info!("Using strategy: {}", strategy.name());
 
     // Initialize trading engine
     let mut engine = TradingEngine::new(
-        client,
+        Box::new(client),
         risk_manager,
         strategy,
-        config.exchange.symbols.clone(),
+        symbols,
         paper_trading,
+    )
+    .with_max_concurrent_symbols(config.trading.max_concurrent_symbols)
+    .with_cancel_orders_on_shutdown(config.trading.cancel_orders_on_shutdown)
+    .with_cancel_orders_to_sell(config.trading.cancel_orders_to_sell)
+    .with_paper_order_validation(config.trading.validate_paper_orders)
+    .with_daily_reset_timezone(daily_reset_tz)
+    .with_equity_quote_asset(config.trading.equity_quote_asset())
+    .with_cooldown(Duration::from_millis(config.trading.cooldown_ms))
+    .with_account_cache_ttl(Duration::from_millis(config.trading.account_cache_ttl_ms))
+    .with_reduce_only(config.trading.reduce_only)
+    .with_signal_log_capacity(config.trading.signal_log_size)
+    .with_fill_polling(
+        Duration::from_millis(config.trading.fill_poll_interval_ms),
+        config.trading.fill_poll_attempts,
     );
 
+    for group in &config.strategy.symbol_strategies {
+        for symbol in &group.symbols {
+            let strategy = Box::new(WithInterval::new(
+                build_strategy(&group.strategy, &config.strategy)?,
+                config.strategy.kline_interval(&group.strategy),
+            ));
+            info!("Using strategy {} for {}", strategy.name(), symbol);
+            engine = engine.with_symbol_strategy(symbol, strategy);
+        }
+    }
+
+    let slippage_tolerance = Decimal::try_from(config.trading.slippage_tolerance)
+        .context("Invalid slippage_tolerance")?;
+    engine = match config.trading.default_order_type.to_lowercase().as_str() {
+        "market" => engine.with_slippage_protection(slippage_tolerance),
+        "limit" => engine.with_limit_orders(slippage_tolerance),
+        other => anyhow::bail!("Unsupported default_order_type: {}", other),
+    };
+
+    if config.trading.order_ttl_ms > 0 {
+        engine = engine.with_order_ttl(Duration::from_millis(config.trading.order_ttl_ms));
+        if config.trading.reprice_expired_orders {
+            engine = engine.with_expired_order_repricing();
+        }
+    }
+    if let Some(fee_asset) = &config.risk.fee_asset {
+        engine = engine.with_fee_asset(fee_asset, config.risk.min_fee_asset_balance);
+    }
+    if config.trading.reserve_quote_amount > Decimal::ZERO {
+        engine = engine.with_quote_reserve(config.trading.reserve_quote_amount);
+    }
+    if let Some(path) = &config.trading.state_path {
+        engine = engine.with_state_file(
+            PathBuf::from(path),
+            Duration::from_secs(config.trading.state_save_interval_secs),
+        );
+    }
+    if config.trading.max_data_age_ms > 0 {
+        engine = engine.with_max_data_age(Duration::from_millis(config.trading.max_data_age_ms));
+    }
+
+    if !config.trading.trading_hours.is_empty() {
+        engine = engine.with_trading_hours(config.trading.trading_hours.clone());
+    }
+    if let Some(min_volume) = config.trading.min_24h_quote_volume {
+        engine = engine.with_min_24h_quote_volume(min_volume);
+    }
+    if let Some(smoothing) = config.trading.signal_smoothing {
+        engine = engine.with_signal_smoothing(smoothing);
+    }
+
+    if paper_trading {
+        engine = engine.with_paper_wallet(
+            PaperWallet::new(
+                config.trading.paper_balances.clone(),
+                config.trading.paper_fee_pct,
+            )
+            .with_slippage(config.trading.paper_slippage_pct),
+        );
+    }
+
+    if let Some(breaker_pct) = config.risk.circuit_breaker_pct {
+        if breaker_pct <= config.risk.max_daily_loss_pct {
+            warn!(
+                "circuit_breaker_pct ({}%) is not above max_daily_loss_pct ({}%)",
+                breaker_pct, config.risk.max_daily_loss_pct
+            );
+        }
+        engine = engine.with_circuit_breaker(breaker_pct);
+    }
+
+    if config.trading.require_confirmation && !paper_trading && !args.yes {
+        engine = engine.with_order_approver(Box::new(StdinApprover::new()));
+        info!("Live orders need confirmation on stdin");
+    }
+
+    if let Some(min_notional) = config.trading.twap_min_notional {
+        engine = engine.with_twap(
+            TwapExecutor::new(
+                config.trading.twap_slices,
+                Duration::from_secs(config.trading.twap_duration_secs),
+            ),
+            min_notional,
+        );
+    }
+
+    #[cfg(feature = "journal")]
+    if let Some(path) = &config.trading.journal_path {
+        engine = engine.with_journal(cryptobot::trading::TradeJournal::open(path)?);
+        info!("Trade journal enabled at {}", path);
+    }
+
+    #[cfg(feature = "telegram")]
+    if config.telegram.enabled {
+        engine = engine.with_notifier(Box::new(cryptobot::notify::TelegramNotifier::new(
+            &config.telegram,
+        )?));
+        info!("Telegram notifications enabled");
+    }
+    #[cfg(not(feature = "telegram"))]
+    if config.telegram.enabled {
+        warn!("telegram.enabled is set but this build lacks the `telegram` feature");
+    }
+
+    if config.webhook.enabled {
+        engine = engine.with_notifier(Box::new(cryptobot::notify::WebhookNotifier::new(
+            &config.webhook,
+        )?));
+        info!("Posting signals to {}", config.webhook.url);
+    }
+
+    engine.reconcile_positions().await?;
+
+    if args.plan {
+        for action in engine.dry_run_cycle().await? {
+            println!("{}", action);
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "api")]
+    if config.api.enabled {
+        let state = cryptobot::api::ApiState::new(engine.risk_manager(), engine.trading_enabled())
+            .with_signal_log(engine.signal_log());
+        cryptobot::api::serve(&config.api, state).await?;
+    }
+    #[cfg(not(feature = "api"))]
+    if config.api.enabled {
+        warn!("api.enabled is set but this build lacks the `api` feature");
+    }
+
     // Run trading engine
     if args.once {
         info!("Running single iteration (--once mode)");
         engine.run_once().await?;
+    } else if config.exchange.streaming {
+        info!("Starting trading loop on the kline stream...");
+        engine
+            .run_streaming(&BinanceWebSocket::new(credentials.environment))
+            .await?;
     } else {
         info!("Starting trading loop...");
         engine.run(config.exchange.update_interval_ms).await?;
     }
 
+    if args.dump_signals {
+        for record in engine.signal_log().records() {
+            let indicators: Vec<String> = record
+                .indicators
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            println!(
+                "{} {} {:?} {}",
+                record.timestamp.to_rfc3339(),
+                record.symbol,
+                record.signal,
+                indicators.join(" ")
+            );
+        }
+    }
+
     Ok(())
 }
+
+/// Reports what the API key can do and exits non-zero if it can't trade.
+async fn check_credentials(client: &BinanceClient) -> Result<()> {
+    let check = match client.check_credentials().await {
+        Ok(check) => check,
+        Err(e) => {
+            eprintln!("Credential check failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Credentials accepted");
+    println!("Can trade: {}", check.can_trade);
+    println!("Can withdraw: {}", check.can_withdraw);
+    println!("Permissions: {}", check.permissions.join(", "));
+    println!("Clock skew: {} ms", check.clock_skew_ms);
+    for warning in check.warnings() {
+        println!("Warning: {}", warning);
+    }
+
+    let problems = check.problems();
+    if problems.is_empty() {
+        println!("Ready to trade");
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("Problem: {}", problem);
+    }
+    std::process::exit(1);
+}
+
+fn build_strategy(name: &str, config: &StrategyConfig) -> Result<Box<dyn Strategy>> {
+    Ok(match name {
+        "macd" => Box::new(
+            MacdStrategy::new(
+                config.macd.fast_period,
+                config.macd.slow_period,
+                config.macd.signal_period,
+                config.macd.min_signal_strength,
+            )
+            .with_min_strengths(
+                config
+                    .macd
+                    .min_buy_strength
+                    .unwrap_or(config.macd.min_signal_strength),
+                config
+                    .macd
+                    .min_sell_strength
+                    .unwrap_or(config.macd.min_signal_strength),
+            ),
+        ),
+        "ema_crossover" => Box::new(
+            MaCrossoverStrategy::new(
+                config.ema_crossover.short_period,
+                config.ema_crossover.long_period,
+                config.ema_crossover.min_signal_strength,
+            )
+            .with_ma_type(MaType::Ema)
+            .with_min_strengths(
+                config
+                    .ema_crossover
+                    .min_buy_strength
+                    .unwrap_or(config.ema_crossover.min_signal_strength),
+                config
+                    .ema_crossover
+                    .min_sell_strength
+                    .unwrap_or(config.ema_crossover.min_signal_strength),
+            ),
+        ),
+        "bollinger" => Box::new(BollingerStrategy::new(
+            config.bollinger.period,
+            config.bollinger.num_std_dev,
+        )),
+        "keltner" => Box::new(KeltnerStrategy::new(
+            config.keltner.ema_period,
+            config.keltner.atr_period,
+            config.keltner.multiplier,
+        )),
+        "dca" => Box::new(DcaStrategy::new(
+            config.dca.quote_amount,
+            config.dca.interval_candles,
+        )),
+        "composite" => {
+            let mode = match config.composite.mode.to_lowercase().as_str() {
+                "unanimous" => CombinationMode::Unanimous,
+                "majority" => CombinationMode::Majority,
+                "weighted_average" => CombinationMode::WeightedAverage {
+                    threshold: config.composite.threshold,
+                },
+                other => anyhow::bail!("Unsupported composite mode: {}", other),
+            };
+
+            let strategies = config
+                .composite
+                .strategies
+                .iter()
+                .map(|child| match child.as_str() {
+                    "composite" => anyhow::bail!("Composite strategies cannot be nested"),
+                    child => build_strategy(child, config),
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            if strategies.is_empty() {
+                anyhow::bail!("Composite strategy needs at least one child strategy");
+            }
+
+            Box::new(CompositeStrategy::new(strategies, mode))
+        }
+        _ => Box::new(
+            MaCrossoverStrategy::new(
+                config.sma_crossover.short_period,
+                config.sma_crossover.long_period,
+                config.sma_crossover.min_signal_strength,
+            )
+            .with_ma_type(config.sma_crossover.ma_type)
+            .with_min_strengths(
+                config
+                    .sma_crossover
+                    .min_buy_strength
+                    .unwrap_or(config.sma_crossover.min_signal_strength),
+                config
+                    .sma_crossover
+                    .min_sell_strength
+                    .unwrap_or(config.sma_crossover.min_signal_strength),
+            )
+            .with_min_separation_pct(config.sma_crossover.min_separation_pct),
+        ),
+    })
+}
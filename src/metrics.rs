@@ -0,0 +1,28 @@
+use anyhow::{Context, Result};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use std::net::SocketAddr;
+use tracing::info;
+
+use crate::config::MetricsConfig;
+
+pub const ORDERS_PLACED: &str = "cryptobot_orders_placed_total";
+pub const ORDERS_REJECTED: &str = "cryptobot_orders_rejected_total";
+pub const OPEN_POSITIONS: &str = "cryptobot_open_positions";
+pub const DAILY_LOSS_PCT: &str = "cryptobot_daily_loss_pct";
+pub const ANALYZE_LATENCY: &str = "cryptobot_analyze_duration_seconds";
+
+/// Installs the global Prometheus recorder and starts serving `/metrics`.
+/// Must be called from within a Tokio runtime.
+pub fn install(config: &MetricsConfig) -> Result<()> {
+    let addr: SocketAddr = format!("{}:{}", config.bind_address, config.port)
+        .parse()
+        .context("Invalid metrics bind address")?;
+
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()
+        .context("Failed to install Prometheus exporter")?;
+
+    info!("Prometheus metrics available at http://{}/metrics", addr);
+    Ok(())
+}
@@ -0,0 +1,443 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sha2::Sha256;
+use std::fmt;
+use tracing::debug;
+
+use crate::config::WebhookConfig;
+use crate::exchange::OrderSide;
+use crate::strategy::Signal;
+
+/// Header carrying the hex HMAC-SHA256 of a webhook's body.
+pub const SIGNATURE_HEADER: &str = "X-Cryptobot-Signature";
+
+/// Something an operator should hear about as it happens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    OrderFilled {
+        symbol: String,
+        side: OrderSide,
+        quantity: Decimal,
+        price: Decimal,
+        paper: bool,
+    },
+    OrderRejected {
+        symbol: String,
+        side: OrderSide,
+        reason: String,
+    },
+    DailyLossLimit {
+        loss_pct: Decimal,
+        limit_pct: Decimal,
+    },
+    EmergencyStop,
+    /// The strategy's signal for a symbol this cycle, including holds
+    Signal {
+        symbol: String,
+        signal: Signal,
+        price: Decimal,
+        timestamp: u64,
+    },
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Notification::OrderFilled {
+                symbol,
+                side,
+                quantity,
+                price,
+                paper,
+            } => write!(
+                f,
+                "{}{} {} {} at {}",
+                if *paper { "[PAPER] " } else { "" },
+                side,
+                quantity.normalize(),
+                symbol,
+                price.normalize()
+            ),
+            Notification::OrderRejected {
+                symbol,
+                side,
+                reason,
+            } => write!(f, "{} {} rejected: {}", side, symbol, reason),
+            Notification::DailyLossLimit {
+                loss_pct,
+                limit_pct,
+            } => write!(
+                f,
+                "Daily loss limit hit: {}% (max {}%), no new orders until the reset",
+                loss_pct.round_dp(2),
+                limit_pct
+            ),
+            Notification::EmergencyStop => write!(
+                f,
+                "Emergency stop: trading disabled and open orders cancelled"
+            ),
+            Notification::Signal {
+                symbol,
+                signal,
+                price,
+                ..
+            } => write!(f, "{}: {:?} at {}", symbol, signal, price.normalize()),
+        }
+    }
+}
+
+/// Delivers notifications somewhere an operator will see them. Failures are
+/// reported back to the engine, which logs them and carries on trading.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Notifier that drops everything.
+pub struct NullNotifier;
+
+#[async_trait]
+impl Notifier for NullNotifier {
+    async fn notify(&self, _notification: &Notification) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SignalPayload<'a> {
+    symbol: &'a str,
+    signal: &'static str,
+    #[serde(with = "rust_decimal::serde::float")]
+    strength: Decimal,
+    price: Decimal,
+    timestamp: u64,
+}
+
+/// Posts strategy signals as JSON to a URL, for users who run their own
+/// execution. Each body is signed with HMAC-SHA256 under a shared secret,
+/// sent hex-encoded in `SIGNATURE_HEADER`. Other notifications are ignored.
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+    secret: String,
+    include_holds: bool,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: &WebhookConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()?;
+
+        Ok(Self {
+            client,
+            url: config.url.clone(),
+            secret: config.secret.clone(),
+            include_holds: config.include_holds,
+        })
+    }
+
+    fn sign(&self, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()).unwrap();
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<()> {
+        let Notification::Signal {
+            symbol,
+            signal,
+            price,
+            timestamp,
+        } = notification
+        else {
+            return Ok(());
+        };
+
+        let (name, strength) = match signal {
+            Signal::Buy { strength } => ("BUY", *strength),
+            Signal::Sell { strength } => ("SELL", *strength),
+            Signal::Hold if self.include_holds => ("HOLD", Decimal::ZERO),
+            Signal::Hold => return Ok(()),
+        };
+
+        let body = serde_json::to_vec(&SignalPayload {
+            symbol,
+            signal: name,
+            strength,
+            price: *price,
+            timestamp: *timestamp,
+        })?;
+
+        debug!("Posting {} signal for {} to webhook", name, symbol);
+
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, self.sign(&body))
+            .body(body)
+            .send()
+            .await
+            .context("Failed to send webhook")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Webhook returned {}: {}", status, body);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "telegram")]
+pub use telegram::TelegramNotifier;
+
+#[cfg(feature = "telegram")]
+mod telegram {
+    use anyhow::{Context, Result};
+    use async_trait::async_trait;
+    use reqwest::Client;
+    use serde::Serialize;
+    use tracing::debug;
+
+    use super::{Notification, Notifier};
+    use crate::config::TelegramConfig;
+
+    const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+    #[derive(Serialize)]
+    struct SendMessage<'a> {
+        chat_id: &'a str,
+        text: String,
+    }
+
+    /// Sends each notification as a message from a Telegram bot to one chat.
+    pub struct TelegramNotifier {
+        client: Client,
+        base_url: String,
+        bot_token: String,
+        chat_id: String,
+    }
+
+    impl TelegramNotifier {
+        pub fn new(config: &TelegramConfig) -> Result<Self> {
+            let client = Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()?;
+
+            Ok(Self {
+                client,
+                base_url: TELEGRAM_API_URL.to_string(),
+                bot_token: config.bot_token.clone(),
+                chat_id: config.chat_id.clone(),
+            })
+        }
+
+        pub fn with_base_url(mut self, base_url: &str) -> Self {
+            self.base_url = base_url.to_string();
+            self
+        }
+    }
+
+    #[async_trait]
+    impl Notifier for TelegramNotifier {
+        async fn notify(&self, notification: &Notification) -> Result<()> {
+            // Signals come every cycle; the chat is for trades and risk events
+            if matches!(notification, Notification::Signal { .. }) {
+                return Ok(());
+            }
+
+            let url = format!("{}/bot{}/sendMessage", self.base_url, self.bot_token);
+            let message = SendMessage {
+                chat_id: &self.chat_id,
+                text: notification.to_string(),
+            };
+
+            debug!("Sending Telegram notification: {}", message.text);
+
+            let response = self
+                .client
+                .post(&url)
+                .json(&message)
+                .send()
+                .await
+                // The URL carries the bot token, which must stay out of logs
+                .map_err(|e| e.without_url())
+                .context("Failed to send Telegram message")?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                anyhow::bail!("Telegram API returned {}: {}", status, body);
+            }
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::exchange::OrderSide;
+        use mockito::Matcher;
+        use rust_decimal_macros::dec;
+
+        fn notifier(server_url: &str) -> TelegramNotifier {
+            TelegramNotifier::new(&TelegramConfig {
+                enabled: true,
+                bot_token: "123:abc".to_string(),
+                chat_id: "42".to_string(),
+            })
+            .unwrap()
+            .with_base_url(server_url)
+        }
+
+        #[tokio::test]
+        async fn test_sends_buy_fill_to_chat() {
+            let mut server = mockito::Server::new_async().await;
+            let send = server
+                .mock("POST", "/bot123:abc/sendMessage")
+                .match_body(Matcher::Json(serde_json::json!({
+                    "chat_id": "42",
+                    "text": "BUY 0.0125 BTCUSDT at 30000",
+                })))
+                .with_body(r#"{"ok": true, "result": {}}"#)
+                .create_async()
+                .await;
+
+            notifier(&server.url())
+                .notify(&Notification::OrderFilled {
+                    symbol: "BTCUSDT".to_string(),
+                    side: OrderSide::Buy,
+                    quantity: dec!(0.01250),
+                    price: dec!(30000.00),
+                    paper: false,
+                })
+                .await
+                .unwrap();
+
+            send.assert_async().await;
+        }
+
+        #[tokio::test]
+        async fn test_api_error_is_reported() {
+            let mut server = mockito::Server::new_async().await;
+            let _send = server
+                .mock("POST", "/bot123:abc/sendMessage")
+                .with_status(400)
+                .with_body(r#"{"ok": false, "description": "Bad Request: chat not found"}"#)
+                .create_async()
+                .await;
+
+            let err = notifier(&server.url())
+                .notify(&Notification::EmergencyStop)
+                .await
+                .unwrap_err();
+
+            assert!(err.to_string().contains("chat not found"));
+        }
+
+        #[tokio::test]
+        async fn test_network_error_leaves_the_token_out() {
+            // Nothing listens on the discard port
+            let err = notifier("http://127.0.0.1:9")
+                .notify(&Notification::EmergencyStop)
+                .await
+                .unwrap_err();
+
+            let message = format!("{:#}", err);
+            assert!(message.contains("Failed to send Telegram message"));
+            assert!(!message.contains("123:abc"), "{}", message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use rust_decimal_macros::dec;
+
+    fn webhook(url: String, include_holds: bool) -> WebhookNotifier {
+        WebhookNotifier::new(&WebhookConfig {
+            enabled: true,
+            url,
+            secret: "s3cret".to_string(),
+            include_holds,
+        })
+        .unwrap()
+    }
+
+    fn signal(signal: Signal) -> Notification {
+        Notification::Signal {
+            symbol: "BTCUSDT".to_string(),
+            signal,
+            price: dec!(30000.5),
+            timestamp: 1700000000000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_posts_signed_buy_signal() {
+        let body = r#"{"symbol":"BTCUSDT","signal":"BUY","strength":0.75,"price":"30000.5","timestamp":1700000000000}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(body.as_bytes());
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/hooks/signals")
+            .match_header("content-type", "application/json")
+            .match_header(SIGNATURE_HEADER, signature.as_str())
+            .match_body(Matcher::Exact(body.to_string()))
+            .create_async()
+            .await;
+
+        webhook(format!("{}/hooks/signals", server.url()), false)
+            .notify(&signal(Signal::Buy {
+                strength: dec!(0.75),
+            }))
+            .await
+            .unwrap();
+
+        hook.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_holds_are_only_sent_when_enabled() {
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "signal": "HOLD",
+                "strength": 0.0,
+            })))
+            .expect(1)
+            .create_async()
+            .await;
+
+        webhook(server.url(), false)
+            .notify(&signal(Signal::Hold))
+            .await
+            .unwrap();
+        webhook(server.url(), false)
+            .notify(&Notification::EmergencyStop)
+            .await
+            .unwrap();
+        webhook(server.url(), true)
+            .notify(&signal(Signal::Hold))
+            .await
+            .unwrap();
+
+        hook.assert_async().await;
+    }
+}
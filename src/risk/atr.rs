@@ -0,0 +1,21 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// Volatility-based sizing: the position is sized so that a stop
+/// `multiplier` × ATR below entry loses `risk_pct` of the balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct AtrSizing {
+    pub risk_pct: Decimal,
+    #[serde(default = "default_atr_period")]
+    pub period: usize,
+    #[serde(default = "default_atr_multiplier")]
+    pub multiplier: Decimal,
+}
+
+fn default_atr_period() -> usize {
+    14
+}
+
+fn default_atr_multiplier() -> Decimal {
+    Decimal::TWO
+}
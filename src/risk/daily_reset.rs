@@ -0,0 +1,131 @@
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use super::position_sizing::RiskManager;
+
+/// The first midnight in `tz` strictly after `after`.
+pub fn next_midnight(after: DateTime<Utc>, tz: Tz) -> DateTime<Utc> {
+    let mut date = after.with_timezone(&tz).date_naive();
+
+    loop {
+        date = date.succ_opt().expect("date out of range");
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+
+        // Zones that skip midnight for DST start the day at 01:00 instead
+        let local = tz.from_local_datetime(&midnight).earliest().or_else(|| {
+            tz.from_local_datetime(&(midnight + ChronoDuration::hours(1)))
+                .earliest()
+        });
+
+        if let Some(local) = local {
+            let utc = local.with_timezone(&Utc);
+            if utc > after {
+                return utc;
+            }
+        }
+    }
+}
+
+/// Longest the reset task sleeps before looking at the clock again, so a
+/// clock that jumps, or is a `MockClock` moved by hand, is noticed promptly.
+pub const DAILY_RESET_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Calls `reset_daily_stats` at every midnight in `tz`, forever, going by the
+/// risk manager's clock.
+pub async fn run_daily_reset(risk_manager: Arc<RiskManager>, tz: Tz) {
+    let clock = risk_manager.clock();
+    let mut boundary = next_midnight(clock.now(), tz);
+
+    loop {
+        let now = clock.now();
+        if now < boundary {
+            let wait = (boundary - now).to_std().unwrap_or_default();
+            tokio::time::sleep(wait.min(DAILY_RESET_POLL_INTERVAL)).await;
+            continue;
+        }
+
+        info!(
+            "Midnight ({}) reached, resetting daily loss of {}%",
+            tz,
+            risk_manager.current_daily_loss()
+        );
+        risk_manager.reset_daily_stats();
+
+        boundary = next_midnight(now, tz);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use rust_decimal_macros::dec;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_next_midnight_utc_and_named_zone() {
+        assert_eq!(
+            next_midnight(utc("2024-03-10T15:30:00Z"), Tz::UTC),
+            utc("2024-03-11T00:00:00Z")
+        );
+
+        // Exactly midnight rolls to the following day
+        assert_eq!(
+            next_midnight(utc("2024-03-11T00:00:00Z"), Tz::UTC),
+            utc("2024-03-12T00:00:00Z")
+        );
+
+        // Berlin is UTC+1 in winter and UTC+2 in summer
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        assert_eq!(
+            next_midnight(utc("2024-01-15T12:00:00Z"), berlin),
+            utc("2024-01-15T23:00:00Z")
+        );
+        assert_eq!(
+            next_midnight(utc("2024-07-15T12:00:00Z"), berlin),
+            utc("2024-07-15T22:00:00Z")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_resets_daily_loss_at_midnight() {
+        let clock = Arc::new(MockClock::new(utc("2024-01-01T23:59:00Z")));
+        let rm = Arc::new(RiskManager::new(dec!(2), dec!(5), 3).with_clock(clock.clone()));
+        rm.record_trade_result(dec!(-3));
+
+        let task = tokio::spawn(run_daily_reset(rm.clone(), Tz::UTC));
+        // Long enough for the task to look at the clock at least once
+        let tick = || tokio::time::sleep(DAILY_RESET_POLL_INTERVAL + Duration::from_secs(1));
+
+        // Time passing elsewhere does not count, only the risk manager's clock
+        tick().await;
+        tick().await;
+        assert_eq!(rm.current_daily_loss(), dec!(3));
+
+        clock.advance(Duration::from_secs(30));
+        tick().await;
+        assert_eq!(rm.current_daily_loss(), dec!(3));
+
+        // Past midnight
+        clock.advance(Duration::from_secs(31));
+        tick().await;
+        assert_eq!(rm.current_daily_loss(), dec!(0));
+
+        // Re-armed for the next day
+        rm.record_trade_result(dec!(-1));
+        clock.advance(Duration::from_secs(23 * 3600));
+        tick().await;
+        assert_eq!(rm.current_daily_loss(), dec!(1));
+        clock.advance(Duration::from_secs(3600));
+        tick().await;
+        assert_eq!(rm.current_daily_loss(), dec!(0));
+
+        task.abort();
+    }
+}
@@ -1,3 +1,16 @@
+mod atr;
+mod daily_reset;
+mod performance;
+mod position;
 mod position_sizing;
+mod slippage;
 
-pub use position_sizing::{RiskError, RiskManager};
+pub use atr::AtrSizing;
+pub use daily_reset::{next_midnight, run_daily_reset};
+pub use performance::{PerformanceSummary, PerformanceTracker};
+pub use position::{Position, PositionScaling};
+pub use position_sizing::{
+    ExitPrices, RiskError, RiskManager, RiskState, SymbolRiskOverride, TrailingStop,
+    DEFAULT_STABLE_QUOTE_ASSETS,
+};
+pub use slippage::check_slippage;
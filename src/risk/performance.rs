@@ -0,0 +1,108 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::sync::RwLock;
+
+/// Aggregate results of the trades closed so far. PnL figures are percentages
+/// of each position's entry value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PerformanceSummary {
+    /// Sum of every trade's PnL percentage
+    pub total_pnl_pct: Decimal,
+    pub num_trades: u32,
+    pub wins: u32,
+    /// Break-even trades count as neither a win nor a loss
+    pub losses: u32,
+    /// Fraction of trades that were wins, 0 to 1
+    pub win_rate: Decimal,
+    pub avg_win: Decimal,
+    /// Average losing trade, as a negative percentage
+    pub avg_loss: Decimal,
+    /// Gross wins over gross losses; `None` until there is a loss
+    pub profit_factor: Option<Decimal>,
+}
+
+/// Records the PnL of each closed trade for the lifetime of the process,
+/// unlike the daily loss, which resets at midnight.
+#[derive(Debug, Default)]
+pub struct PerformanceTracker {
+    trades: RwLock<Vec<Decimal>>,
+}
+
+impl PerformanceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, pnl_pct: Decimal) {
+        self.trades.write().unwrap().push(pnl_pct);
+    }
+
+    pub fn summary(&self) -> PerformanceSummary {
+        let trades = self.trades.read().unwrap();
+
+        let (wins, losses): (Vec<Decimal>, Vec<Decimal>) = trades
+            .iter()
+            .filter(|pnl| !pnl.is_zero())
+            .partition(|pnl| **pnl > dec!(0));
+        let gross_win: Decimal = wins.iter().sum();
+        let gross_loss: Decimal = losses.iter().sum();
+
+        let average = |total: Decimal, count: usize| {
+            if count == 0 {
+                dec!(0)
+            } else {
+                total / Decimal::from(count)
+            }
+        };
+
+        PerformanceSummary {
+            total_pnl_pct: trades.iter().sum(),
+            num_trades: trades.len() as u32,
+            wins: wins.len() as u32,
+            losses: losses.len() as u32,
+            win_rate: average(Decimal::from(wins.len()), trades.len()),
+            avg_win: average(gross_win, wins.len()),
+            avg_loss: average(gross_loss, losses.len()),
+            profit_factor: (!losses.is_empty()).then(|| gross_win / gross_loss.abs()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_of_mixed_trades() {
+        let tracker = PerformanceTracker::new();
+        for pnl in [dec!(4), dec!(-2), dec!(6), dec!(0), dec!(-1), dec!(2)] {
+            tracker.record(pnl);
+        }
+
+        let summary = tracker.summary();
+
+        assert_eq!(summary.total_pnl_pct, dec!(9));
+        assert_eq!(summary.num_trades, 6);
+        assert_eq!(summary.wins, 3);
+        assert_eq!(summary.losses, 2);
+        assert_eq!(summary.win_rate, dec!(0.5));
+        assert_eq!(summary.avg_win, dec!(4));
+        assert_eq!(summary.avg_loss, dec!(-1.5));
+        // 12 won against 3 lost
+        assert_eq!(summary.profit_factor, Some(dec!(4)));
+    }
+
+    #[test]
+    fn test_summary_without_losses_or_trades() {
+        let tracker = PerformanceTracker::new();
+        assert_eq!(tracker.summary(), PerformanceSummary::default());
+
+        tracker.record(dec!(3));
+        let summary = tracker.summary();
+
+        assert_eq!(summary.win_rate, dec!(1));
+        assert_eq!(summary.avg_loss, dec!(0));
+        assert_eq!(summary.profit_factor, None);
+    }
+}
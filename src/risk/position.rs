@@ -0,0 +1,127 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::exchange::OrderSide;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    pub entry_price: Decimal,
+    pub quantity: Decimal,
+    pub side: OrderSide,
+    pub opened_at: u64,
+    /// Most favourable price seen since entry, used by trailing stops
+    pub peak_price: Decimal,
+    /// Quantity bought by each fill that built the position, oldest first
+    pub legs: Vec<Decimal>,
+}
+
+/// Builds positions in legs: each buy signal adds `leg_size_pct` of the
+/// quote balance until the symbol holds `max_position_legs`, and each sell
+/// signal sells the newest leg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct PositionScaling {
+    pub max_position_legs: u32,
+    pub leg_size_pct: Decimal,
+}
+
+impl Position {
+    pub fn new(
+        symbol: &str,
+        entry_price: Decimal,
+        quantity: Decimal,
+        side: OrderSide,
+        opened_at: u64,
+    ) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            entry_price,
+            quantity,
+            side,
+            opened_at,
+            peak_price: entry_price,
+            legs: vec![quantity],
+        }
+    }
+
+    pub fn leg_count(&self) -> u32 {
+        self.legs.len() as u32
+    }
+
+    /// Quantity of the most recently added leg.
+    pub fn newest_leg(&self) -> Option<Decimal> {
+        self.legs.last().copied()
+    }
+
+    /// Adds a fill as a new leg, moving `entry_price` to the volume-weighted
+    /// average price of every fill so far.
+    pub fn add_fill(&mut self, price: Decimal, quantity: Decimal) {
+        let total = self.quantity + quantity;
+        if total > dec!(0) {
+            self.entry_price = (self.cost_basis() + price * quantity) / total;
+        }
+        self.quantity = total;
+        self.legs.push(quantity);
+    }
+
+    pub fn cost_basis(&self) -> Decimal {
+        self.entry_price * self.quantity
+    }
+
+    /// Profit or loss in quote currency if the position were closed at `price`.
+    pub fn unrealized_pnl(&self, price: Decimal) -> Decimal {
+        match self.side {
+            OrderSide::Buy => (price - self.entry_price) * self.quantity,
+            OrderSide::Sell => (self.entry_price - price) * self.quantity,
+        }
+    }
+
+    /// Profit or loss as a percentage of the entry value.
+    pub fn pnl_pct(&self, price: Decimal) -> Decimal {
+        let basis = self.cost_basis();
+        if basis == dec!(0) {
+            return dec!(0);
+        }
+
+        self.unrealized_pnl(price) / basis * dec!(100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_pnl() {
+        let position = Position::new("BTCUSDT", dec!(50000), dec!(0.1), OrderSide::Buy, 0);
+
+        assert_eq!(position.unrealized_pnl(dec!(51000)), dec!(100));
+        assert_eq!(position.pnl_pct(dec!(51000)), dec!(2));
+        assert_eq!(position.unrealized_pnl(dec!(49000)), dec!(-100));
+    }
+
+    #[test]
+    fn test_fills_average_into_entry_price() {
+        let mut position = Position::new("BTCUSDT", dec!(100), dec!(1), OrderSide::Buy, 0);
+        position.add_fill(dec!(110), dec!(2));
+        position.add_fill(dec!(120), dec!(1));
+
+        // (100 * 1 + 110 * 2 + 120 * 1) / 4
+        assert_eq!(position.entry_price, dec!(110));
+        assert_eq!(position.quantity, dec!(4));
+        assert_eq!(position.legs, [dec!(1), dec!(2), dec!(1)]);
+        assert_eq!(position.unrealized_pnl(dec!(121)), dec!(44));
+        assert_eq!(position.pnl_pct(dec!(121)), dec!(10));
+        // Below the last fill, but still above the average
+        assert_eq!(position.unrealized_pnl(dec!(115)), dec!(20));
+    }
+
+    #[test]
+    fn test_short_pnl() {
+        let position = Position::new("BTCUSDT", dec!(50000), dec!(0.1), OrderSide::Sell, 0);
+
+        assert_eq!(position.unrealized_pnl(dec!(49000)), dec!(100));
+        assert_eq!(position.pnl_pct(dec!(51000)), dec!(-2));
+    }
+}
@@ -1,11 +1,27 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 use thiserror::Error;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
+use crate::clock::{Clock, SystemClock};
 use crate::exchange::{Balance, OrderRequest, OrderSide};
+use crate::metrics::{DAILY_LOSS_PCT, OPEN_POSITIONS, ORDERS_REJECTED};
+
+use super::atr::AtrSizing;
+use super::daily_reset::next_midnight;
+use super::performance::{PerformanceSummary, PerformanceTracker};
+use super::position::{Position, PositionScaling};
+
+/// Quote assets pegged to the dollar, valued 1:1 as USD unless configured
+/// otherwise.
+pub const DEFAULT_STABLE_QUOTE_ASSETS: [&str; 5] = ["USDT", "USDC", "FDUSD", "BUSD", "TUSD"];
 
 #[derive(Error, Debug)]
 pub enum RiskError {
@@ -25,6 +41,33 @@ pub enum RiskError {
         max_loss: Decimal,
     },
 
+    #[error("Drawdown of {current_drawdown}% from the equity peak exceeds max {max_drawdown}%")]
+    MaxDrawdownExceeded {
+        current_drawdown: Decimal,
+        max_drawdown: Decimal,
+    },
+
+    #[error("{symbol} position value {requested} would exceed its cap of {max_allowed}")]
+    SymbolLimitExceeded {
+        symbol: String,
+        requested: Decimal,
+        max_allowed: Decimal,
+    },
+
+    #[error("Price moved {slippage_pct}% from {signal_price} to {current_price}, beyond the {tolerance_pct}% tolerance")]
+    SlippageExceeded {
+        signal_price: Decimal,
+        current_price: Decimal,
+        slippage_pct: Decimal,
+        tolerance_pct: Decimal,
+    },
+
+    #[error("Order value {value} exceeds the maximum order value of {max}")]
+    ExceedsMaxNotional { value: Decimal, max: Decimal },
+
+    #[error("Order value {value} is below the minimum notional of {min}")]
+    BelowMinNotional { value: Decimal, min: Decimal },
+
     #[error("Maximum open positions ({max}) reached")]
     MaxPositionsReached { max: u32 },
 
@@ -32,12 +75,78 @@ pub enum RiskError {
     InvalidOrder { reason: String },
 }
 
+/// Protective exit levels for a long position, as placed in an OCO sell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitPrices {
+    pub stop_price: Decimal,
+    pub stop_limit_price: Decimal,
+    pub take_profit_price: Decimal,
+}
+
+/// Stop that follows price up once a position is `activation_pct` in profit,
+/// staying `trail_pct` behind the best price seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct TrailingStop {
+    pub activation_pct: Decimal,
+    pub trail_pct: Decimal,
+}
+
+/// Per-symbol limits that replace or add to the global ones, e.g. to keep a
+/// volatile altcoin from taking the whole budget.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SymbolRiskOverride {
+    /// Replaces the global `max_position_pct` for this symbol
+    pub max_position_pct: Option<Decimal>,
+    /// Cap on the total quote value held in this symbol, including any
+    /// existing position
+    pub max_position_value: Option<Decimal>,
+}
+
+/// What a `RiskManager` saves to its state file: enough to pick up position
+/// tracking and the loss limits after a restart, and for dashboards to read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskState {
+    pub saved_at: DateTime<Utc>,
+    pub daily_loss_pct: Decimal,
+    /// Informational; restoring derives it from `positions`
+    pub open_positions: u32,
+    /// High-water mark of the account equity
+    pub equity_peak: Decimal,
+    pub current_equity: Decimal,
+    pub positions: Vec<Position>,
+}
+
 pub struct RiskManager {
     max_position_pct: Decimal,
     max_daily_loss_pct: Decimal,
     max_open_positions: u32,
+    stop_loss_pct: Option<Decimal>,
+    take_profit_pct: Option<Decimal>,
+    stop_limit_offset_pct: Decimal,
+    trailing_stop: Option<TrailingStop>,
+    atr_sizing: Option<AtrSizing>,
+    symbol_overrides: HashMap<String, SymbolRiskOverride>,
+    /// Smallest order value in USD; 0 accepts any size
+    min_order_notional: Decimal,
+    /// Largest buy in USD whatever the balance; checked like
+    /// `min_order_notional`
+    max_order_notional: Option<Decimal>,
+    /// Quote assets taken as worth one USD each. Orders quoted in anything
+    /// else skip the `min_order_notional` check
+    stable_quote_assets: HashSet<String>,
+    /// Fee charged on each fill as a fraction of its value, e.g. 0.001 for
+    /// 0.1%
+    fee_rate: Decimal,
+    /// Peak-to-trough equity drop that stops trading; unlike the daily loss
+    /// it never resets
+    max_drawdown_pct: Option<Decimal>,
+    position_scaling: Option<PositionScaling>,
     current_daily_loss_pct: RwLock<Decimal>,
-    current_open_positions: AtomicU32,
+    equity_peak: RwLock<Decimal>,
+    current_equity: RwLock<Decimal>,
+    positions: RwLock<HashMap<String, Position>>,
+    performance: PerformanceTracker,
+    clock: Arc<dyn Clock>,
 }
 
 impl RiskManager {
@@ -46,19 +155,201 @@ impl RiskManager {
             max_position_pct,
             max_daily_loss_pct,
             max_open_positions,
+            stop_loss_pct: None,
+            take_profit_pct: None,
+            stop_limit_offset_pct: dec!(0),
+            trailing_stop: None,
+            atr_sizing: None,
+            symbol_overrides: HashMap::new(),
+            min_order_notional: dec!(0),
+            max_order_notional: None,
+            stable_quote_assets: DEFAULT_STABLE_QUOTE_ASSETS
+                .iter()
+                .map(|asset| asset.to_string())
+                .collect(),
+            fee_rate: dec!(0),
+            max_drawdown_pct: None,
+            position_scaling: None,
             current_daily_loss_pct: RwLock::new(dec!(0)),
-            current_open_positions: AtomicU32::new(0),
+            equity_peak: RwLock::new(dec!(0)),
+            current_equity: RwLock::new(dec!(0)),
+            positions: RwLock::new(HashMap::new()),
+            performance: PerformanceTracker::new(),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Enables protective exits. The stop-limit price sits
+    /// `stop_limit_offset_pct` below the stop trigger so it still fills on a
+    /// fast move.
+    pub fn with_exit_levels(
+        mut self,
+        stop_loss_pct: Decimal,
+        take_profit_pct: Decimal,
+        stop_limit_offset_pct: Decimal,
+    ) -> Self {
+        self.stop_loss_pct = Some(stop_loss_pct);
+        self.take_profit_pct = Some(take_profit_pct);
+        self.stop_limit_offset_pct = stop_limit_offset_pct;
+        self
+    }
+
+    pub fn with_trailing_stop(mut self, trailing_stop: TrailingStop) -> Self {
+        self.trailing_stop = Some(trailing_stop);
+        self
+    }
+
+    /// Sizes buys by volatility instead of a fixed share of the balance.
+    pub fn with_atr_sizing(mut self, atr_sizing: AtrSizing) -> Self {
+        self.atr_sizing = Some(atr_sizing);
+        self
+    }
+
+    pub fn atr_sizing(&self) -> Option<AtrSizing> {
+        self.atr_sizing
+    }
+
+    pub fn with_symbol_overrides(
+        mut self,
+        symbol_overrides: HashMap<String, SymbolRiskOverride>,
+    ) -> Self {
+        self.symbol_overrides = symbol_overrides;
+        self
+    }
+
+    /// Rejects orders worth less than `min_order_notional` USD, which the
+    /// exchange would refuse with MIN_NOTIONAL anyway.
+    pub fn with_min_order_notional(mut self, min_order_notional: Decimal) -> Self {
+        self.min_order_notional = min_order_notional;
+        self
+    }
+
+    /// Caps every buy at `max_order_notional` USD, on top of the
+    /// balance-relative `max_position_pct`.
+    pub fn with_max_order_notional(mut self, max_order_notional: Decimal) -> Self {
+        self.max_order_notional = Some(max_order_notional);
+        self
+    }
+
+    /// Largest buy in `quote_asset`, if capped. Like the minimum, only
+    /// stablecoin quotes have one.
+    pub fn max_order_notional_for(&self, quote_asset: &str) -> Option<Decimal> {
+        self.max_order_notional
+            .filter(|_| self.is_stable_quote(quote_asset))
+    }
+
+    /// Quote assets counted 1:1 as USD, replacing
+    /// `DEFAULT_STABLE_QUOTE_ASSETS`.
+    pub fn with_stable_quote_assets(mut self, assets: HashSet<String>) -> Self {
+        self.stable_quote_assets = assets;
+        self
+    }
+
+    pub fn is_stable_quote(&self, asset: &str) -> bool {
+        self.stable_quote_assets.contains(asset)
+    }
+
+    /// Sizes buys so the order plus its fee fits the budget, and takes the
+    /// fees of both fills off each trade's recorded PnL.
+    pub fn with_fee_rate(mut self, fee_rate: Decimal) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Smallest order value in `quote_asset`: `min_order_notional` for
+    /// stablecoins, none for quotes with no fixed USD value.
+    pub fn min_order_notional_for(&self, quote_asset: &str) -> Decimal {
+        if self.is_stable_quote(quote_asset) {
+            self.min_order_notional
+        } else {
+            dec!(0)
+        }
+    }
+
+    pub fn fee_rate(&self) -> Decimal {
+        self.fee_rate
+    }
+
+    /// Time source for the daily reset.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Stops trading once equity, as fed to `update_equity`, falls
+    /// `max_drawdown_pct` below its high-water mark.
+    pub fn with_max_drawdown(mut self, max_drawdown_pct: Decimal) -> Self {
+        self.max_drawdown_pct = Some(max_drawdown_pct);
+        self
+    }
+
+    pub fn max_drawdown_pct(&self) -> Option<Decimal> {
+        self.max_drawdown_pct
+    }
+
+    /// Scales into positions leg by leg instead of buying on every signal
+    /// and selling a strength-scaled share of the balance.
+    pub fn with_position_scaling(mut self, position_scaling: PositionScaling) -> Self {
+        self.position_scaling = Some(position_scaling);
+        self
+    }
+
+    pub fn position_scaling(&self) -> Option<PositionScaling> {
+        self.position_scaling
+    }
+
+    /// Maximum percentage of balance for a single order on `symbol`.
+    pub fn max_position_pct_for(&self, symbol: &str) -> Decimal {
+        self.symbol_overrides
+            .get(symbol)
+            .and_then(|o| o.max_position_pct)
+            .unwrap_or(self.max_position_pct)
+    }
+
+    pub fn exit_prices(&self, entry_price: Decimal) -> Option<ExitPrices> {
+        let stop_loss_pct = self.stop_loss_pct?;
+        let take_profit_pct = self.take_profit_pct?;
+
+        let stop_price = entry_price * (dec!(100) - stop_loss_pct) / dec!(100);
+        let stop_limit_price = stop_price * (dec!(100) - self.stop_limit_offset_pct) / dec!(100);
+        let take_profit_price = entry_price * (dec!(100) + take_profit_pct) / dec!(100);
+
+        Some(ExitPrices {
+            stop_price,
+            stop_limit_price,
+            take_profit_price,
+        })
+    }
+
     pub fn validate_order(
         &self,
         order: &OrderRequest,
         quote_balance: &Balance,
         current_price: Decimal,
     ) -> Result<(), RiskError> {
+        let result = self.check_order(order, quote_balance, current_price);
+        if result.is_err() {
+            ::metrics::counter!(ORDERS_REJECTED, "symbol" => order.symbol.clone()).increment(1);
+        }
+        result
+    }
+
+    fn check_order(
+        &self,
+        order: &OrderRequest,
+        quote_balance: &Balance,
+        current_price: Decimal,
+    ) -> Result<(), RiskError> {
+        // The loss, drawdown and open position limits stop new risk, never
+        // the sells that reduce it
+        let is_buy = matches!(order.side, OrderSide::Buy);
+
         // Check daily loss limit
-        {
+        if is_buy {
             let daily_loss = self.current_daily_loss_pct.read().unwrap();
             if *daily_loss >= self.max_daily_loss_pct {
                 return Err(RiskError::DailyLossExceeded {
@@ -68,33 +359,77 @@ impl RiskManager {
             }
         }
 
-        // Check open positions limit
-        let open_positions = self.current_open_positions.load(Ordering::SeqCst);
-        if open_positions >= self.max_open_positions {
+        if let Some(max_drawdown) = self.max_drawdown_pct {
+            let drawdown = self.current_drawdown_pct();
+            if is_buy && drawdown >= max_drawdown {
+                return Err(RiskError::MaxDrawdownExceeded {
+                    current_drawdown: drawdown,
+                    max_drawdown,
+                });
+            }
+        }
+
+        // Check open positions limit; a leg added to an open position
+        // doesn't open another
+        if is_buy && !self.opens_within_position_limit(&order.symbol) {
             return Err(RiskError::MaxPositionsReached {
                 max: self.max_open_positions,
             });
         }
 
         // For buy orders, check if we have sufficient quote balance
-        if matches!(order.side, OrderSide::Buy) {
-            let order_value = order.quantity * current_price;
+        if is_buy {
+            let order_value = match order.quote_order_qty {
+                Some(quote_qty) => quote_qty,
+                // Limit orders can fill at up to their price, not just the current one
+                None => order.quantity * order.price.unwrap_or(current_price),
+            };
             let available = quote_balance.free_decimal();
-            let max_position_value = available * self.max_position_pct / dec!(100);
+            let max_position_pct = self.max_position_pct_for(&order.symbol);
+            let max_position_value = available * max_position_pct / dec!(100);
 
             debug!(
                 "Order validation: value={}, available={}, max_allowed={}",
                 order_value, available, max_position_value
             );
 
+            // Whichever cap is tighter decides, and names itself
+            if let Some(max) = self
+                .max_order_notional_for(&quote_balance.asset)
+                .filter(|max| order_value > *max && *max < max_position_value)
+            {
+                return Err(RiskError::ExceedsMaxNotional {
+                    value: order_value,
+                    max,
+                });
+            }
+
             if order_value > max_position_value {
                 return Err(RiskError::PositionTooLarge {
                     requested: order_value,
                     max_allowed: max_position_value,
-                    max_pct: self.max_position_pct,
+                    max_pct: max_position_pct,
                 });
             }
 
+            let symbol_cap = self
+                .symbol_overrides
+                .get(&order.symbol)
+                .and_then(|o| o.max_position_value);
+            if let Some(max_allowed) = symbol_cap {
+                let held = self
+                    .position(&order.symbol)
+                    .map(|p| p.cost_basis())
+                    .unwrap_or(dec!(0));
+                if held + order_value > max_allowed {
+                    return Err(RiskError::SymbolLimitExceeded {
+                        symbol: order.symbol.clone(),
+                        requested: held + order_value,
+                        max_allowed,
+                    });
+                }
+            }
+
             if order_value > available {
                 return Err(RiskError::InsufficientBalance {
                     available,
@@ -104,7 +439,7 @@ impl RiskManager {
         }
 
         // Validate order has positive quantity
-        if order.quantity <= dec!(0) {
+        if order.quote_order_qty.unwrap_or(order.quantity) <= dec!(0) {
             return Err(RiskError::InvalidOrder {
                 reason: "Quantity must be positive".to_string(),
             });
@@ -117,17 +452,32 @@ impl RiskManager {
             });
         }
 
+        let value = match order.quote_order_qty {
+            Some(quote_qty) => quote_qty,
+            None => order.quantity * order.price.unwrap_or(current_price),
+        };
+        let min_notional = self.min_order_notional_for(&quote_balance.asset);
+        if value < min_notional {
+            return Err(RiskError::BelowMinNotional {
+                value,
+                min: min_notional,
+            });
+        }
+
         Ok(())
     }
 
+    /// Quantity worth `risk_pct` of `balance` at `price`, capped at
+    /// `symbol`'s `max_position_pct`.
     pub fn calculate_position_size(
         &self,
+        symbol: &str,
         balance: Decimal,
         risk_pct: Decimal,
         price: Decimal,
     ) -> Decimal {
-        let effective_risk_pct = risk_pct.min(self.max_position_pct);
-        let position_value = balance * effective_risk_pct / dec!(100);
+        let effective_risk_pct = risk_pct.min(self.max_position_pct_for(symbol));
+        let position_value = self.reserve_fee(balance * effective_risk_pct / dec!(100));
         let quantity = position_value / price;
 
         debug!(
@@ -138,49 +488,389 @@ impl RiskManager {
         quantity
     }
 
-    pub fn record_trade_result(&self, pnl_pct: Decimal) {
+    /// Quantity such that a stop `atr_multiplier` × `atr` below `price`
+    /// loses `risk_pct` of `balance`, so volatile symbols get smaller
+    /// positions. Still capped at `symbol`'s `max_position_pct` of the
+    /// balance.
+    pub fn calculate_position_size_atr(
+        &self,
+        symbol: &str,
+        balance: Decimal,
+        risk_pct: Decimal,
+        price: Decimal,
+        atr: Decimal,
+        atr_multiplier: Decimal,
+    ) -> Decimal {
+        let stop_distance = atr * atr_multiplier;
+        if stop_distance <= dec!(0) {
+            return dec!(0);
+        }
+
+        let risk_amount = balance * risk_pct / dec!(100);
+        let max_pct = self.max_position_pct_for(symbol);
+        let max_quantity = self.reserve_fee(balance * max_pct / dec!(100)) / price;
+        let quantity = (risk_amount / stop_distance).min(max_quantity);
+
+        debug!(
+            "ATR position size: balance={}, risk_pct={}, atr={}, stop_distance={}, quantity={}",
+            balance, risk_pct, atr, stop_distance, quantity
+        );
+
+        quantity
+    }
+
+    /// Order value that, with its fee on top, spends exactly `budget`.
+    fn reserve_fee(&self, budget: Decimal) -> Decimal {
+        budget / (dec!(1) + self.fee_rate)
+    }
+
+    /// `pnl_pct` of a round trip after the entry and exit fees, both as a
+    /// percentage of the entry value.
+    pub fn net_pnl_pct(&self, pnl_pct: Decimal) -> Decimal {
+        // Entry fee is fee_rate of the entry value, exit fee fee_rate of the
+        // exit value, which is (100 + pnl_pct)% of it
+        pnl_pct - self.fee_rate * (dec!(200) + pnl_pct)
+    }
+
+    /// Records a closed trade from its PnL before fees, returning the PnL
+    /// after them that counts toward the daily loss and performance.
+    pub fn record_trade_result(&self, pnl_pct: Decimal) -> Decimal {
+        let pnl_pct = self.net_pnl_pct(pnl_pct);
+        self.performance.record(pnl_pct);
+
         let mut daily_loss = self.current_daily_loss_pct.write().unwrap();
 
         if pnl_pct < dec!(0) {
             *daily_loss += pnl_pct.abs();
             warn!("Trade loss recorded: {}%. Total daily loss: {}%", pnl_pct, *daily_loss);
+            ::metrics::gauge!(DAILY_LOSS_PCT).set(decimal_to_f64(*daily_loss));
+        }
+
+        pnl_pct
+    }
+
+    /// Records the account's current value in the quote asset, raising the
+    /// high-water mark when it is a new peak.
+    pub fn update_equity(&self, equity: Decimal) {
+        *self.current_equity.write().unwrap() = equity;
+
+        let mut peak = self.equity_peak.write().unwrap();
+        if equity > *peak {
+            *peak = equity;
+        }
+        drop(peak);
+
+        let drawdown = self.current_drawdown_pct();
+        match self.max_drawdown_pct {
+            Some(max_drawdown) if drawdown >= max_drawdown => warn!(
+                "Equity {} is {}% below its peak, beyond the {}% drawdown limit",
+                equity,
+                drawdown.round_dp(2),
+                max_drawdown
+            ),
+            _ => debug!("Equity {}, drawdown {}%", equity, drawdown.round_dp(2)),
         }
     }
 
-    pub fn increment_positions(&self) {
-        self.current_open_positions.fetch_add(1, Ordering::SeqCst);
+    /// Percentage the current equity sits below its high-water mark, or 0
+    /// before any equity has been recorded.
+    pub fn current_drawdown_pct(&self) -> Decimal {
+        let peak = *self.equity_peak.read().unwrap();
+        if peak <= dec!(0) {
+            return dec!(0);
+        }
+
+        let equity = *self.current_equity.read().unwrap();
+        ((peak - equity) / peak * dec!(100)).max(dec!(0))
+    }
+
+    /// Records a new position. A fill on a symbol that already has an open
+    /// position adds a leg to it, moving its entry to the average price,
+    /// rather than counting as another position.
+    pub fn open_position(&self, position: Position) {
+        let mut positions = self.positions.write().unwrap();
+
+        match positions.get_mut(&position.symbol) {
+            Some(existing) => {
+                existing.add_fill(position.entry_price, position.quantity);
+                debug!(
+                    "Added {} to {} position, now {} in {} legs",
+                    position.quantity,
+                    position.symbol,
+                    existing.quantity,
+                    existing.leg_count()
+                );
+            }
+            None => {
+                debug!(
+                    "Opened {} position: {} @ {}",
+                    position.symbol, position.quantity, position.entry_price
+                );
+                positions.insert(position.symbol.clone(), position);
+            }
+        }
+
+        ::metrics::gauge!(OPEN_POSITIONS).set(positions.len() as f64);
     }
 
-    pub fn decrement_positions(&self) {
-        self.current_open_positions.fetch_sub(1, Ordering::SeqCst);
+    pub fn close_position(&self, symbol: &str) -> Option<Position> {
+        let mut positions = self.positions.write().unwrap();
+        let closed = positions.remove(symbol);
+        if closed.is_some() {
+            debug!("Closed {} position", symbol);
+        }
+
+        ::metrics::gauge!(OPEN_POSITIONS).set(positions.len() as f64);
+        closed
+    }
+
+    /// Takes a sold `quantity` off the `symbol` position, newest legs first,
+    /// and returns the part that was sold. The position closes once no legs
+    /// remain.
+    pub fn reduce_position(&self, symbol: &str, quantity: Decimal) -> Option<Position> {
+        let mut positions = self.positions.write().unwrap();
+        let position = positions.get_mut(symbol)?;
+
+        let sold_quantity = quantity.min(position.quantity);
+        let mut sold = position.clone();
+        sold.quantity = sold_quantity;
+        sold.legs = vec![sold_quantity];
+
+        position.quantity -= sold_quantity;
+        let mut remaining = sold_quantity;
+        while let Some(leg) = position.legs.last_mut() {
+            if remaining <= dec!(0) {
+                break;
+            }
+            if *leg <= remaining {
+                remaining -= *leg;
+                position.legs.pop();
+            } else {
+                *leg -= remaining;
+                remaining = dec!(0);
+            }
+        }
+
+        if position.legs.is_empty() || position.quantity <= dec!(0) {
+            positions.remove(symbol);
+            debug!("Closed {} position", symbol);
+        } else {
+            debug!(
+                "Reduced {} position by {}, now {} in {} legs",
+                symbol,
+                sold_quantity,
+                position.quantity,
+                position.leg_count()
+            );
+        }
+
+        ::metrics::gauge!(OPEN_POSITIONS).set(positions.len() as f64);
+        Some(sold)
+    }
+
+    pub fn position(&self, symbol: &str) -> Option<Position> {
+        self.positions.read().unwrap().get(symbol).cloned()
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.positions.read().unwrap().values().cloned().collect()
+    }
+
+    /// Total unrealized PnL in quote currency across all open positions.
+    /// Positions without a price in `prices` are left out of the sum.
+    pub fn unrealized_pnl(&self, prices: &HashMap<String, Decimal>) -> Decimal {
+        self.positions
+            .read()
+            .unwrap()
+            .values()
+            .filter_map(|p| prices.get(&p.symbol).map(|price| p.unrealized_pnl(*price)))
+            .sum()
+    }
+
+    /// Moves each position's high-water mark to the latest price and returns
+    /// the symbols whose trailing stop has been hit, so they can be sold.
+    pub fn update_trailing_stops(&self, prices: &HashMap<String, Decimal>) -> Vec<String> {
+        let trailing_stop = match self.trailing_stop {
+            Some(ts) => ts,
+            None => return Vec::new(),
+        };
+
+        let mut positions = self.positions.write().unwrap();
+        let mut triggered = Vec::new();
+
+        for position in positions.values_mut() {
+            let price = match prices.get(&position.symbol) {
+                Some(price) => *price,
+                None => continue,
+            };
+
+            position.peak_price = match position.side {
+                OrderSide::Buy => position.peak_price.max(price),
+                OrderSide::Sell => position.peak_price.min(price),
+            };
+
+            let stop = match Self::trailing_stop_for(position, trailing_stop) {
+                Some(stop) => stop,
+                None => continue,
+            };
+
+            let hit = match position.side {
+                OrderSide::Buy => price <= stop,
+                OrderSide::Sell => price >= stop,
+            };
+            if hit {
+                info!(
+                    "Trailing stop hit for {}: price {} crossed {} (peak {})",
+                    position.symbol, price, stop, position.peak_price
+                );
+                triggered.push(position.symbol.clone());
+            }
+        }
+
+        triggered.sort();
+        triggered
+    }
+
+    /// Current trailing stop level for `symbol`, if the stop has activated.
+    pub fn trailing_stop_price(&self, symbol: &str) -> Option<Decimal> {
+        let trailing_stop = self.trailing_stop?;
+        let positions = self.positions.read().unwrap();
+        Self::trailing_stop_for(positions.get(symbol)?, trailing_stop)
+    }
+
+    fn trailing_stop_for(position: &Position, trailing_stop: TrailingStop) -> Option<Decimal> {
+        if position.pnl_pct(position.peak_price) < trailing_stop.activation_pct {
+            return None;
+        }
+
+        let stop = match position.side {
+            OrderSide::Buy => position.peak_price * (dec!(100) - trailing_stop.trail_pct),
+            OrderSide::Sell => position.peak_price * (dec!(100) + trailing_stop.trail_pct),
+        };
+        Some(stop / dec!(100))
+    }
+
+    pub fn state(&self) -> RiskState {
+        let mut positions = self.positions();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        RiskState {
+            saved_at: self.clock.now(),
+            daily_loss_pct: self.current_daily_loss(),
+            open_positions: positions.len() as u32,
+            equity_peak: *self.equity_peak.read().unwrap(),
+            current_equity: *self.current_equity.read().unwrap(),
+            positions,
+        }
+    }
+
+    /// Writes `state()` to `path` as JSON. The file is replaced in one
+    /// rename, so a crash mid-write leaves the previous state intact.
+    pub fn save_state(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(&self.state())?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Replaces the tracked positions, daily loss and equity marks with the
+    /// ones saved at `path`. The daily loss is only kept if no midnight in
+    /// `daily_reset_tz` has passed since it was saved.
+    pub fn load_state(&self, path: &Path, daily_reset_tz: Tz) -> anyhow::Result<RiskState> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let state: RiskState = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid state file {}", path.display()))?;
+
+        let daily_loss_pct = if next_midnight(state.saved_at, daily_reset_tz) > self.clock.now() {
+            state.daily_loss_pct
+        } else {
+            info!(
+                "Not restoring the {}% daily loss saved at {}, a daily reset has passed since",
+                state.daily_loss_pct, state.saved_at
+            );
+            dec!(0)
+        };
+        *self.current_daily_loss_pct.write().unwrap() = daily_loss_pct;
+        *self.equity_peak.write().unwrap() = state.equity_peak;
+        *self.current_equity.write().unwrap() = state.current_equity;
+
+        let mut positions = self.positions.write().unwrap();
+        *positions = state
+            .positions
+            .iter()
+            .map(|position| (position.symbol.clone(), position.clone()))
+            .collect();
+
+        ::metrics::gauge!(OPEN_POSITIONS).set(positions.len() as f64);
+        ::metrics::gauge!(DAILY_LOSS_PCT).set(decimal_to_f64(daily_loss_pct));
+        Ok(state)
     }
 
     pub fn reset_daily_stats(&self) {
         let mut daily_loss = self.current_daily_loss_pct.write().unwrap();
         *daily_loss = dec!(0);
+        ::metrics::gauge!(DAILY_LOSS_PCT).set(0.0);
         debug!("Daily stats reset");
     }
 
+    /// Results of every trade recorded since startup.
+    pub fn performance(&self) -> PerformanceSummary {
+        self.performance.summary()
+    }
+
+    pub fn max_daily_loss_pct(&self) -> Decimal {
+        self.max_daily_loss_pct
+    }
+
     pub fn current_daily_loss(&self) -> Decimal {
         *self.current_daily_loss_pct.read().unwrap()
     }
 
     pub fn open_positions_count(&self) -> u32 {
-        self.current_open_positions.load(Ordering::SeqCst)
+        self.positions.read().unwrap().len() as u32
     }
 
+    /// Whether new positions may be opened. Sells are allowed regardless.
     pub fn can_trade(&self) -> bool {
-        let daily_loss = self.current_daily_loss_pct.read().unwrap();
-        let positions = self.current_open_positions.load(Ordering::SeqCst);
+        self.within_loss_limits() && self.open_positions_count() < self.max_open_positions
+    }
+
+    /// Whether `symbol` may be bought: as `can_trade`, except that adding a
+    /// leg to its open position is allowed at the open positions limit.
+    pub fn can_buy(&self, symbol: &str) -> bool {
+        self.within_loss_limits() && self.opens_within_position_limit(symbol)
+    }
+
+    fn within_loss_limits(&self) -> bool {
+        let within_drawdown = self
+            .max_drawdown_pct
+            .is_none_or(|max| self.current_drawdown_pct() < max);
 
-        *daily_loss < self.max_daily_loss_pct && positions < self.max_open_positions
+        *self.current_daily_loss_pct.read().unwrap() < self.max_daily_loss_pct && within_drawdown
     }
+
+    /// Whether a buy of `symbol` stays within the open positions limit.
+    fn opens_within_position_limit(&self, symbol: &str) -> bool {
+        let positions = self.positions.read().unwrap();
+        positions.contains_key(symbol) || (positions.len() as u32) < self.max_open_positions
+    }
+}
+
+fn decimal_to_f64(value: Decimal) -> f64 {
+    value.try_into().unwrap_or(0.0)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn create_test_position(symbol: &str, entry_price: Decimal, quantity: Decimal) -> Position {
+        Position::new(symbol, entry_price, quantity, OrderSide::Buy, 0)
+    }
+
     fn create_test_balance(free: &str) -> Balance {
         Balance {
             asset: "USDT".to_string(),
@@ -193,7 +883,7 @@ mod tests {
     fn test_position_size_calculation() {
         let rm = RiskManager::new(dec!(2), dec!(5), 3);
 
-        let size = rm.calculate_position_size(dec!(1000), dec!(2), dec!(50));
+        let size = rm.calculate_position_size("BTCUSDT", dec!(1000), dec!(2), dec!(50));
         assert_eq!(size, dec!(0.4)); // 2% of 1000 = 20, 20/50 = 0.4
     }
 
@@ -202,10 +892,104 @@ mod tests {
         let rm = RiskManager::new(dec!(2), dec!(5), 3);
 
         // Request 10% but max is 2%
-        let size = rm.calculate_position_size(dec!(1000), dec!(10), dec!(50));
+        let size = rm.calculate_position_size("BTCUSDT", dec!(1000), dec!(10), dec!(50));
         assert_eq!(size, dec!(0.4)); // Capped at 2%
     }
 
+    #[test]
+    fn test_position_size_reserves_fee() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+        let with_fee = RiskManager::new(dec!(2), dec!(5), 3).with_fee_rate(dec!(0.001));
+
+        let baseline = rm.calculate_position_size("BTCUSDT", dec!(1000), dec!(2), dec!(50));
+        let size = with_fee.calculate_position_size("BTCUSDT", dec!(1000), dec!(2), dec!(50));
+
+        // The 20 USDT budget covers both the order and its 0.1% fee
+        assert!(size < baseline);
+        assert_eq!((size * dec!(50) * dec!(1.001)).round_dp(10), dec!(20));
+
+        let size = with_fee.calculate_position_size_atr(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(1),
+            dec!(1000),
+            dec!(50),
+            dec!(2),
+        );
+        assert_eq!((size * dec!(1000) * dec!(1.001)).round_dp(10), dec!(200));
+    }
+
+    #[test]
+    fn test_trade_result_pays_round_trip_fees() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+        let with_fee = RiskManager::new(dec!(2), dec!(5), 3).with_fee_rate(dec!(0.001));
+
+        assert_eq!(rm.record_trade_result(dec!(2)), dec!(2));
+        // Bought 100 for a 0.1 fee, sold at 102 for a 0.102 fee
+        assert_eq!(with_fee.record_trade_result(dec!(2)), dec!(1.798));
+        assert_eq!(with_fee.performance().total_pnl_pct, dec!(1.798));
+
+        // A flat trade loses both fees and counts toward the daily loss
+        assert_eq!(with_fee.record_trade_result(dec!(0)), dec!(-0.2));
+        assert_eq!(with_fee.current_daily_loss(), dec!(0.2));
+        assert_eq!(rm.record_trade_result(dec!(0)), dec!(0));
+        assert_eq!(rm.current_daily_loss(), dec!(0));
+    }
+
+    #[test]
+    fn test_atr_position_size_risks_fixed_fraction() {
+        let rm = RiskManager::new(dec!(20), dec!(5), 3);
+
+        // Risking 1% of 10000 = 100 on a stop 2 x 50 = 100 away: 100 / 100 = 1
+        let size = rm.calculate_position_size_atr(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(1),
+            dec!(1000),
+            dec!(50),
+            dec!(2),
+        );
+        assert_eq!(size, dec!(1));
+
+        // Twice the volatility, half the position
+        let size = rm.calculate_position_size_atr(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(1),
+            dec!(1000),
+            dec!(100),
+            dec!(2),
+        );
+        assert_eq!(size, dec!(0.5));
+    }
+
+    #[test]
+    fn test_atr_position_size_capped_at_max() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+
+        // Uncapped would be 1 (1000 USDT, 10%); 2% of 10000 at 1000 is 0.2
+        let size = rm.calculate_position_size_atr(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(1),
+            dec!(1000),
+            dec!(50),
+            dec!(2),
+        );
+        assert_eq!(size, dec!(0.2));
+
+        // No volatility data to size from
+        let size = rm.calculate_position_size_atr(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(1),
+            dec!(1000),
+            dec!(0),
+            dec!(2),
+        );
+        assert_eq!(size, dec!(0));
+    }
+
     #[test]
     fn test_validate_order_too_large() {
         let rm = RiskManager::new(dec!(2), dec!(5), 3);
@@ -229,6 +1013,177 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_validate_quote_order_uses_quote_amount() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+        let balance = create_test_balance("1000");
+
+        let order = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(20));
+        assert!(rm.validate_order(&order, &balance, dec!(50000)).is_ok());
+
+        let order = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(21));
+        assert!(matches!(
+            rm.validate_order(&order, &balance, dec!(50000)),
+            Err(RiskError::PositionTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_order_min_notional() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_min_order_notional(dec!(10));
+        let balance = create_test_balance("1000");
+
+        // 0.00019 * 50000 = 9.5 USDT
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.00019));
+        assert!(matches!(
+            rm.validate_order(&order, &balance, dec!(50000)),
+            Err(RiskError::BelowMinNotional { value, min }) if value == dec!(9.5) && min == dec!(10)
+        ));
+
+        // 0.00021 * 50000 = 10.5 USDT
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.00021));
+        assert!(rm.validate_order(&order, &balance, dec!(50000)).is_ok());
+
+        // Sells are held to the same minimum
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.00019));
+        assert!(matches!(
+            rm.validate_order(&order, &balance, dec!(50000)),
+            Err(RiskError::BelowMinNotional { .. })
+        ));
+
+        let order = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(9.99));
+        assert!(matches!(
+            rm.validate_order(&order, &balance, dec!(50000)),
+            Err(RiskError::BelowMinNotional { .. })
+        ));
+    }
+
+    #[test]
+    fn test_absolute_cap_binds_before_percentage_cap() {
+        // 2% of 100000 allows 2000, the absolute cap only 500
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_max_order_notional(dec!(500));
+        let balance = create_test_balance("100000");
+
+        let order = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(600));
+        assert!(matches!(
+            rm.validate_order(&order, &balance, dec!(50000)),
+            Err(RiskError::ExceedsMaxNotional { value, max }) if value == dec!(600) && max == dec!(500)
+        ));
+
+        let order = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(500));
+        assert!(rm.validate_order(&order, &balance, dec!(50000)).is_ok());
+
+        // On a small balance the percentage cap is the tighter one
+        let balance = create_test_balance("10000");
+        let order = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(300));
+        assert!(matches!(
+            rm.validate_order(&order, &balance, dec!(50000)),
+            Err(RiskError::PositionTooLarge { max_allowed, .. }) if max_allowed == dec!(200)
+        ));
+    }
+
+    #[test]
+    fn test_min_notional_applies_only_to_stable_quotes() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_min_order_notional(dec!(10));
+        let order = OrderRequest::market("BTCFDUSD", OrderSide::Buy, dec!(0.00019));
+        let fdusd = Balance {
+            asset: "FDUSD".to_string(),
+            ..create_test_balance("1000")
+        };
+        assert!(matches!(
+            rm.validate_order(&order, &fdusd, dec!(50000)),
+            Err(RiskError::BelowMinNotional { .. })
+        ));
+
+        // 0.018 BTC is far below 10 but worth far more than 10 USD
+        let order = OrderRequest::market("ETHBTC", OrderSide::Buy, dec!(0.3));
+        let btc = Balance {
+            asset: "BTC".to_string(),
+            ..create_test_balance("1")
+        };
+        assert_eq!(rm.min_order_notional_for("BTC"), dec!(0));
+        assert!(rm.validate_order(&order, &btc, dec!(0.06)).is_ok());
+
+        let rm = rm.with_stable_quote_assets(HashSet::from(["USDT".to_string()]));
+        assert_eq!(rm.min_order_notional_for("FDUSD"), dec!(0));
+        assert_eq!(rm.min_order_notional_for("USDT"), dec!(10));
+    }
+
+    #[test]
+    fn test_symbol_override_tightens_only_that_symbol() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_symbol_overrides(HashMap::from([(
+            "DOGEUSDT".to_string(),
+            SymbolRiskOverride {
+                max_position_pct: Some(dec!(0.5)),
+                max_position_value: None,
+            },
+        )]));
+        let balance = create_test_balance("1000");
+
+        // 20 USDT: within the global 2% but over DOGE's 0.5%
+        let doge = OrderRequest::market("DOGEUSDT", OrderSide::Buy, dec!(200));
+        let result = rm.validate_order(&doge, &balance, dec!(0.1));
+        assert!(matches!(
+            result,
+            Err(RiskError::PositionTooLarge { max_pct, .. }) if max_pct == dec!(0.5)
+        ));
+
+        let btc = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.0004));
+        assert!(rm.validate_order(&btc, &balance, dec!(50000)).is_ok());
+
+        assert_eq!(rm.max_position_pct_for("DOGEUSDT"), dec!(0.5));
+        assert_eq!(rm.max_position_pct_for("BTCUSDT"), dec!(2));
+    }
+
+    #[test]
+    fn test_position_size_capped_at_symbol_override() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_symbol_overrides(HashMap::from([(
+            "ETHUSDT".to_string(),
+            SymbolRiskOverride {
+                max_position_pct: Some(dec!(5)),
+                max_position_value: None,
+            },
+        )]));
+
+        // ETH may take 5% of 1000 = 50, above the global 2%
+        let size = rm.calculate_position_size("ETHUSDT", dec!(1000), dec!(10), dec!(50));
+        assert_eq!(size, dec!(1));
+        let size = rm.calculate_position_size("BTCUSDT", dec!(1000), dec!(10), dec!(50));
+        assert_eq!(size, dec!(0.4));
+
+        // Uncapped ATR size would be 1 (10%); 5% of 10000 at 1000 is 0.5
+        let size = rm.calculate_position_size_atr(
+            "ETHUSDT",
+            dec!(10000),
+            dec!(1),
+            dec!(1000),
+            dec!(50),
+            dec!(2),
+        );
+        assert_eq!(size, dec!(0.5));
+    }
+
+    #[test]
+    fn test_symbol_position_value_cap_counts_existing_position() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_symbol_overrides(HashMap::from([(
+            "ETHUSDT".to_string(),
+            SymbolRiskOverride {
+                max_position_pct: None,
+                max_position_value: Some(dec!(30)),
+            },
+        )]));
+        let balance = create_test_balance("1000");
+        let order = OrderRequest::market("ETHUSDT", OrderSide::Buy, dec!(0.01));
+
+        // 20 USDT order on its own fits the 30 USDT cap
+        assert!(rm.validate_order(&order, &balance, dec!(2000)).is_ok());
+
+        // With 20 USDT already held, another 20 would exceed it
+        rm.open_position(create_test_position("ETHUSDT", dec!(2000), dec!(0.01)));
+        let result = rm.validate_order(&order, &balance, dec!(2000));
+        assert!(matches!(result, Err(RiskError::SymbolLimitExceeded { .. })));
+    }
+
     #[test]
     fn test_daily_loss_tracking() {
         let rm = RiskManager::new(dec!(2), dec!(5), 3);
@@ -247,6 +1202,68 @@ mod tests {
         assert!(rm.can_trade());
     }
 
+    #[test]
+    fn test_drawdown_from_equity_peak_stops_trading() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_max_drawdown(dec!(10));
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.001));
+        let balance = create_test_balance("10000");
+
+        for equity in [dec!(10000), dec!(11000), dec!(12000), dec!(11500)] {
+            rm.update_equity(equity);
+        }
+        assert!(rm.can_trade());
+        assert!(rm.validate_order(&order, &balance, dec!(50000)).is_ok());
+
+        // 10% below the 12000 peak, though no single day lost 5%
+        rm.update_equity(dec!(10800));
+        assert_eq!(rm.current_drawdown_pct(), dec!(10));
+        assert!(!rm.can_trade());
+        let result = rm.validate_order(&order, &balance, dec!(50000));
+        assert!(matches!(
+            result,
+            Err(RiskError::MaxDrawdownExceeded { current_drawdown, max_drawdown })
+                if current_drawdown == dec!(10) && max_drawdown == dec!(10)
+        ));
+
+        // Sells that cut the exposure still go through
+        let sell = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.001));
+        assert!(rm.validate_order(&sell, &balance, dec!(50000)).is_ok());
+
+        // The daily reset does not lift it, a recovery does
+        rm.reset_daily_stats();
+        assert!(!rm.can_trade());
+        rm.update_equity(dec!(11000));
+        assert!(rm.can_trade());
+    }
+
+    #[test]
+    fn test_drawdown_disabled_by_default() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+
+        rm.update_equity(dec!(10000));
+        rm.update_equity(dec!(5000));
+
+        assert_eq!(rm.current_drawdown_pct(), dec!(50));
+        assert!(rm.can_trade());
+    }
+
+    #[test]
+    fn test_exit_prices() {
+        let rm =
+            RiskManager::new(dec!(2), dec!(5), 3).with_exit_levels(dec!(2), dec!(4), dec!(0.5));
+
+        let exits = rm.exit_prices(dec!(50000)).unwrap();
+        assert_eq!(exits.stop_price, dec!(49000)); // 2% below entry
+        assert_eq!(exits.stop_limit_price, dec!(48755)); // 0.5% below stop
+        assert_eq!(exits.take_profit_price, dec!(52000)); // 4% above entry
+    }
+
+    #[test]
+    fn test_exit_prices_disabled_by_default() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+        assert!(rm.exit_prices(dec!(50000)).is_none());
+    }
+
     #[test]
     fn test_position_count_tracking() {
         let rm = RiskManager::new(dec!(2), dec!(5), 2);
@@ -254,15 +1271,213 @@ mod tests {
         assert_eq!(rm.open_positions_count(), 0);
         assert!(rm.can_trade());
 
-        rm.increment_positions();
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.1)));
         assert_eq!(rm.open_positions_count(), 1);
         assert!(rm.can_trade());
 
-        rm.increment_positions();
+        rm.open_position(create_test_position("ETHUSDT", dec!(3000), dec!(1)));
         assert_eq!(rm.open_positions_count(), 2);
         assert!(!rm.can_trade());
 
-        rm.decrement_positions();
+        let closed = rm.close_position("BTCUSDT").unwrap();
+        assert_eq!(closed.entry_price, dec!(50000));
+        assert!(rm.close_position("BTCUSDT").is_none());
         assert!(rm.can_trade());
     }
+
+    #[test]
+    fn test_adding_to_an_open_position_is_allowed_at_the_position_limit() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 1);
+        let balance = create_test_balance("10000");
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.1)));
+        assert!(!rm.can_trade());
+
+        let add = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.001));
+        assert!(rm.can_buy("BTCUSDT"));
+        assert!(rm.validate_order(&add, &balance, dec!(50000)).is_ok());
+
+        let open = OrderRequest::market("ETHUSDT", OrderSide::Buy, dec!(0.01));
+        assert!(!rm.can_buy("ETHUSDT"));
+        assert!(matches!(
+            rm.validate_order(&open, &balance, dec!(3000)),
+            Err(RiskError::MaxPositionsReached { max: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_open_position_same_symbol_accumulates() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.1)));
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.2)));
+
+        assert_eq!(rm.open_positions_count(), 1);
+        assert_eq!(rm.position("BTCUSDT").unwrap().quantity, dec!(0.3));
+    }
+
+    #[test]
+    fn test_state_round_trips_through_file() {
+        let path =
+            std::env::temp_dir().join(format!("cryptobot-state-{}.json", std::process::id()));
+        let clock = Arc::new(crate::clock::MockClock::new(
+            "2024-03-01T12:00:00Z".parse().unwrap(),
+        ));
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_clock(clock.clone());
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.1)));
+        rm.open_position(create_test_position("BTCUSDT", dec!(60000), dec!(0.1)));
+        rm.open_position(create_test_position("ETHUSDT", dec!(3000), dec!(2)));
+        rm.record_trade_result(dec!(-1.5));
+        rm.update_equity(dec!(12000));
+        rm.update_equity(dec!(11000));
+
+        rm.save_state(&path).unwrap();
+
+        let restored = RiskManager::new(dec!(2), dec!(5), 3).with_clock(clock);
+        let loaded = restored.load_state(&path, Tz::UTC).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, rm.state());
+        assert_eq!(restored.state(), rm.state());
+        assert_eq!(loaded.open_positions, 2);
+        assert_eq!(restored.open_positions_count(), 2);
+        assert_eq!(restored.current_daily_loss(), dec!(1.5));
+        assert_eq!(restored.current_drawdown_pct(), rm.current_drawdown_pct());
+
+        let btc = restored.position("BTCUSDT").unwrap();
+        assert_eq!(btc.entry_price, dec!(55000));
+        assert_eq!(btc.legs, vec![dec!(0.1), dec!(0.1)]);
+
+        assert!(restored.load_state(&path, Tz::UTC).is_err());
+    }
+
+    #[test]
+    fn test_state_from_before_the_daily_reset_drops_the_daily_loss() {
+        let path =
+            std::env::temp_dir().join(format!("cryptobot-stale-state-{}.json", std::process::id()));
+        let clock = Arc::new(crate::clock::MockClock::new(
+            "2024-03-01T22:00:00Z".parse().unwrap(),
+        ));
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_clock(clock.clone());
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.1)));
+        rm.record_trade_result(dec!(-4));
+        rm.save_state(&path).unwrap();
+
+        // 23:30 UTC is still the same UTC day, but already past midnight in Berlin
+        clock.set("2024-03-01T23:30:00Z".parse().unwrap());
+        let restored = RiskManager::new(dec!(2), dec!(5), 3).with_clock(clock.clone());
+        restored.load_state(&path, Tz::UTC).unwrap();
+        assert_eq!(restored.current_daily_loss(), dec!(4));
+
+        let restored = RiskManager::new(dec!(2), dec!(5), 3).with_clock(clock);
+        let loaded = restored.load_state(&path, Tz::Europe__Berlin).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.daily_loss_pct, dec!(4));
+        assert_eq!(restored.current_daily_loss(), dec!(0));
+        assert!(restored.can_trade());
+        assert_eq!(restored.open_positions_count(), 1);
+    }
+
+    #[test]
+    fn test_reduce_position_peels_newest_legs() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+
+        rm.open_position(create_test_position("BTCUSDT", dec!(40000), dec!(0.1)));
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.2)));
+        rm.open_position(create_test_position("BTCUSDT", dec!(60000), dec!(0.1)));
+        let position = rm.position("BTCUSDT").unwrap();
+        assert_eq!(position.legs, vec![dec!(0.1), dec!(0.2), dec!(0.1)]);
+        assert_eq!(position.entry_price, dec!(50000));
+
+        let sold = rm.reduce_position("BTCUSDT", dec!(0.15)).unwrap();
+        assert_eq!(sold.quantity, dec!(0.15));
+        assert_eq!(sold.entry_price, dec!(50000));
+        let position = rm.position("BTCUSDT").unwrap();
+        assert_eq!(position.legs, vec![dec!(0.1), dec!(0.15)]);
+        assert_eq!(position.quantity, dec!(0.25));
+
+        rm.reduce_position("BTCUSDT", dec!(0.25)).unwrap();
+        assert!(rm.position("BTCUSDT").is_none());
+        assert!(rm.reduce_position("BTCUSDT", dec!(0.1)).is_none());
+    }
+
+    #[test]
+    fn test_unrealized_pnl_aggregation() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+        rm.open_position(create_test_position("BTCUSDT", dec!(50000), dec!(0.1)));
+        rm.open_position(create_test_position("ETHUSDT", dec!(3000), dec!(2)));
+
+        let prices = HashMap::from([
+            ("BTCUSDT".to_string(), dec!(51000)),
+            ("ETHUSDT".to_string(), dec!(2900)),
+        ]);
+
+        // +100 on BTC, -200 on ETH
+        assert_eq!(rm.unrealized_pnl(&prices), dec!(-100));
+    }
+
+    fn price(symbol: &str, price: Decimal) -> HashMap<String, Decimal> {
+        HashMap::from([(symbol.to_string(), price)])
+    }
+
+    #[test]
+    fn test_trailing_stop_ratchets_up_then_triggers() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_trailing_stop(TrailingStop {
+            activation_pct: dec!(2),
+            trail_pct: dec!(1),
+        });
+        rm.open_position(create_test_position("BTCUSDT", dec!(100), dec!(1)));
+
+        // Not yet 2% in profit: no stop
+        assert!(rm
+            .update_trailing_stops(&price("BTCUSDT", dec!(101)))
+            .is_empty());
+        assert_eq!(rm.trailing_stop_price("BTCUSDT"), None);
+
+        // Activates at 102 and follows the high-water mark up
+        assert!(rm
+            .update_trailing_stops(&price("BTCUSDT", dec!(102)))
+            .is_empty());
+        assert_eq!(rm.trailing_stop_price("BTCUSDT"), Some(dec!(100.98)));
+
+        assert!(rm
+            .update_trailing_stops(&price("BTCUSDT", dec!(110)))
+            .is_empty());
+        assert_eq!(rm.trailing_stop_price("BTCUSDT"), Some(dec!(108.9)));
+
+        // A dip that stays above the stop leaves it where it was
+        assert!(rm
+            .update_trailing_stops(&price("BTCUSDT", dec!(109)))
+            .is_empty());
+        assert_eq!(rm.trailing_stop_price("BTCUSDT"), Some(dec!(108.9)));
+
+        // Pullback through the stop triggers it
+        let triggered = rm.update_trailing_stops(&price("BTCUSDT", dec!(108.5)));
+        assert_eq!(triggered, vec!["BTCUSDT".to_string()]);
+    }
+
+    #[test]
+    fn test_trailing_stop_only_checks_priced_positions() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3).with_trailing_stop(TrailingStop {
+            activation_pct: dec!(0),
+            trail_pct: dec!(1),
+        });
+        rm.open_position(create_test_position("BTCUSDT", dec!(100), dec!(1)));
+        rm.open_position(create_test_position("ETHUSDT", dec!(100), dec!(1)));
+
+        let triggered = rm.update_trailing_stops(&price("ETHUSDT", dec!(98)));
+        assert_eq!(triggered, vec!["ETHUSDT".to_string()]);
+        assert_eq!(rm.position("BTCUSDT").unwrap().peak_price, dec!(100));
+    }
+
+    #[test]
+    fn test_no_trailing_stop_configured() {
+        let rm = RiskManager::new(dec!(2), dec!(5), 3);
+        rm.open_position(create_test_position("BTCUSDT", dec!(100), dec!(1)));
+
+        assert!(rm
+            .update_trailing_stops(&price("BTCUSDT", dec!(50)))
+            .is_empty());
+        assert_eq!(rm.trailing_stop_price("BTCUSDT"), None);
+    }
 }
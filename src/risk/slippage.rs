@@ -0,0 +1,76 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::position_sizing::RiskError;
+
+/// How far, in percent, the price has moved from `signal_price` (the price
+/// a signal was generated at) to `current_price`. Fails when the move in
+/// either direction exceeds `tolerance_pct`.
+pub fn check_slippage(
+    signal_price: Decimal,
+    current_price: Decimal,
+    tolerance_pct: Decimal,
+) -> Result<Decimal, RiskError> {
+    if signal_price <= dec!(0) {
+        return Err(RiskError::InvalidOrder {
+            reason: "Signal price must be positive".to_string(),
+        });
+    }
+
+    let slippage_pct = ((current_price - signal_price) / signal_price * dec!(100)).abs();
+    if slippage_pct > tolerance_pct {
+        return Err(RiskError::SlippageExceeded {
+            signal_price,
+            current_price,
+            slippage_pct,
+            tolerance_pct,
+        });
+    }
+
+    Ok(slippage_pct)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within_tolerance() {
+        assert_eq!(
+            check_slippage(dec!(100), dec!(100.4), dec!(0.5)).unwrap(),
+            dec!(0.4)
+        );
+        assert_eq!(
+            check_slippage(dec!(100), dec!(99.5), dec!(0.5)).unwrap(),
+            dec!(0.5)
+        );
+        assert_eq!(
+            check_slippage(dec!(100), dec!(100), dec!(0)).unwrap(),
+            dec!(0)
+        );
+    }
+
+    #[test]
+    fn test_outside_tolerance() {
+        match check_slippage(dec!(100), dec!(101), dec!(0.5)) {
+            Err(RiskError::SlippageExceeded { slippage_pct, .. }) => {
+                assert_eq!(slippage_pct, dec!(1))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Moves against a sell count too
+        assert!(matches!(
+            check_slippage(dec!(100), dec!(98), dec!(0.5)),
+            Err(RiskError::SlippageExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_zero_signal_price() {
+        assert!(matches!(
+            check_slippage(dec!(0), dec!(100), dec!(0.5)),
+            Err(RiskError::InvalidOrder { .. })
+        ));
+    }
+}
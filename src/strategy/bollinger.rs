@@ -0,0 +1,168 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use tracing::debug;
+
+use crate::exchange::MarketData;
+
+use super::indicators::calculate_bollinger;
+use super::r#trait::{Signal, Strategy};
+
+/// Mean reversion on Bollinger Bands: buys a close below the lower band and
+/// sells a close above the upper band.
+pub struct BollingerStrategy {
+    period: usize,
+    num_std_dev: Decimal,
+}
+
+impl BollingerStrategy {
+    pub fn new(period: usize, num_std_dev: Decimal) -> Self {
+        assert!(period > 1, "Period must be greater than 1");
+        assert!(num_std_dev > Decimal::ZERO, "Band width must be positive");
+
+        Self {
+            period,
+            num_std_dev,
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy for BollingerStrategy {
+    fn name(&self) -> &str {
+        "Bollinger Bands"
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        let prices = market_data.close_prices();
+
+        if prices.len() < self.required_history() {
+            debug!(
+                "Insufficient data for Bollinger analysis: have {}, need {}",
+                prices.len(),
+                self.required_history()
+            );
+            return Signal::Hold;
+        }
+
+        let bands = match calculate_bollinger(&prices, self.period, self.num_std_dev) {
+            Some(bands) => bands,
+            None => return Signal::Hold,
+        };
+
+        // A perfectly flat market has no bands to break out of
+        let band_width = bands.upper - bands.middle;
+        if band_width == Decimal::ZERO {
+            return Signal::Hold;
+        }
+
+        let (lower, middle, upper) = (bands.lower, bands.middle, bands.upper);
+        let close = prices[prices.len() - 1];
+
+        debug!(
+            "Bollinger Analysis - Lower: {}, Middle: {}, Upper: {}, Close: {}",
+            lower, middle, upper, close
+        );
+
+        // Strength is how far outside the band the close is, in band widths
+        let strength = |distance: Decimal| (distance / band_width).min(Decimal::ONE);
+
+        if close < lower {
+            let strength = strength(lower - close);
+            debug!("Close below lower band! Strength: {}", strength);
+            return Signal::Buy { strength };
+        }
+
+        if close > upper {
+            let strength = strength(close - upper);
+            debug!("Close above upper band! Strength: {}", strength);
+            return Signal::Sell { strength };
+        }
+
+        Signal::Hold
+    }
+
+    fn required_history(&self) -> usize {
+        self.period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Kline;
+    use rust_decimal_macros::dec;
+
+    fn create_market_data(close_prices: &[u32]) -> MarketData {
+        let klines = close_prices
+            .iter()
+            .enumerate()
+            .map(|(i, price)| Kline {
+                open_time: i as u64 * 3600000,
+                open: price.to_string(),
+                high: price.to_string(),
+                low: price.to_string(),
+                close: price.to_string(),
+                volume: "100".to_string(),
+                close_time: (i as u64 + 1) * 3600000,
+                quote_asset_volume: "10000".to_string(),
+                number_of_trades: 100,
+                taker_buy_base_asset_volume: "50".to_string(),
+                taker_buy_quote_asset_volume: "5000".to_string(),
+            })
+            .collect();
+
+        MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: dec!(100),
+            klines,
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spike_above_upper_band_sells() {
+        let strategy = BollingerStrategy::new(10, dec!(2));
+        let market_data = create_market_data(&[100, 101, 99, 100, 101, 99, 100, 101, 99, 130]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Sell { .. }), "got {:?}", signal);
+    }
+
+    #[tokio::test]
+    async fn test_dip_below_lower_band_buys() {
+        let strategy = BollingerStrategy::new(10, dec!(2));
+        let market_data = create_market_data(&[100, 101, 99, 100, 101, 99, 100, 101, 99, 70]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Buy { .. }), "got {:?}", signal);
+    }
+
+    #[tokio::test]
+    async fn test_strength_grows_with_distance_outside_band() {
+        let strategy = BollingerStrategy::new(10, dec!(2));
+
+        let near = strategy
+            .analyze(&create_market_data(&[
+                100, 101, 99, 100, 101, 99, 100, 101, 99, 97,
+            ]))
+            .await;
+        let far = strategy
+            .analyze(&create_market_data(&[
+                100, 101, 99, 100, 101, 99, 100, 101, 99, 90,
+            ]))
+            .await;
+
+        assert!(matches!(near, Signal::Buy { .. }), "got {:?}", near);
+        assert!(far.strength() > near.strength(), "{:?} vs {:?}", near, far);
+    }
+
+    #[tokio::test]
+    async fn test_inside_bands_holds() {
+        let strategy = BollingerStrategy::new(10, dec!(2));
+        let market_data = create_market_data(&[100, 101, 99, 100, 101, 99, 100, 101, 99, 100]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Hold));
+    }
+}
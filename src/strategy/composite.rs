@@ -0,0 +1,284 @@
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use rust_decimal::Decimal;
+use tracing::debug;
+
+use crate::exchange::MarketData;
+
+use super::r#trait::{Signal, Strategy};
+
+/// How a `CompositeStrategy` turns its children's signals into one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CombinationMode {
+    /// Every child must agree; strength is the weakest child's.
+    Unanimous,
+    /// More than half of the children must agree; strength is the average
+    /// of the agreeing children.
+    Majority,
+    /// Averages strengths with buys positive and sells negative, acting once
+    /// the average reaches `threshold` in either direction.
+    WeightedAverage { threshold: Decimal },
+}
+
+/// Combines several strategies so a trade needs confirmation from more than
+/// one of them.
+pub struct CompositeStrategy {
+    name: String,
+    strategies: Vec<Box<dyn Strategy>>,
+    mode: CombinationMode,
+}
+
+impl CompositeStrategy {
+    pub fn new(strategies: Vec<Box<dyn Strategy>>, mode: CombinationMode) -> Self {
+        assert!(
+            !strategies.is_empty(),
+            "Composite needs at least one strategy"
+        );
+
+        let name = format!(
+            "Composite({})",
+            strategies
+                .iter()
+                .map(|s| s.name())
+                .collect::<Vec<_>>()
+                .join(" + ")
+        );
+
+        Self {
+            name,
+            strategies,
+            mode,
+        }
+    }
+
+    fn combine(&self, signals: &[Signal]) -> Signal {
+        let total = signals.len();
+        let buys: Vec<Decimal> = signals
+            .iter()
+            .filter(|s| matches!(s, Signal::Buy { .. }))
+            .map(Signal::strength)
+            .collect();
+        let sells: Vec<Decimal> = signals
+            .iter()
+            .filter(|s| matches!(s, Signal::Sell { .. }))
+            .map(Signal::strength)
+            .collect();
+
+        match self.mode {
+            CombinationMode::Unanimous => {
+                if buys.len() == total {
+                    Signal::Buy {
+                        strength: buys.iter().copied().min().unwrap_or_default(),
+                    }
+                } else if sells.len() == total {
+                    Signal::Sell {
+                        strength: sells.iter().copied().min().unwrap_or_default(),
+                    }
+                } else {
+                    Signal::Hold
+                }
+            }
+            CombinationMode::Majority => {
+                if buys.len() * 2 > total {
+                    Signal::Buy {
+                        strength: mean(&buys),
+                    }
+                } else if sells.len() * 2 > total {
+                    Signal::Sell {
+                        strength: mean(&sells),
+                    }
+                } else {
+                    Signal::Hold
+                }
+            }
+            CombinationMode::WeightedAverage { threshold } => {
+                let score = (buys.iter().sum::<Decimal>() - sells.iter().sum::<Decimal>())
+                    / Decimal::from(total);
+
+                if score > Decimal::ZERO && score >= threshold {
+                    Signal::Buy { strength: score }
+                } else if score < Decimal::ZERO && -score >= threshold {
+                    Signal::Sell { strength: -score }
+                } else {
+                    Signal::Hold
+                }
+            }
+        }
+    }
+}
+
+fn mean(values: &[Decimal]) -> Decimal {
+    values.iter().sum::<Decimal>() / Decimal::from(values.len())
+}
+
+#[async_trait]
+impl Strategy for CompositeStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        let signals = join_all(self.strategies.iter().map(|s| s.analyze(market_data))).await;
+
+        let signal = self.combine(&signals);
+        debug!(
+            "{} on {}: {:?} -> {:?}",
+            self.name, market_data.symbol, signals, signal
+        );
+        signal
+    }
+
+    /// Hands the whole batch to each child, so children that batch keep
+    /// doing so, then combines their signals symbol by symbol.
+    async fn analyze_batch(&self, data: &[MarketData]) -> Vec<Signal> {
+        let per_child = join_all(self.strategies.iter().map(|s| s.analyze_batch(data))).await;
+
+        data.iter()
+            .enumerate()
+            .map(|(i, market_data)| {
+                let signals: Vec<Signal> = per_child
+                    .iter()
+                    .map(|child| child.get(i).cloned().unwrap_or(Signal::Hold))
+                    .collect();
+                let signal = self.combine(&signals);
+                debug!(
+                    "{} on {}: {:?} -> {:?}",
+                    self.name, market_data.symbol, signals, signal
+                );
+                signal
+            })
+            .collect()
+    }
+
+    fn required_history(&self) -> usize {
+        self.strategies
+            .iter()
+            .map(|s| s.required_history())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    /// Always returns the same signal.
+    struct Fixed(Signal, usize);
+
+    #[async_trait]
+    impl Strategy for Fixed {
+        fn name(&self) -> &str {
+            "Fixed"
+        }
+
+        async fn analyze(&self, _market_data: &MarketData) -> Signal {
+            self.0.clone()
+        }
+
+        fn required_history(&self) -> usize {
+            self.1
+        }
+    }
+
+    fn children(signals: &[Signal]) -> Vec<Box<dyn Strategy>> {
+        signals
+            .iter()
+            .map(|s| Box::new(Fixed(s.clone(), 0)) as Box<dyn Strategy>)
+            .collect()
+    }
+
+    fn market_data() -> MarketData {
+        MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: dec!(100),
+            klines: Vec::new(),
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    fn buy(strength: Decimal) -> Signal {
+        Signal::Buy { strength }
+    }
+
+    fn sell(strength: Decimal) -> Signal {
+        Signal::Sell { strength }
+    }
+
+    #[tokio::test]
+    async fn test_unanimous_requires_every_child() {
+        let agreed = CompositeStrategy::new(
+            children(&[buy(dec!(0.8)), buy(dec!(0.4)), buy(dec!(0.6))]),
+            CombinationMode::Unanimous,
+        );
+        let signal = agreed.analyze(&market_data()).await;
+        assert!(matches!(signal, Signal::Buy { strength } if strength == dec!(0.4)));
+
+        let split = CompositeStrategy::new(
+            children(&[buy(dec!(0.8)), buy(dec!(0.4)), Signal::Hold]),
+            CombinationMode::Unanimous,
+        );
+        assert!(matches!(split.analyze(&market_data()).await, Signal::Hold));
+    }
+
+    #[tokio::test]
+    async fn test_majority_vote() {
+        let majority = CompositeStrategy::new(
+            children(&[sell(dec!(0.6)), sell(dec!(0.2)), buy(dec!(0.9))]),
+            CombinationMode::Majority,
+        );
+        let signal = majority.analyze(&market_data()).await;
+        assert!(matches!(signal, Signal::Sell { strength } if strength == dec!(0.4)));
+
+        // An even split is not a majority
+        let split = CompositeStrategy::new(
+            children(&[sell(dec!(0.6)), buy(dec!(0.9))]),
+            CombinationMode::Majority,
+        );
+        assert!(matches!(split.analyze(&market_data()).await, Signal::Hold));
+    }
+
+    #[tokio::test]
+    async fn test_weighted_average_thresholds_net_strength() {
+        let mode = CombinationMode::WeightedAverage {
+            threshold: dec!(0.3),
+        };
+
+        // (0.9 + 0.6 - 0.3) / 3 = 0.4
+        let strong = CompositeStrategy::new(
+            children(&[buy(dec!(0.9)), buy(dec!(0.6)), sell(dec!(0.3))]),
+            mode,
+        );
+        let signal = strong.analyze(&market_data()).await;
+        assert!(matches!(signal, Signal::Buy { strength } if strength == dec!(0.4)));
+
+        // (0.9 - 0.6 + 0) / 3 = 0.1
+        let weak = CompositeStrategy::new(
+            children(&[buy(dec!(0.9)), sell(dec!(0.6)), Signal::Hold]),
+            mode,
+        );
+        assert!(matches!(weak.analyze(&market_data()).await, Signal::Hold));
+
+        // (-0.9 - 0.6) / 2 = -0.75
+        let bearish = CompositeStrategy::new(children(&[sell(dec!(0.9)), sell(dec!(0.6))]), mode);
+        let signal = bearish.analyze(&market_data()).await;
+        assert!(matches!(signal, Signal::Sell { strength } if strength == dec!(0.75)));
+    }
+
+    #[test]
+    fn test_required_history_is_max_of_children() {
+        let composite = CompositeStrategy::new(
+            vec![
+                Box::new(Fixed(Signal::Hold, 26)),
+                Box::new(Fixed(Signal::Hold, 50)),
+                Box::new(Fixed(Signal::Hold, 20)),
+            ],
+            CombinationMode::Majority,
+        );
+
+        assert_eq!(composite.required_history(), 50);
+        assert_eq!(composite.name(), "Composite(Fixed + Fixed + Fixed)");
+    }
+}
@@ -0,0 +1,161 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tracing::debug;
+
+use super::r#trait::Signal;
+
+/// Golden/death cross detection for `MaCrossoverStrategy`, on whichever
+/// average it is fed.
+pub(super) struct Crossover {
+    short_period: usize,
+    long_period: usize,
+    min_buy_strength: Decimal,
+    min_sell_strength: Decimal,
+    /// Crosses that leave the averages within this percentage of each
+    /// other are treated as noise
+    min_separation_pct: Decimal,
+}
+
+impl Crossover {
+    pub(super) fn new(
+        short_period: usize,
+        long_period: usize,
+        min_signal_strength: Decimal,
+    ) -> Self {
+        assert!(
+            short_period < long_period,
+            "Short period must be less than long period"
+        );
+
+        Self {
+            short_period,
+            long_period,
+            min_buy_strength: min_signal_strength,
+            min_sell_strength: min_signal_strength,
+            min_separation_pct: Decimal::ZERO,
+        }
+    }
+
+    pub(super) fn set_min_separation_pct(&mut self, min_separation_pct: Decimal) {
+        self.min_separation_pct = min_separation_pct;
+    }
+
+    pub(super) fn set_min_strengths(
+        &mut self,
+        min_buy_strength: Decimal,
+        min_sell_strength: Decimal,
+    ) {
+        self.min_buy_strength = min_buy_strength;
+        self.min_sell_strength = min_sell_strength;
+    }
+
+    pub(super) fn required_history(&self) -> usize {
+        self.long_period + 1
+    }
+
+    /// The short and long averages over `prices` as of its last candle.
+    pub(super) fn averages(
+        &self,
+        prices: &[Decimal],
+        average: fn(&[Decimal], usize) -> Option<Decimal>,
+    ) -> Option<(Decimal, Decimal)> {
+        Some((
+            average(prices, self.short_period)?,
+            average(prices, self.long_period)?,
+        ))
+    }
+
+    /// Signal for the last candle of `prices`, comparing `average` over the
+    /// short and long periods against the candle before. `label` names the
+    /// average in debug logs.
+    pub(super) fn signal(
+        &self,
+        prices: &[Decimal],
+        average: fn(&[Decimal], usize) -> Option<Decimal>,
+        label: &str,
+    ) -> Signal {
+        if prices.len() < self.required_history() {
+            debug!(
+                "Insufficient data for {} analysis: have {}, need {}",
+                label,
+                prices.len(),
+                self.required_history()
+            );
+            return Signal::Hold;
+        }
+
+        // Calculate current averages
+        let short_avg = match average(prices, self.short_period) {
+            Some(v) => v,
+            None => return Signal::Hold,
+        };
+
+        let long_avg = match average(prices, self.long_period) {
+            Some(v) => v,
+            None => return Signal::Hold,
+        };
+
+        // Calculate previous averages (one candle back)
+        let prev_prices = &prices[..prices.len() - 1];
+        let prev_short_avg = match average(prev_prices, self.short_period) {
+            Some(v) => v,
+            None => return Signal::Hold,
+        };
+
+        let prev_long_avg = match average(prev_prices, self.long_period) {
+            Some(v) => v,
+            None => return Signal::Hold,
+        };
+
+        debug!(
+            "{} Analysis - Short: {} -> {}, Long: {} -> {}",
+            label, prev_short_avg, short_avg, prev_long_avg, long_avg
+        );
+
+        // Detect crossover
+        let was_below = prev_short_avg < prev_long_avg;
+        let is_above = short_avg > long_avg;
+        let was_above = prev_short_avg > prev_long_avg;
+        let is_below = short_avg < long_avg;
+
+        // Calculate signal strength based on the separation between averages
+        let separation_pct = if long_avg != Decimal::ZERO {
+            (short_avg - long_avg).abs() / long_avg * Decimal::ONE_HUNDRED
+        } else {
+            Decimal::ZERO
+        };
+        let separation = separation_pct.min(Decimal::ONE); // Normalize to 0-1 range
+
+        if (was_below && is_above || was_above && is_below)
+            && separation_pct <= self.min_separation_pct
+        {
+            debug!(
+                "Crossover ignored: {}s only {:.3}% apart (need more than {}%)",
+                label, separation_pct, self.min_separation_pct
+            );
+            return Signal::Hold;
+        }
+
+        // Golden cross: short average crosses above long average (bullish)
+        if was_below && is_above {
+            let strength = (dec!(0.5) + separation).min(Decimal::ONE);
+            debug!("Golden cross detected! Strength: {}", strength);
+
+            if strength >= self.min_buy_strength {
+                return Signal::Buy { strength };
+            }
+        }
+
+        // Death cross: short average crosses below long average (bearish)
+        if was_above && is_below {
+            let strength = (dec!(0.5) + separation).min(Decimal::ONE);
+            debug!("Death cross detected! Strength: {}", strength);
+
+            if strength >= self.min_sell_strength {
+                return Signal::Sell { strength };
+            }
+        }
+
+        Signal::Hold
+    }
+}
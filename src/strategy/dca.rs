@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::debug;
+
+use crate::exchange::MarketData;
+
+use super::r#trait::{Signal, Strategy};
+
+/// Dollar-cost averaging: buys a fixed quote amount of each symbol on the
+/// first analysis and every `interval_candles` analyses after that,
+/// regardless of price action.
+pub struct DcaStrategy {
+    quote_amount: Decimal,
+    interval_candles: usize,
+    calls: Mutex<HashMap<String, usize>>,
+}
+
+impl DcaStrategy {
+    pub fn new(quote_amount: Decimal, interval_candles: usize) -> Self {
+        assert!(
+            quote_amount > Decimal::ZERO,
+            "Quote amount must be positive"
+        );
+        assert!(interval_candles > 0, "Interval must be positive");
+
+        Self {
+            quote_amount,
+            interval_candles,
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy for DcaStrategy {
+    fn name(&self) -> &str {
+        "DCA"
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        let mut calls = self.calls.lock().unwrap();
+        let count = calls.entry(market_data.symbol.clone()).or_insert(0);
+        let due = count.is_multiple_of(self.interval_candles);
+        *count += 1;
+
+        if due {
+            debug!(
+                "DCA buy due for {}: {} quote",
+                market_data.symbol, self.quote_amount
+            );
+            Signal::Buy {
+                strength: Decimal::ONE,
+            }
+        } else {
+            Signal::Hold
+        }
+    }
+
+    fn required_history(&self) -> usize {
+        0
+    }
+
+    fn fixed_quote_amount(&self) -> Option<Decimal> {
+        Some(self.quote_amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn create_market_data(symbol: &str) -> MarketData {
+        MarketData {
+            symbol: symbol.to_string(),
+            current_price: dec!(100),
+            klines: Vec::new(),
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buys_every_interval() {
+        let strategy = DcaStrategy::new(dec!(50), 3);
+        let market_data = create_market_data("BTCUSDT");
+
+        let mut buys = Vec::new();
+        for call in 0..7 {
+            if matches!(strategy.analyze(&market_data).await, Signal::Buy { .. }) {
+                buys.push(call);
+            }
+        }
+
+        assert_eq!(buys, vec![0, 3, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_cadence_is_tracked_per_symbol() {
+        let strategy = DcaStrategy::new(dec!(50), 2);
+        let btc = create_market_data("BTCUSDT");
+        let eth = create_market_data("ETHUSDT");
+
+        assert!(matches!(strategy.analyze(&btc).await, Signal::Buy { .. }));
+        assert!(matches!(strategy.analyze(&eth).await, Signal::Buy { .. }));
+        assert!(matches!(strategy.analyze(&btc).await, Signal::Hold));
+        assert!(matches!(strategy.analyze(&eth).await, Signal::Hold));
+        assert!(matches!(strategy.analyze(&btc).await, Signal::Buy { .. }));
+    }
+
+    #[test]
+    fn test_advertises_fixed_quote_amount() {
+        let strategy = DcaStrategy::new(dec!(25.5), 4);
+        assert_eq!(strategy.fixed_quote_amount(), Some(dec!(25.5)));
+    }
+}
@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use tracing::debug;
+
+use crate::exchange::MarketData;
+
+use super::indicators::calculate_rsi;
+use super::r#trait::{Signal, Strategy};
+
+/// Gates another strategy's signals on RSI: buys only go through below
+/// `overbought` and sells only above `oversold`, so entries aren't taken at
+/// the top of a run or exits at the bottom of a flush.
+pub struct FilteredStrategy {
+    strategy: Box<dyn Strategy>,
+    rsi_period: usize,
+    oversold: f64,
+    overbought: f64,
+}
+
+impl FilteredStrategy {
+    pub fn new(
+        strategy: Box<dyn Strategy>,
+        rsi_period: usize,
+        oversold: f64,
+        overbought: f64,
+    ) -> Self {
+        assert!(rsi_period > 0, "RSI period must be positive");
+        assert!(
+            oversold < overbought,
+            "Oversold threshold must be below overbought"
+        );
+
+        Self {
+            strategy,
+            rsi_period,
+            oversold,
+            overbought,
+        }
+    }
+
+    fn filter(&self, market_data: &MarketData, signal: Signal) -> Signal {
+        if matches!(signal, Signal::Hold) {
+            return signal;
+        }
+
+        let rsi = match calculate_rsi(&market_data.close_prices(), self.rsi_period) {
+            Some(rsi) => rsi,
+            None => return Signal::Hold,
+        };
+
+        match signal {
+            Signal::Buy { .. } if rsi >= self.overbought => {
+                debug!(
+                    "Buy on {} vetoed, RSI {:.1} is overbought",
+                    market_data.symbol, rsi
+                );
+                Signal::Hold
+            }
+            Signal::Sell { .. } if rsi <= self.oversold => {
+                debug!(
+                    "Sell on {} vetoed, RSI {:.1} is oversold",
+                    market_data.symbol, rsi
+                );
+                Signal::Hold
+            }
+            signal => signal,
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy for FilteredStrategy {
+    fn name(&self) -> &str {
+        self.strategy.name()
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        let signal = self.strategy.analyze(market_data).await;
+        self.filter(market_data, signal)
+    }
+
+    async fn analyze_batch(&self, data: &[MarketData]) -> Vec<Signal> {
+        let signals = self.strategy.analyze_batch(data).await;
+        data.iter()
+            .zip(signals)
+            .map(|(market_data, signal)| self.filter(market_data, signal))
+            .collect()
+    }
+
+    fn required_history(&self) -> usize {
+        self.strategy.required_history().max(self.rsi_period + 1)
+    }
+
+    fn kline_interval(&self) -> &str {
+        self.strategy.kline_interval()
+    }
+
+    fn debug_state(&self, market_data: &MarketData) -> BTreeMap<&'static str, Decimal> {
+        let mut state = self.strategy.debug_state(market_data);
+        if let Some(rsi) = calculate_rsi(&market_data.close_prices(), self.rsi_period)
+            .and_then(|rsi| Decimal::try_from(rsi).ok())
+        {
+            state.insert("rsi", rsi.round_dp(2));
+        }
+        state
+    }
+
+    fn fixed_quote_amount(&self) -> Option<Decimal> {
+        self.strategy.fixed_quote_amount()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Kline;
+    use crate::strategy::MaCrossoverStrategy;
+    use rust_decimal_macros::dec;
+
+    fn create_market_data(close_prices: &[u32]) -> MarketData {
+        let klines = close_prices
+            .iter()
+            .enumerate()
+            .map(|(i, price)| Kline {
+                open_time: i as u64 * 3600000,
+                open: price.to_string(),
+                high: price.to_string(),
+                low: price.to_string(),
+                close: price.to_string(),
+                volume: "100".to_string(),
+                close_time: (i as u64 + 1) * 3600000,
+                quote_asset_volume: "10000".to_string(),
+                number_of_trades: 100,
+                taker_buy_base_asset_volume: "50".to_string(),
+                taker_buy_quote_asset_volume: "5000".to_string(),
+            })
+            .collect();
+
+        MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: dec!(100),
+            klines,
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    fn filtered(rsi_period: usize) -> FilteredStrategy {
+        FilteredStrategy::new(
+            Box::new(MaCrossoverStrategy::new(2, 4, dec!(0))),
+            rsi_period,
+            30.0,
+            70.0,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_overbought_rsi_vetoes_buy() {
+        // Golden cross after three candles without a loss: RSI(3) = 100
+        let golden = create_market_data(&[20, 20, 10, 10, 15, 25]);
+
+        let unfiltered = MaCrossoverStrategy::new(2, 4, dec!(0));
+        assert!(matches!(
+            unfiltered.analyze(&golden).await,
+            Signal::Buy { .. }
+        ));
+        assert_eq!(filtered(3).analyze(&golden).await, Signal::Hold);
+        assert_eq!(filtered(3).analyze_batch(&[golden]).await, [Signal::Hold]);
+    }
+
+    #[tokio::test]
+    async fn test_buy_passes_below_overbought() {
+        // Over five changes the earlier drop holds RSI(5) at 60
+        let golden = create_market_data(&[20, 20, 10, 10, 15, 25]);
+
+        assert!(matches!(
+            filtered(5).analyze(&golden).await,
+            Signal::Buy { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_oversold_rsi_vetoes_sell() {
+        // Death cross after three candles without a gain: RSI(3) = 0
+        let death = create_market_data(&[10, 10, 20, 20, 15, 5]);
+
+        assert_eq!(filtered(3).analyze(&death).await, Signal::Hold);
+        assert!(matches!(
+            filtered(5).analyze(&death).await,
+            Signal::Sell { .. }
+        ));
+    }
+}
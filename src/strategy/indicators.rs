@@ -0,0 +1,340 @@
+use rust_decimal::{Decimal, MathematicalOps};
+
+use crate::exchange::Kline;
+
+pub fn calculate_sma(prices: &[Decimal], period: usize) -> Option<Decimal> {
+    if prices.len() < period {
+        return None;
+    }
+
+    let sum: Decimal = prices.iter().rev().take(period).sum();
+    Some(sum / Decimal::from(period))
+}
+
+pub fn calculate_ema(prices: &[Decimal], period: usize) -> Option<Decimal> {
+    if prices.len() < period {
+        return None;
+    }
+
+    let multiplier = Decimal::from(2) / Decimal::from(period + 1);
+
+    // Start with SMA for initial EMA
+    let initial_sma = calculate_sma(&prices[..period], period)?;
+
+    let mut ema = initial_sma;
+    for price in prices.iter().skip(period) {
+        ema = (*price - ema) * multiplier + ema;
+    }
+
+    Some(ema)
+}
+
+/// Population standard deviation of the last `period` prices.
+pub fn calculate_std_dev(prices: &[Decimal], period: usize) -> Option<Decimal> {
+    let mean = calculate_sma(prices, period)?;
+
+    let variance = prices
+        .iter()
+        .rev()
+        .take(period)
+        .map(|p| (*p - mean) * (*p - mean))
+        .sum::<Decimal>()
+        / Decimal::from(period);
+
+    variance.sqrt()
+}
+
+pub fn calculate_rsi(prices: &[Decimal], period: usize) -> Option<f64> {
+    if prices.len() < period + 1 {
+        return None;
+    }
+
+    let mut gains = Vec::new();
+    let mut losses = Vec::new();
+
+    for i in 1..prices.len() {
+        let change = prices[i] - prices[i - 1];
+        if change > Decimal::ZERO {
+            gains.push(change);
+            losses.push(Decimal::ZERO);
+        } else {
+            gains.push(Decimal::ZERO);
+            losses.push(change.abs());
+        }
+    }
+
+    // Calculate average gain and loss over the period
+    let recent_gains: Vec<_> = gains.iter().rev().take(period).collect();
+    let recent_losses: Vec<_> = losses.iter().rev().take(period).collect();
+
+    let avg_gain: Decimal = recent_gains.iter().copied().sum::<Decimal>() / Decimal::from(period);
+    let avg_loss: Decimal = recent_losses.iter().copied().sum::<Decimal>() / Decimal::from(period);
+
+    if avg_loss == Decimal::ZERO {
+        return Some(100.0);
+    }
+
+    let rs = avg_gain / avg_loss;
+    let rs_f64: f64 = rs.try_into().unwrap_or(1.0);
+
+    Some(100.0 - (100.0 / (1.0 + rs_f64)))
+}
+
+/// MACD reading for the newest candle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Macd {
+    /// Fast EMA minus slow EMA
+    pub macd: Decimal,
+    /// EMA of the MACD line over the signal period
+    pub signal: Decimal,
+    /// `macd` minus `signal`
+    pub histogram: Decimal,
+}
+
+/// MACD of `prices`: the fast minus slow EMA at every candle with
+/// `slow_period` of history, and that line's own EMA as the signal line.
+/// Needs `slow_period + signal_period - 1` prices.
+pub fn calculate_macd(
+    prices: &[Decimal],
+    fast_period: usize,
+    slow_period: usize,
+    signal_period: usize,
+) -> Option<Macd> {
+    if fast_period == 0 || slow_period == 0 || signal_period == 0 {
+        return None;
+    }
+
+    let line: Vec<Decimal> = (slow_period..=prices.len())
+        .filter_map(|end| {
+            let window = &prices[..end];
+            Some(calculate_ema(window, fast_period)? - calculate_ema(window, slow_period)?)
+        })
+        .collect();
+
+    let signal = calculate_ema(&line, signal_period)?;
+    let macd = *line.last()?;
+    Some(Macd {
+        macd,
+        signal,
+        histogram: macd - signal,
+    })
+}
+
+/// Bollinger Bands around the newest candle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BollingerBands {
+    pub lower: Decimal,
+    /// SMA of the last `period` prices
+    pub middle: Decimal,
+    pub upper: Decimal,
+}
+
+/// Bands `num_std_dev` standard deviations either side of the SMA of the
+/// last `period` prices.
+pub fn calculate_bollinger(
+    prices: &[Decimal],
+    period: usize,
+    num_std_dev: Decimal,
+) -> Option<BollingerBands> {
+    if period == 0 {
+        return None;
+    }
+
+    let middle = calculate_sma(prices, period)?;
+    let band_width = calculate_std_dev(prices, period)? * num_std_dev;
+    Some(BollingerBands {
+        lower: middle - band_width,
+        middle,
+        upper: middle + band_width,
+    })
+}
+
+/// Average true range over the last `period` klines: the mean of each
+/// candle's largest move among high - low, |high - previous close| and
+/// |low - previous close|. Needs `period + 1` klines.
+pub fn calculate_atr(klines: &[Kline], period: usize) -> Option<Decimal> {
+    if period == 0 || klines.len() < period + 1 {
+        return None;
+    }
+
+    let recent = &klines[klines.len() - period - 1..];
+    let total: Decimal = recent
+        .windows(2)
+        .map(|pair| {
+            let prev_close = pair[0].close_decimal();
+            let high = pair[1].high_decimal();
+            let low = pair[1].low_decimal();
+
+            (high - low)
+                .max((high - prev_close).abs())
+                .max((low - prev_close).abs())
+        })
+        .sum();
+
+    Some(total / Decimal::from(period))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn prices(values: &[u32]) -> Vec<Decimal> {
+        values.iter().map(|&v| Decimal::from(v)).collect()
+    }
+
+    #[test]
+    fn test_calculate_sma() {
+        let prices = vec![dec!(10), dec!(11), dec!(12), dec!(13), dec!(14)];
+        let sma = calculate_sma(&prices, 3);
+        assert_eq!(sma, Some(dec!(13))); // (12 + 13 + 14) / 3 = 13
+    }
+
+    #[test]
+    fn test_calculate_sma_insufficient_data() {
+        let prices = vec![dec!(10), dec!(11)];
+        let sma = calculate_sma(&prices, 3);
+        assert!(sma.is_none());
+    }
+
+    #[test]
+    fn test_calculate_ema() {
+        // Seeded with the SMA of 1, 2, 3, then each price pulls it halfway:
+        // 2 -> 3 -> 4 -> 5
+        assert_eq!(
+            calculate_ema(&prices(&[1, 2, 3, 4, 5, 6]), 3),
+            Some(dec!(5))
+        );
+        assert_eq!(calculate_ema(&prices(&[1, 2, 3]), 3), Some(dec!(2)));
+        assert!(calculate_ema(&prices(&[1, 2]), 3).is_none());
+    }
+
+    #[test]
+    fn test_calculate_std_dev() {
+        let prices = vec![
+            dec!(1),
+            dec!(2),
+            dec!(4),
+            dec!(4),
+            dec!(4),
+            dec!(5),
+            dec!(5),
+            dec!(7),
+            dec!(9),
+        ];
+        // Last 8 prices have mean 5 and variance 4
+        assert_eq!(calculate_std_dev(&prices, 8), Some(dec!(2)));
+        assert_eq!(calculate_std_dev(&[dec!(3); 5], 5), Some(dec!(0)));
+        assert!(calculate_std_dev(&prices, 10).is_none());
+    }
+
+    #[test]
+    fn test_calculate_rsi() {
+        // Create a simple uptrend
+        let prices: Vec<Decimal> = (0..20).map(|i| Decimal::from(100 + i)).collect();
+        let rsi = calculate_rsi(&prices, 14);
+        assert!(rsi.is_some());
+        assert!(rsi.unwrap() > 50.0); // Should be bullish
+    }
+
+    #[test]
+    fn test_calculate_rsi_reference_values() {
+        // Changes +1, -1, +2: average gain 1, average loss 1/3, RS 3
+        let rsi = calculate_rsi(&prices(&[44, 45, 44, 46]), 3).unwrap();
+        assert!((rsi - 75.0).abs() < 1e-9, "{}", rsi);
+
+        assert_eq!(calculate_rsi(&prices(&[1, 2, 3, 4]), 3), Some(100.0));
+        assert!(calculate_rsi(&prices(&[1, 2, 3]), 3).is_none());
+    }
+
+    #[test]
+    fn test_calculate_macd() {
+        // On a steady rise each EMA trails price by a fixed (period - 1) / 2,
+        // so MACD and its signal line are both 3 - 1 = 2
+        let rising: Vec<Decimal> = (1..=12).map(Decimal::from).collect();
+        assert_eq!(
+            calculate_macd(&rising, 3, 7, 3),
+            Some(Macd {
+                macd: dec!(2),
+                signal: dec!(2),
+                histogram: dec!(0),
+            })
+        );
+
+        // A jump from 10 to 18 moves the 3-EMA to 14 and the 7-EMA to 12;
+        // the signal line moves halfway from 0 to that MACD of 2
+        let jump = prices(&[10, 10, 10, 10, 10, 10, 10, 10, 10, 18]);
+        assert_eq!(
+            calculate_macd(&jump, 3, 7, 3),
+            Some(Macd {
+                macd: dec!(2),
+                signal: dec!(1),
+                histogram: dec!(1),
+            })
+        );
+
+        // slow_period + signal_period - 1 prices at the least
+        assert!(calculate_macd(&rising[..9], 3, 7, 3).is_some());
+        assert!(calculate_macd(&rising[..8], 3, 7, 3).is_none());
+    }
+
+    #[test]
+    fn test_calculate_bollinger() {
+        // Mean 5 and standard deviation 2 over the last 8
+        let prices = prices(&[1, 2, 4, 4, 4, 5, 5, 7, 9]);
+        assert_eq!(
+            calculate_bollinger(&prices, 8, dec!(2)),
+            Some(BollingerBands {
+                lower: dec!(1),
+                middle: dec!(5),
+                upper: dec!(9),
+            })
+        );
+        assert!(calculate_bollinger(&prices, 10, dec!(2)).is_none());
+        assert!(calculate_bollinger(&prices, 0, dec!(2)).is_none());
+    }
+
+    fn kline(high: &str, low: &str, close: &str) -> Kline {
+        Kline {
+            open_time: 0,
+            open: close.to_string(),
+            high: high.to_string(),
+            low: low.to_string(),
+            close: close.to_string(),
+            volume: "100".to_string(),
+            close_time: 0,
+            quote_asset_volume: "10000".to_string(),
+            number_of_trades: 100,
+            taker_buy_base_asset_volume: "50".to_string(),
+            taker_buy_quote_asset_volume: "5000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_atr_matches_manual_true_ranges() {
+        let klines = vec![
+            kline("100", "90", "95"),
+            kline("10", "8", "9"),
+            kline("11", "9", "10"),
+            kline("12", "9", "11"),
+            kline("11", "10", "10.5"),
+        ];
+
+        // True ranges of the last three candles:
+        //   max(11 - 9, |11 - 9|, |9 - 9|)      = 2
+        //   max(12 - 9, |12 - 10|, |9 - 10|)    = 3
+        //   max(11 - 10, |11 - 11|, |10 - 11|)  = 1
+        assert_eq!(calculate_atr(&klines, 3), Some(dec!(2)));
+
+        // A gap from the previous close counts as range: max(2, |10 - 95|, |8 - 95|) = 87
+        assert_eq!(calculate_atr(&klines[..2], 1), Some(dec!(87)));
+    }
+
+    #[test]
+    fn test_atr_needs_period_plus_one_klines() {
+        let klines = vec![kline("10", "8", "9"), kline("11", "9", "10")];
+
+        assert_eq!(calculate_atr(&klines, 2), None);
+        assert_eq!(calculate_atr(&klines, 0), None);
+    }
+}
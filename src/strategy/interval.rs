@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+use crate::exchange::MarketData;
+
+use super::r#trait::{Signal, Strategy};
+
+/// Runs a strategy on candles of another interval than the default hourly
+/// ones. The wrapped strategy's periods count candles, so they cover more or
+/// less time accordingly.
+pub struct WithInterval {
+    strategy: Box<dyn Strategy>,
+    interval: String,
+}
+
+impl WithInterval {
+    pub fn new(strategy: Box<dyn Strategy>, interval: &str) -> Self {
+        Self {
+            strategy,
+            interval: interval.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy for WithInterval {
+    fn name(&self) -> &str {
+        self.strategy.name()
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        self.strategy.analyze(market_data).await
+    }
+
+    async fn analyze_batch(&self, data: &[MarketData]) -> Vec<Signal> {
+        self.strategy.analyze_batch(data).await
+    }
+
+    fn required_history(&self) -> usize {
+        self.strategy.required_history()
+    }
+
+    fn kline_interval(&self) -> &str {
+        &self.interval
+    }
+
+    fn debug_state(&self, market_data: &MarketData) -> BTreeMap<&'static str, Decimal> {
+        self.strategy.debug_state(market_data)
+    }
+
+    fn fixed_quote_amount(&self) -> Option<Decimal> {
+        self.strategy.fixed_quote_amount()
+    }
+}
@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use tracing::debug;
+
+use crate::exchange::MarketData;
+
+use super::indicators::{calculate_atr, calculate_ema};
+use super::r#trait::{Signal, Strategy};
+
+/// Breakout on Keltner Channels: an EMA with bands `multiplier` × ATR either
+/// side. Buys a close above the upper band and sells a close below the lower
+/// band.
+pub struct KeltnerStrategy {
+    ema_period: usize,
+    atr_period: usize,
+    multiplier: Decimal,
+}
+
+impl KeltnerStrategy {
+    pub fn new(ema_period: usize, atr_period: usize, multiplier: Decimal) -> Self {
+        assert!(ema_period > 1, "EMA period must be greater than 1");
+        assert!(atr_period > 0, "ATR period must be positive");
+        assert!(multiplier > Decimal::ZERO, "Band width must be positive");
+
+        Self {
+            ema_period,
+            atr_period,
+            multiplier,
+        }
+    }
+}
+
+#[async_trait]
+impl Strategy for KeltnerStrategy {
+    fn name(&self) -> &str {
+        "Keltner Channels"
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        let prices = market_data.close_prices();
+
+        if prices.len() < self.required_history() {
+            debug!(
+                "Insufficient data for Keltner analysis: have {}, need {}",
+                prices.len(),
+                self.required_history()
+            );
+            return Signal::Hold;
+        }
+
+        let middle = match calculate_ema(&prices, self.ema_period) {
+            Some(v) => v,
+            None => return Signal::Hold,
+        };
+        let atr = match calculate_atr(&market_data.klines, self.atr_period) {
+            Some(v) => v,
+            None => return Signal::Hold,
+        };
+
+        // Candles with no range give no channel to break out of
+        if atr == Decimal::ZERO {
+            return Signal::Hold;
+        }
+
+        let band_width = atr * self.multiplier;
+        let upper = middle + band_width;
+        let lower = middle - band_width;
+        let close = prices[prices.len() - 1];
+
+        debug!(
+            "Keltner Analysis - Lower: {}, Middle: {}, Upper: {}, Close: {}",
+            lower, middle, upper, close
+        );
+
+        // Strength is how far past the band the close is, in band widths
+        let strength = |distance: Decimal| (distance / band_width).min(Decimal::ONE);
+
+        if close > upper {
+            let strength = strength(close - upper);
+            debug!("Breakout above upper channel! Strength: {}", strength);
+            return Signal::Buy { strength };
+        }
+
+        if close < lower {
+            let strength = strength(lower - close);
+            debug!("Breakdown below lower channel! Strength: {}", strength);
+            return Signal::Sell { strength };
+        }
+
+        Signal::Hold
+    }
+
+    fn required_history(&self) -> usize {
+        self.ema_period.max(self.atr_period + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Kline;
+    use rust_decimal_macros::dec;
+
+    /// Candles spanning one unit either side of each close.
+    fn create_market_data(close_prices: &[u32]) -> MarketData {
+        let klines = close_prices
+            .iter()
+            .enumerate()
+            .map(|(i, price)| Kline {
+                open_time: i as u64 * 3600000,
+                open: price.to_string(),
+                high: (price + 1).to_string(),
+                low: (price - 1).to_string(),
+                close: price.to_string(),
+                volume: "100".to_string(),
+                close_time: (i as u64 + 1) * 3600000,
+                quote_asset_volume: "10000".to_string(),
+                number_of_trades: 100,
+                taker_buy_base_asset_volume: "50".to_string(),
+                taker_buy_quote_asset_volume: "5000".to_string(),
+            })
+            .collect();
+
+        MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: dec!(100),
+            klines,
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_breakout_above_upper_channel_buys() {
+        let strategy = KeltnerStrategy::new(10, 10, dec!(2));
+        let market_data =
+            create_market_data(&[100, 101, 99, 100, 101, 99, 100, 101, 99, 100, 101, 120]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Buy { .. }), "got {:?}", signal);
+    }
+
+    #[tokio::test]
+    async fn test_breakdown_below_lower_channel_sells() {
+        let strategy = KeltnerStrategy::new(10, 10, dec!(2));
+        let market_data =
+            create_market_data(&[100, 101, 99, 100, 101, 99, 100, 101, 99, 100, 101, 80]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Sell { .. }), "got {:?}", signal);
+    }
+
+    #[tokio::test]
+    async fn test_inside_channel_holds() {
+        let strategy = KeltnerStrategy::new(10, 10, dec!(2));
+        let market_data =
+            create_market_data(&[100, 101, 99, 100, 101, 99, 100, 101, 99, 100, 101, 100]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Hold));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_data() {
+        let strategy = KeltnerStrategy::new(10, 10, dec!(2));
+        let market_data = create_market_data(&[100, 101, 99, 100, 101, 99, 100, 101, 99, 100]);
+
+        assert_eq!(strategy.required_history(), 11);
+        assert!(matches!(strategy.analyze(&market_data).await, Signal::Hold));
+    }
+}
@@ -0,0 +1,323 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::exchange::MarketData;
+
+use super::crossover::Crossover;
+use super::indicators::{calculate_ema, calculate_sma};
+use super::r#trait::{Signal, Strategy};
+
+/// The moving average a `MaCrossoverStrategy` compares.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaType {
+    #[default]
+    Sma,
+    /// Weights recent candles more, so turns sooner after a sharp move
+    Ema,
+}
+
+impl MaType {
+    fn average(self) -> fn(&[Decimal], usize) -> Option<Decimal> {
+        match self {
+            MaType::Sma => calculate_sma,
+            MaType::Ema => calculate_ema,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            MaType::Sma => "SMA",
+            MaType::Ema => "EMA",
+        }
+    }
+}
+
+/// Golden/death cross of a short over a long moving average, simple unless
+/// set otherwise with `with_ma_type`.
+pub struct MaCrossoverStrategy {
+    crossover: Crossover,
+    ma_type: MaType,
+}
+
+impl MaCrossoverStrategy {
+    pub fn new(short_period: usize, long_period: usize, min_signal_strength: Decimal) -> Self {
+        Self {
+            crossover: Crossover::new(short_period, long_period, min_signal_strength),
+            ma_type: MaType::Sma,
+        }
+    }
+
+    pub fn with_ma_type(mut self, ma_type: MaType) -> Self {
+        self.ma_type = ma_type;
+        self
+    }
+
+    /// Separate thresholds for golden (buy) and death (sell) crosses,
+    /// replacing the single `min_signal_strength`.
+    pub fn with_min_strengths(
+        mut self,
+        min_buy_strength: Decimal,
+        min_sell_strength: Decimal,
+    ) -> Self {
+        self.crossover
+            .set_min_strengths(min_buy_strength, min_sell_strength);
+        self
+    }
+
+    /// Ignores crosses that leave the averages within `min_separation_pct`
+    /// percent of each other.
+    pub fn with_min_separation_pct(mut self, min_separation_pct: Decimal) -> Self {
+        self.crossover.set_min_separation_pct(min_separation_pct);
+        self
+    }
+}
+
+#[async_trait]
+impl Strategy for MaCrossoverStrategy {
+    fn name(&self) -> &str {
+        match self.ma_type {
+            MaType::Sma => "SMA Crossover",
+            MaType::Ema => "EMA Crossover",
+        }
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        self.crossover.signal(
+            &market_data.close_prices(),
+            self.ma_type.average(),
+            self.ma_type.label(),
+        )
+    }
+
+    fn required_history(&self) -> usize {
+        self.crossover.required_history()
+    }
+
+    fn debug_state(&self, market_data: &MarketData) -> BTreeMap<&'static str, Decimal> {
+        let (short_key, long_key) = match self.ma_type {
+            MaType::Sma => ("short_sma", "long_sma"),
+            MaType::Ema => ("short_ema", "long_ema"),
+        };
+        self.crossover
+            .averages(&market_data.close_prices(), self.ma_type.average())
+            .map(|(short, long)| BTreeMap::from([(short_key, short), (long_key, long)]))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Kline;
+    use rust_decimal_macros::dec;
+
+    fn create_market_data(close_prices: Vec<&str>) -> MarketData {
+        let klines = close_prices
+            .into_iter()
+            .enumerate()
+            .map(|(i, price)| Kline {
+                open_time: i as u64 * 3600000,
+                open: price.to_string(),
+                high: price.to_string(),
+                low: price.to_string(),
+                close: price.to_string(),
+                volume: "100".to_string(),
+                close_time: (i as u64 + 1) * 3600000,
+                quote_asset_volume: "10000".to_string(),
+                number_of_trades: 100,
+                taker_buy_base_asset_volume: "50".to_string(),
+                taker_buy_quote_asset_volume: "5000".to_string(),
+            })
+            .collect();
+
+        MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: dec!(100),
+            klines,
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_golden_cross() {
+        let strategy = MaCrossoverStrategy::new(2, 4, dec!(0));
+
+        // Create data where short MA crosses above long MA
+        // Previous (5 prices): short_sma=12.5, long_sma=13.75 -> short < long
+        // Current (6 prices): short_sma=20, long_sma=15 -> short > long
+        // This creates a golden cross (short crosses above long)
+        let market_data = create_market_data(vec!["20", "20", "10", "10", "15", "25"]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Buy { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_death_cross() {
+        let strategy = MaCrossoverStrategy::new(2, 4, dec!(0));
+
+        // Create data where short MA crosses below long MA
+        // Previous (5 prices): short_sma=17.5, long_sma=16.25 -> short > long
+        // Current (6 prices): short_sma=10, long_sma=15 -> short < long
+        // This creates a death cross (short crosses below long)
+        let market_data = create_market_data(vec!["10", "10", "20", "20", "15", "5"]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Sell { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_batch_matches_analyzing_each() {
+        let strategy = MaCrossoverStrategy::new(2, 4, dec!(0));
+        let data = vec![
+            create_market_data(vec!["20", "20", "10", "10", "15", "25"]),
+            create_market_data(vec!["10", "10", "10", "10", "10", "10"]),
+            create_market_data(vec!["10", "10", "20", "20", "15", "5"]),
+            create_market_data(vec!["10", "10"]),
+        ];
+
+        let mut expected = Vec::new();
+        for market_data in &data {
+            expected.push(strategy.analyze(market_data).await);
+        }
+
+        assert_eq!(strategy.analyze_batch(&data).await, expected);
+        assert!(strategy.analyze_batch(&[]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hold_signal() {
+        let strategy = MaCrossoverStrategy::new(2, 4, dec!(0));
+
+        // Create flat data - no crossover
+        let market_data = create_market_data(vec!["10", "10", "10", "10", "10", "10"]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Hold));
+    }
+
+    #[tokio::test]
+    async fn test_marginal_cross_suppressed_by_min_separation() {
+        let strategy = MaCrossoverStrategy::new(2, 4, dec!(0)).with_min_separation_pct(dec!(0.5));
+
+        // Previous: short_sma=99.5, long_sma=99.75
+        // Current: short_sma=100.1, long_sma=100.05 -> 0.05% apart
+        let marginal = create_market_data(vec!["100", "100", "100", "100", "99", "101.2"]);
+        assert!(matches!(strategy.analyze(&marginal).await, Signal::Hold));
+
+        // Without the gate the same cross fires
+        let ungated = MaCrossoverStrategy::new(2, 4, dec!(0));
+        assert!(matches!(
+            ungated.analyze(&marginal).await,
+            Signal::Buy { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decisive_cross_passes_min_separation() {
+        let strategy = MaCrossoverStrategy::new(2, 4, dec!(0)).with_min_separation_pct(dec!(0.5));
+
+        // short_sma=20 vs long_sma=15 after the golden cross
+        let golden = create_market_data(vec!["20", "20", "10", "10", "15", "25"]);
+        assert!(matches!(
+            strategy.analyze(&golden).await,
+            Signal::Buy { .. }
+        ));
+
+        // short_sma=10 vs long_sma=15 after the death cross
+        let death = create_market_data(vec!["10", "10", "20", "20", "15", "5"]);
+        assert!(matches!(
+            strategy.analyze(&death).await,
+            Signal::Sell { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_side_thresholds_gate_buys_and_sells_separately() {
+        // Cautious to enter, eager to exit
+        let strategy =
+            MaCrossoverStrategy::new(2, 4, dec!(0.6)).with_min_strengths(dec!(0.7), dec!(0.5));
+
+        // Both crosses leave the SMAs about 0.1% apart, strength ~0.6
+        let golden = create_market_data(vec!["100", "100", "100", "99.6", "99.6", "100.4"]);
+        let death = create_market_data(vec!["100", "100", "100", "100.4", "100.4", "99.6"]);
+
+        assert!(matches!(strategy.analyze(&golden).await, Signal::Hold));
+        assert!(matches!(
+            strategy.analyze(&death).await,
+            Signal::Sell { strength } if strength < dec!(0.7)
+        ));
+
+        // One shared threshold treats them alike
+        let shared = MaCrossoverStrategy::new(2, 4, dec!(0.7));
+        assert!(matches!(shared.analyze(&death).await, Signal::Hold));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_data() {
+        let strategy = MaCrossoverStrategy::new(2, 4, dec!(0));
+
+        // Not enough data points
+        let market_data = create_market_data(vec!["10", "11", "12"]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Hold));
+    }
+
+    fn ema(short_period: usize, long_period: usize) -> MaCrossoverStrategy {
+        MaCrossoverStrategy::new(short_period, long_period, dec!(0)).with_ma_type(MaType::Ema)
+    }
+
+    /// Downtrend followed by a sharp rebound on the last two candles.
+    const REBOUND: [&str; 11] = [
+        "30", "28", "26", "24", "22", "20", "18", "16", "14", "25", "26",
+    ];
+
+    #[tokio::test]
+    async fn test_ema_golden_and_death_cross() {
+        let strategy = ema(3, 5);
+
+        let rebound = create_market_data(REBOUND[..10].to_vec());
+        assert!(matches!(
+            strategy.analyze(&rebound).await,
+            Signal::Buy { .. }
+        ));
+
+        // Mirror image: uptrend, then a sharp drop
+        let crash: Vec<String> = REBOUND[..10]
+            .iter()
+            .map(|p| (50 - p.parse::<u32>().unwrap()).to_string())
+            .collect();
+        let crash = create_market_data(crash.iter().map(String::as_str).collect());
+        assert!(matches!(
+            strategy.analyze(&crash).await,
+            Signal::Sell { .. }
+        ));
+
+        let flat = create_market_data(vec!["10"; 8]);
+        assert!(matches!(strategy.analyze(&flat).await, Signal::Hold));
+    }
+
+    #[tokio::test]
+    async fn test_ema_crosses_before_sma_on_the_same_series() {
+        let sma = MaCrossoverStrategy::new(3, 5, dec!(0));
+        let ema = ema(3, 5);
+
+        // On the rebound candle only the EMAs have crossed
+        let rebound = create_market_data(REBOUND[..10].to_vec());
+        assert!(matches!(ema.analyze(&rebound).await, Signal::Buy { .. }));
+        assert!(matches!(sma.analyze(&rebound).await, Signal::Hold));
+
+        // The SMAs follow a candle later
+        let next = create_market_data(REBOUND.to_vec());
+        assert!(matches!(sma.analyze(&next).await, Signal::Buy { .. }));
+
+        assert_eq!(sma.name(), "SMA Crossover");
+        assert_eq!(ema.name(), "EMA Crossover");
+        assert!(ema.debug_state(&rebound).contains_key("short_ema"));
+    }
+}
@@ -0,0 +1,200 @@
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use tracing::debug;
+
+use crate::exchange::MarketData;
+
+use super::indicators::calculate_macd;
+use super::r#trait::{Signal, Strategy};
+
+pub struct MacdStrategy {
+    fast_period: usize,
+    slow_period: usize,
+    signal_period: usize,
+    min_buy_strength: Decimal,
+    min_sell_strength: Decimal,
+}
+
+impl MacdStrategy {
+    pub fn new(
+        fast_period: usize,
+        slow_period: usize,
+        signal_period: usize,
+        min_signal_strength: Decimal,
+    ) -> Self {
+        assert!(
+            fast_period < slow_period,
+            "Fast period must be less than slow period"
+        );
+        assert!(signal_period > 0, "Signal period must be positive");
+
+        Self {
+            fast_period,
+            slow_period,
+            signal_period,
+            min_buy_strength: min_signal_strength,
+            min_sell_strength: min_signal_strength,
+        }
+    }
+
+    /// Separate thresholds for bullish (buy) and bearish (sell) crossovers,
+    /// replacing the single `min_signal_strength`.
+    pub fn with_min_strengths(
+        mut self,
+        min_buy_strength: Decimal,
+        min_sell_strength: Decimal,
+    ) -> Self {
+        self.min_buy_strength = min_buy_strength;
+        self.min_sell_strength = min_sell_strength;
+        self
+    }
+}
+
+#[async_trait]
+impl Strategy for MacdStrategy {
+    fn name(&self) -> &str {
+        "MACD"
+    }
+
+    async fn analyze(&self, market_data: &MarketData) -> Signal {
+        let prices = market_data.close_prices();
+
+        if prices.len() < self.required_history() {
+            debug!(
+                "Insufficient data for MACD analysis: have {}, need {}",
+                prices.len(),
+                self.required_history()
+            );
+            return Signal::Hold;
+        }
+
+        // Histogram (MACD - signal line) now and one candle back
+        let macd = |prices: &[Decimal]| {
+            calculate_macd(
+                prices,
+                self.fast_period,
+                self.slow_period,
+                self.signal_period,
+            )
+        };
+        let (Some(now), Some(prev)) = (macd(&prices), macd(&prices[..prices.len() - 1])) else {
+            return Signal::Hold;
+        };
+        let (histogram, prev_histogram) = (now.histogram, prev.histogram);
+
+        debug!(
+            "MACD Analysis - Histogram: {} -> {}",
+            prev_histogram, histogram
+        );
+
+        // Strength grows with the histogram as a fraction of price
+        let price = prices[prices.len() - 1];
+        let strength = if price != Decimal::ZERO {
+            (histogram.abs() / price * Decimal::ONE_HUNDRED).min(Decimal::ONE)
+        } else {
+            Decimal::ZERO
+        };
+
+        // Bullish: MACD crosses above its signal line
+        if prev_histogram <= Decimal::ZERO && histogram > Decimal::ZERO {
+            debug!("Bullish MACD crossover! Strength: {}", strength);
+
+            if strength >= self.min_buy_strength {
+                return Signal::Buy { strength };
+            }
+        }
+
+        // Bearish: MACD crosses below its signal line
+        if prev_histogram >= Decimal::ZERO && histogram < Decimal::ZERO {
+            debug!("Bearish MACD crossover! Strength: {}", strength);
+
+            if strength >= self.min_sell_strength {
+                return Signal::Sell { strength };
+            }
+        }
+
+        Signal::Hold
+    }
+
+    fn required_history(&self) -> usize {
+        // signal_period MACD values for the signal line, plus one to look back
+        self.slow_period + self.signal_period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Kline;
+    use rust_decimal_macros::dec;
+
+    fn create_market_data(close_prices: &[u32]) -> MarketData {
+        let klines = close_prices
+            .iter()
+            .enumerate()
+            .map(|(i, price)| Kline {
+                open_time: i as u64 * 3600000,
+                open: price.to_string(),
+                high: price.to_string(),
+                low: price.to_string(),
+                close: price.to_string(),
+                volume: "100".to_string(),
+                close_time: (i as u64 + 1) * 3600000,
+                quote_asset_volume: "10000".to_string(),
+                number_of_trades: 100,
+                taker_buy_base_asset_volume: "50".to_string(),
+                taker_buy_quote_asset_volume: "5000".to_string(),
+            })
+            .collect();
+
+        MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: dec!(100),
+            klines,
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bullish_crossover() {
+        let strategy = MacdStrategy::new(3, 6, 3, dec!(0));
+
+        // Steady decline, then a sharp reversal on the last candle
+        let market_data =
+            create_market_data(&[120, 118, 116, 114, 112, 110, 108, 106, 104, 102, 100, 112]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Buy { .. }), "got {:?}", signal);
+    }
+
+    #[tokio::test]
+    async fn test_bearish_crossover() {
+        let strategy = MacdStrategy::new(3, 6, 3, dec!(0));
+
+        // Steady rally, then a sharp drop on the last candle
+        let market_data =
+            create_market_data(&[100, 102, 104, 106, 108, 110, 112, 114, 116, 118, 120, 108]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Sell { .. }), "got {:?}", signal);
+    }
+
+    #[tokio::test]
+    async fn test_flat_market_holds() {
+        let strategy = MacdStrategy::new(3, 6, 3, dec!(0));
+        let market_data = create_market_data(&[100; 12]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Hold));
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_data() {
+        let strategy = MacdStrategy::new(3, 6, 3, dec!(0));
+        let market_data = create_market_data(&[100, 101, 102, 103, 104]);
+
+        let signal = strategy.analyze(&market_data).await;
+        assert!(matches!(signal, Signal::Hold));
+    }
+}
@@ -1,5 +1,25 @@
-mod sma_crossover;
+mod bollinger;
+mod composite;
+mod crossover;
+mod dca;
+mod filtered;
+mod indicators;
+mod interval;
+mod keltner;
+mod ma_crossover;
+mod macd;
 mod r#trait;
 
-pub use sma_crossover::SmaCrossoverStrategy;
+pub use bollinger::BollingerStrategy;
+pub use composite::{CombinationMode, CompositeStrategy};
+pub use dca::DcaStrategy;
+pub use filtered::FilteredStrategy;
+pub use indicators::{
+    calculate_atr, calculate_bollinger, calculate_ema, calculate_macd, calculate_rsi,
+    calculate_sma, calculate_std_dev, BollingerBands, Macd,
+};
+pub use interval::WithInterval;
+pub use keltner::KeltnerStrategy;
+pub use ma_crossover::{MaCrossoverStrategy, MaType};
+pub use macd::MacdStrategy;
 pub use r#trait::{Signal, Strategy};
@@ -1,27 +1,38 @@
 use async_trait::async_trait;
 use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
 
-use crate::exchange::MarketData;
+use crate::exchange::{MarketData, DEFAULT_KLINE_INTERVAL};
 
-#[derive(Debug, Clone)]
+/// A strategy's call on a symbol. Strengths run from 0 to 1 and size the
+/// order, so they are kept as `Decimal` like the rest of the money math;
+/// they serialize as plain numbers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Signal {
-    Buy { strength: f64 },
-    Sell { strength: f64 },
+    Buy {
+        #[serde(with = "rust_decimal::serde::float")]
+        strength: Decimal,
+    },
+    Sell {
+        #[serde(with = "rust_decimal::serde::float")]
+        strength: Decimal,
+    },
     Hold,
 }
 
 impl Signal {
-    pub fn is_actionable(&self, min_strength: f64) -> bool {
+    pub fn is_actionable(&self, min_strength: Decimal) -> bool {
         match self {
             Signal::Buy { strength } | Signal::Sell { strength } => *strength >= min_strength,
             Signal::Hold => false,
         }
     }
 
-    pub fn strength(&self) -> f64 {
+    pub fn strength(&self) -> Decimal {
         match self {
             Signal::Buy { strength } | Signal::Sell { strength } => *strength,
-            Signal::Hold => 0.0,
+            Signal::Hold => Decimal::ZERO,
         }
     }
 }
@@ -32,70 +43,35 @@ pub trait Strategy: Send + Sync {
 
     async fn analyze(&self, market_data: &MarketData) -> Signal;
 
-    fn required_history(&self) -> usize;
-}
-
-pub fn calculate_sma(prices: &[Decimal], period: usize) -> Option<Decimal> {
-    if prices.len() < period {
-        return None;
-    }
-
-    let sum: Decimal = prices.iter().rev().take(period).sum();
-    Some(sum / Decimal::from(period))
-}
-
-pub fn calculate_ema(prices: &[Decimal], period: usize) -> Option<Decimal> {
-    if prices.len() < period {
-        return None;
+    /// One signal per element of `data`, in order. Strategies that can share
+    /// work across symbols override this; by default each is analyzed in
+    /// turn.
+    async fn analyze_batch(&self, data: &[MarketData]) -> Vec<Signal> {
+        let mut signals = Vec::with_capacity(data.len());
+        for market_data in data {
+            signals.push(self.analyze(market_data).await);
+        }
+        signals
     }
 
-    let multiplier = Decimal::from(2) / Decimal::from(period + 1);
-
-    // Start with SMA for initial EMA
-    let initial_sma = calculate_sma(&prices[..period], period)?;
+    fn required_history(&self) -> usize;
 
-    let mut ema = initial_sma;
-    for price in prices.iter().skip(period) {
-        ema = (*price - ema) * multiplier + ema;
+    /// Binance interval of the candles `analyze` expects, e.g. "15m" or "1d".
+    fn kline_interval(&self) -> &str {
+        DEFAULT_KLINE_INTERVAL
     }
 
-    Some(ema)
-}
-
-pub fn calculate_rsi(prices: &[Decimal], period: usize) -> Option<f64> {
-    if prices.len() < period + 1 {
-        return None;
+    /// Indicator values behind the signal for `market_data`, e.g. the two
+    /// averages of a crossover, for the engine's signal log.
+    fn debug_state(&self, _market_data: &MarketData) -> BTreeMap<&'static str, Decimal> {
+        BTreeMap::new()
     }
 
-    let mut gains = Vec::new();
-    let mut losses = Vec::new();
-
-    for i in 1..prices.len() {
-        let change = prices[i] - prices[i - 1];
-        if change > Decimal::ZERO {
-            gains.push(change);
-            losses.push(Decimal::ZERO);
-        } else {
-            gains.push(Decimal::ZERO);
-            losses.push(change.abs());
-        }
+    /// Quote amount to spend on every buy, for strategies that size their own
+    /// orders instead of leaving it to the risk manager.
+    fn fixed_quote_amount(&self) -> Option<Decimal> {
+        None
     }
-
-    // Calculate average gain and loss over the period
-    let recent_gains: Vec<_> = gains.iter().rev().take(period).collect();
-    let recent_losses: Vec<_> = losses.iter().rev().take(period).collect();
-
-    let avg_gain: Decimal = recent_gains.iter().copied().sum::<Decimal>() / Decimal::from(period);
-    let avg_loss: Decimal = recent_losses.iter().copied().sum::<Decimal>() / Decimal::from(period);
-
-    if avg_loss == Decimal::ZERO {
-        return Some(100.0);
-    }
-
-    let rs = avg_gain / avg_loss;
-    let rs_f64: f64 = rs.try_into().unwrap_or(1.0);
-
-    Some(100.0 - (100.0 / (1.0 + rs_f64)))
 }
 
 #[cfg(test)]
@@ -103,33 +79,16 @@ mod tests {
     use super::*;
     use rust_decimal_macros::dec;
 
-    #[test]
-    fn test_calculate_sma() {
-        let prices = vec![dec!(10), dec!(11), dec!(12), dec!(13), dec!(14)];
-        let sma = calculate_sma(&prices, 3);
-        assert_eq!(sma, Some(dec!(13))); // (12 + 13 + 14) / 3 = 13
-    }
-
-    #[test]
-    fn test_calculate_sma_insufficient_data() {
-        let prices = vec![dec!(10), dec!(11)];
-        let sma = calculate_sma(&prices, 3);
-        assert!(sma.is_none());
-    }
-
     #[test]
     fn test_signal_actionable() {
-        assert!(Signal::Buy { strength: 0.8 }.is_actionable(0.6));
-        assert!(!Signal::Buy { strength: 0.5 }.is_actionable(0.6));
-        assert!(!Signal::Hold.is_actionable(0.0));
-    }
-
-    #[test]
-    fn test_calculate_rsi() {
-        // Create a simple uptrend
-        let prices: Vec<Decimal> = (0..20).map(|i| Decimal::from(100 + i)).collect();
-        let rsi = calculate_rsi(&prices, 14);
-        assert!(rsi.is_some());
-        assert!(rsi.unwrap() > 50.0); // Should be bullish
+        assert!(Signal::Buy {
+            strength: dec!(0.8)
+        }
+        .is_actionable(dec!(0.6)));
+        assert!(!Signal::Buy {
+            strength: dec!(0.5)
+        }
+        .is_actionable(dec!(0.6)));
+        assert!(!Signal::Hold.is_actionable(dec!(0)));
     }
 }
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use std::io::{BufRead, Write};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::exchange::OrderRequest;
+
+/// Decides whether a live order may be sent to the exchange. Asked once per
+/// order, right before it is placed; a declined order is dropped.
+#[async_trait]
+pub trait OrderApprover: Send + Sync {
+    async fn approve(&self, order: &OrderRequest) -> bool;
+}
+
+/// Prints each order and asks on stdin whether to place it. Anything but
+/// "y" or "yes" declines.
+#[derive(Default)]
+pub struct StdinApprover {
+    // Symbols are traded concurrently; one prompt at a time
+    prompt: Mutex<()>,
+}
+
+impl StdinApprover {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl OrderApprover for StdinApprover {
+    async fn approve(&self, order: &OrderRequest) -> bool {
+        let _prompt = self.prompt.lock().await;
+        let question = format!("Place {}? [y/N] ", describe_order(order));
+
+        let answer = tokio::task::spawn_blocking(move || {
+            let mut stdout = std::io::stdout();
+            write!(stdout, "{}", question)?;
+            stdout.flush()?;
+
+            let mut answer = String::new();
+            std::io::stdin().lock().read_line(&mut answer)?;
+            Ok::<_, std::io::Error>(answer)
+        })
+        .await;
+
+        match answer {
+            Ok(Ok(answer)) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+            Ok(Err(e)) => {
+                warn!("Could not read order confirmation: {}", e);
+                false
+            }
+            Err(e) => {
+                warn!("Order confirmation prompt failed: {}", e);
+                false
+            }
+        }
+    }
+}
+
+/// One line summary of `order` for the confirmation prompt, e.g.
+/// "BUY 0.01 BTCUSDT at limit 25000".
+pub(crate) fn describe_order(order: &OrderRequest) -> String {
+    let amount = match order.quote_order_qty {
+        Some(quote_qty) => format!("{} quote of", quote_qty),
+        None => order.quantity.to_string(),
+    };
+    let price = match order.price {
+        Some(price) => format!("limit {}", price),
+        None => "market price".to_string(),
+    };
+    format!("{} {} {} at {}", order.side, amount, order.symbol, price)
+}
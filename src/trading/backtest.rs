@@ -0,0 +1,176 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tracing::debug;
+
+use crate::exchange::{Kline, MarketData};
+use crate::strategy::{Signal, Strategy};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestReport {
+    pub total_return_pct: Decimal,
+    pub max_drawdown_pct: Decimal,
+    /// Completed round trips (a buy followed by a sell)
+    pub num_trades: u32,
+    /// Fraction of round trips that closed at a profit, 0.0 - 1.0
+    pub win_rate: f64,
+}
+
+/// Replays historical klines through a strategy, going all-in on buy signals
+/// and fully exiting on sell signals. Fills happen at the candle close.
+pub struct Backtester {
+    strategy: Box<dyn Strategy>,
+    klines: Vec<Kline>,
+    starting_balance: Decimal,
+    fee_rate: Decimal,
+}
+
+impl Backtester {
+    pub fn new(
+        strategy: Box<dyn Strategy>,
+        klines: Vec<Kline>,
+        starting_balance: Decimal,
+        fee_rate: Decimal,
+    ) -> Self {
+        Self {
+            strategy,
+            klines,
+            starting_balance,
+            fee_rate,
+        }
+    }
+
+    pub async fn run(&self) -> BacktestReport {
+        let symbol = "BACKTEST".to_string();
+
+        let mut cash = self.starting_balance;
+        let mut holdings = dec!(0);
+        let mut entry_cost = dec!(0);
+
+        let mut peak_equity = self.starting_balance;
+        let mut max_drawdown_pct = dec!(0);
+        let mut num_trades = 0u32;
+        let mut wins = 0u32;
+
+        for i in 0..self.klines.len() {
+            let price = self.klines[i].close_decimal();
+            let market_data = MarketData {
+                symbol: symbol.clone(),
+                current_price: price,
+                klines: self.klines[..=i].to_vec(),
+                timestamp: self.klines[i].close_time,
+                mid_price: None,
+            };
+
+            match self.strategy.analyze(&market_data).await {
+                Signal::Buy { .. } if holdings == dec!(0) && cash > dec!(0) && price > dec!(0) => {
+                    holdings = cash * (dec!(1) - self.fee_rate) / price;
+                    entry_cost = cash;
+                    cash = dec!(0);
+                    debug!("Backtest BUY {} @ {}", holdings, price);
+                }
+                Signal::Sell { .. } if holdings > dec!(0) => {
+                    cash = holdings * price * (dec!(1) - self.fee_rate);
+                    debug!("Backtest SELL {} @ {}", holdings, price);
+
+                    num_trades += 1;
+                    if cash > entry_cost {
+                        wins += 1;
+                    }
+                    holdings = dec!(0);
+                }
+                _ => {}
+            }
+
+            let equity = cash + holdings * price;
+            peak_equity = peak_equity.max(equity);
+            if peak_equity > dec!(0) {
+                let drawdown_pct = (peak_equity - equity) / peak_equity * dec!(100);
+                max_drawdown_pct = max_drawdown_pct.max(drawdown_pct);
+            }
+        }
+
+        let final_price = self
+            .klines
+            .last()
+            .map(|k| k.close_decimal())
+            .unwrap_or_default();
+        let final_equity = cash + holdings * final_price;
+
+        let total_return_pct = if self.starting_balance > dec!(0) {
+            (final_equity - self.starting_balance) / self.starting_balance * dec!(100)
+        } else {
+            dec!(0)
+        };
+
+        let win_rate = if num_trades > 0 {
+            wins as f64 / num_trades as f64
+        } else {
+            0.0
+        };
+
+        BacktestReport {
+            total_return_pct,
+            max_drawdown_pct,
+            num_trades,
+            win_rate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::MaCrossoverStrategy;
+
+    fn create_klines(close_prices: &[u32]) -> Vec<Kline> {
+        close_prices
+            .iter()
+            .enumerate()
+            .map(|(i, price)| Kline {
+                open_time: i as u64 * 3600000,
+                open: price.to_string(),
+                high: price.to_string(),
+                low: price.to_string(),
+                close: price.to_string(),
+                volume: "100".to_string(),
+                close_time: (i as u64 + 1) * 3600000,
+                quote_asset_volume: "10000".to_string(),
+                number_of_trades: 100,
+                taker_buy_base_asset_volume: "50".to_string(),
+                taker_buy_quote_asset_volume: "5000".to_string(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sma_up_then_down_is_profitable() {
+        // Dip, strong rally (golden cross), then a sell-off (death cross)
+        let klines = create_klines(&[
+            110, 108, 106, 104, 102, 100, 104, 110, 118, 128, 140, 150, 155, 150, 140, 125, 110,
+        ]);
+        let strategy = Box::new(MaCrossoverStrategy::new(2, 4, dec!(0)));
+
+        let report = Backtester::new(strategy, klines, dec!(1000), dec!(0.001))
+            .run()
+            .await;
+
+        assert!(report.total_return_pct > dec!(0), "report: {:?}", report);
+        assert_eq!(report.num_trades, 1);
+        assert_eq!(report.win_rate, 1.0);
+        assert!(report.max_drawdown_pct > dec!(0));
+    }
+
+    #[tokio::test]
+    async fn test_no_signals_keeps_balance() {
+        let klines = create_klines(&[100, 100, 100, 100, 100, 100]);
+        let strategy = Box::new(MaCrossoverStrategy::new(2, 4, dec!(0)));
+
+        let report = Backtester::new(strategy, klines, dec!(1000), dec!(0.001))
+            .run()
+            .await;
+
+        assert_eq!(report.total_return_pct, dec!(0));
+        assert_eq!(report.max_drawdown_pct, dec!(0));
+        assert_eq!(report.num_trades, 0);
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use crate::exchange::{Kline, WsKline};
+
+/// The last `capacity` candles of one symbol, kept current from kline stream
+/// updates so the strategy can run on every close without refetching the
+/// history over REST.
+#[derive(Debug, Clone)]
+pub struct CandleBuffer {
+    capacity: usize,
+    klines: VecDeque<Kline>,
+    /// Open time of the newest candle reported closed, so a repeated close
+    /// event doesn't count twice
+    last_closed: Option<u64>,
+}
+
+impl CandleBuffer {
+    /// Starts from `history`, oldest first, as returned by the klines
+    /// endpoint. Its last candle may still be forming.
+    pub fn new(capacity: usize, history: Vec<Kline>) -> Self {
+        let mut klines = VecDeque::from(history);
+        while klines.len() > capacity {
+            klines.pop_front();
+        }
+
+        Self {
+            capacity,
+            klines,
+            last_closed: None,
+        }
+    }
+
+    /// Applies a stream update to the candle it belongs to, starting a new
+    /// candle when the update opens one. Returns whether it closed a candle
+    /// not seen closed before. Updates for candles older than the newest are
+    /// ignored.
+    pub fn apply(&mut self, update: &WsKline) -> bool {
+        let kline = Kline::from(update);
+        match self.klines.back() {
+            Some(newest) if kline.open_time < newest.open_time => return false,
+            Some(newest) if kline.open_time == newest.open_time => {
+                *self.klines.back_mut().unwrap() = kline;
+            }
+            _ => {
+                self.klines.push_back(kline);
+                if self.klines.len() > self.capacity {
+                    self.klines.pop_front();
+                }
+            }
+        }
+
+        if !update.is_closed || self.last_closed >= Some(update.open_time) {
+            return false;
+        }
+        self.last_closed = Some(update.open_time);
+        true
+    }
+
+    pub fn klines(&self) -> Vec<Kline> {
+        self.klines.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(open_time: u64, close: &str, is_closed: bool) -> WsKline {
+        WsKline {
+            open_time,
+            close_time: open_time + 59_999,
+            interval: "1m".to_string(),
+            open: close.to_string(),
+            high: close.to_string(),
+            low: close.to_string(),
+            close: close.to_string(),
+            volume: "1".to_string(),
+            number_of_trades: 1,
+            is_closed,
+            quote_asset_volume: close.to_string(),
+            taker_buy_base_asset_volume: "0".to_string(),
+            taker_buy_quote_asset_volume: "0".to_string(),
+        }
+    }
+
+    fn closes(buffer: &CandleBuffer) -> Vec<String> {
+        buffer.klines().into_iter().map(|k| k.close).collect()
+    }
+
+    #[test]
+    fn test_updates_replace_the_forming_candle_and_roll_the_window() {
+        let history = vec![
+            Kline::from(&update(0, "1", true)),
+            Kline::from(&update(60_000, "2", false)),
+        ];
+        let mut buffer = CandleBuffer::new(2, history);
+
+        assert!(!buffer.apply(&update(60_000, "3", false)));
+        assert_eq!(closes(&buffer), ["1", "3"]);
+
+        assert!(buffer.apply(&update(60_000, "4", true)));
+        assert!(!buffer.apply(&update(120_000, "5", false)));
+        assert_eq!(closes(&buffer), ["4", "5"]);
+
+        // Late updates for a candle already rolled past change nothing
+        assert!(!buffer.apply(&update(60_000, "9", true)));
+        assert_eq!(closes(&buffer), ["4", "5"]);
+    }
+
+    #[test]
+    fn test_repeated_close_counts_once() {
+        let mut buffer = CandleBuffer::new(10, Vec::new());
+
+        assert!(buffer.apply(&update(0, "1", true)));
+        assert!(!buffer.apply(&update(0, "1", true)));
+        assert!(buffer.apply(&update(60_000, "2", true)));
+    }
+}
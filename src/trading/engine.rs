@@ -1,315 +1,5686 @@
 use anyhow::Result;
-use rust_decimal::Decimal;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use futures_util::stream::{self, StreamExt};
+use rust_decimal::{Decimal, RoundingStrategy};
 use rust_decimal_macros::dec;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::OnceCell;
 use tracing::{debug, error, info, warn};
 
-use crate::exchange::{BinanceClient, OrderRequest, OrderSide};
-use crate::risk::RiskManager;
-use crate::strategy::{Signal, Strategy};
+use crate::clock::Clock;
+use crate::exchange::{
+    value_balances, AccountInfo, Balance, BinanceError, BinanceWebSocket, CostBasis, Exchange,
+    OcoOrderRequest, OrderRequest, OrderResponse, OrderSide, OrderType, QueryOrderResponse,
+    SymbolInfo, Ticker24h, WsKlineUpdate, WsMessage, ORDER_BOOK_PRICING_DEPTH,
+};
+use crate::metrics::{ANALYZE_LATENCY, ORDERS_PLACED};
+use crate::notify::{Notification, Notifier};
+use crate::risk::{check_slippage, run_daily_reset, Position, RiskError, RiskManager};
+use crate::strategy::{calculate_atr, Signal, Strategy};
+
+use super::approval::OrderApprover;
+use super::candles::CandleBuffer;
+#[cfg(feature = "journal")]
+use super::journal::{TradeJournal, TradeRecord};
+use super::paper::PaperWallet;
+use super::plan::{Decision, PlannedAction};
+use super::session::TradingWindow;
+use super::signal_log::{SignalLog, SignalRecord, DEFAULT_SIGNAL_LOG_CAPACITY};
+use super::smoothing::{SignalSmoother, SignalSmoothing};
+use super::twap::TwapExecutor;
+
+/// Decimal places kept on quote amounts sent as `quoteOrderQty`.
+pub(super) const QUOTE_PRECISION: u32 = 8;
+
+/// Asset the account's equity is valued in for the drawdown limit unless
+/// `with_equity_quote_asset` says otherwise.
+const DEFAULT_EQUITY_QUOTE_ASSET: &str = "USDT";
+
+pub const DEFAULT_FILL_POLL_INTERVAL_MS: u64 = 500;
+pub const DEFAULT_FILL_POLL_ATTEMPTS: u32 = 5;
+
+/// Most recent orders per symbol replayed when rebuilding positions.
+const ORDER_HISTORY_LIMIT: u32 = 500;
+
+/// What actually executed for a placed order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fill {
+    quantity: Decimal,
+    avg_price: Decimal,
+}
+
+/// A limit order left on the book, checked each cycle for fills and
+/// cancelled once it outlives `order_ttl`.
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    symbol: String,
+    order_id: u64,
+    side: OrderSide,
+    placed_at: DateTime<Utc>,
+    /// What had filled when it was placed, already booked
+    booked: Fill,
+}
 
 pub struct TradingEngine {
-    client: BinanceClient,
-    risk_manager: RiskManager,
+    client: Box<dyn Exchange>,
+    risk_manager: Arc<RiskManager>,
     strategy: Box<dyn Strategy>,
+    /// Strategies for particular symbols; the rest use `strategy`
+    symbol_strategies: HashMap<String, Box<dyn Strategy>>,
     symbols: Vec<String>,
     paper_trading: bool,
+    max_concurrent_symbols: usize,
+    cancel_orders_on_shutdown: bool,
+    /// Cancel a symbol's open orders when they lock base asset a sell
+    /// needs, rather than selling only the free balance
+    cancel_orders_to_sell: bool,
+    /// Percentage a limit order may be priced through the current price;
+    /// `None` places market orders
+    limit_slippage_pct: Option<Decimal>,
+    /// Percentage the price may move between signal and market order
+    /// before the order is abandoned; `None` disables the check
+    market_slippage_pct: Option<Decimal>,
+    validate_paper_orders: bool,
+    daily_reset_tz: Tz,
+    /// Asset the drawdown limit values the account in
+    equity_quote_asset: String,
+    fill_poll_interval: tokio::time::Duration,
+    fill_poll_attempts: u32,
+    /// Market orders at or above this notional are split by the executor
+    twap: Option<(TwapExecutor, Decimal)>,
+    /// Cleared by the HTTP API or `emergency_stop` to skip cycles until set
+    trading_enabled: Arc<AtomicBool>,
+    /// Daily loss percentage that triggers `emergency_stop`; a hard limit
+    /// above the risk manager's soft `max_daily_loss_pct`
+    circuit_breaker_pct: Option<Decimal>,
+    /// Signals on a symbol are ignored for this long after it trades
+    cooldown: tokio::time::Duration,
+    last_trade_time: RwLock<HashMap<String, DateTime<Utc>>>,
+    /// Client order id of the last order placed on each symbol and side, so
+    /// a repeat of the same intent is never sent; Binance only rejects a
+    /// repeated id while the first order is still open
+    placed_order_ids: RwLock<HashMap<(String, OrderSide), String>>,
+    /// Limit orders still open this long after placement are cancelled;
+    /// `None` leaves them on the book
+    order_ttl: Option<tokio::time::Duration>,
+    /// Re-place the unfilled rest of an expired order at the current price
+    reprice_expired_orders: bool,
+    resting_orders: RwLock<Vec<RestingOrder>>,
+    /// Exchange info for the configured symbols, fetched on first trade
+    symbol_info: OnceCell<HashMap<String, SymbolInfo>>,
+    /// Simulated balances used instead of the account in paper mode
+    paper_wallet: Option<PaperWallet>,
+    /// Market data older than this is not acted on; `None` trusts any age
+    max_data_age: Option<tokio::time::Duration>,
+    /// Symbols quoted in a stable asset that traded less than this over 24
+    /// hours are skipped
+    min_quote_volume: Option<Decimal>,
+    /// UTC windows the strategy trades in; empty trades around the clock
+    trading_hours: Vec<TradingWindow>,
+    /// How long a fetched account snapshot is reused; zero fetches it
+    /// whenever it is needed
+    account_cache_ttl: tokio::time::Duration,
+    /// Last account snapshot and when it was fetched, dropped whenever an
+    /// order is placed or resolved
+    account_cache: RwLock<Option<(DateTime<Utc>, AccountInfo)>>,
+    /// Quote asset that buys leave in the account
+    quote_reserve: Decimal,
+    /// Asset fees are paid in, e.g. BNB, and the balance of it below which
+    /// buys warn that fees may soon come out of the traded assets instead
+    fee_asset: Option<(String, Decimal)>,
+    /// Marks sells reduce-only, so they never sell more than the tracked
+    /// position or the free balance
+    reduce_only: bool,
+    /// Where, and how often, the risk manager's state is saved
+    state_file: Option<(PathBuf, tokio::time::Duration)>,
+    /// Recent signals and the indicator values behind them
+    signal_log: Arc<SignalLog>,
+    /// Smooths strategy signals across cycles before they are acted on;
+    /// `None` acts on each cycle's signal as is
+    signal_smoother: Option<SignalSmoother>,
+    notifiers: Vec<Box<dyn Notifier>>,
+    /// Asked before each live order is submitted
+    approver: Option<Box<dyn OrderApprover>>,
+    /// Set once the daily loss limit has been reported, until it lifts
+    daily_loss_notified: AtomicBool,
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "journal")]
+    journal: Option<TradeJournal>,
 }
 
 impl TradingEngine {
     pub fn new(
-        client: BinanceClient,
+        client: Box<dyn Exchange>,
         risk_manager: RiskManager,
         strategy: Box<dyn Strategy>,
         symbols: Vec<String>,
         paper_trading: bool,
     ) -> Self {
+        let clock = risk_manager.clock();
         Self {
             client,
-            risk_manager,
+            risk_manager: Arc::new(risk_manager),
             strategy,
+            symbol_strategies: HashMap::new(),
             symbols,
             paper_trading,
+            max_concurrent_symbols: 1,
+            cancel_orders_on_shutdown: false,
+            cancel_orders_to_sell: false,
+            limit_slippage_pct: None,
+            market_slippage_pct: None,
+            validate_paper_orders: false,
+            daily_reset_tz: Tz::UTC,
+            equity_quote_asset: DEFAULT_EQUITY_QUOTE_ASSET.to_string(),
+            fill_poll_interval: tokio::time::Duration::from_millis(DEFAULT_FILL_POLL_INTERVAL_MS),
+            fill_poll_attempts: DEFAULT_FILL_POLL_ATTEMPTS,
+            twap: None,
+            trading_enabled: Arc::new(AtomicBool::new(true)),
+            circuit_breaker_pct: None,
+            cooldown: tokio::time::Duration::ZERO,
+            last_trade_time: RwLock::new(HashMap::new()),
+            placed_order_ids: RwLock::new(HashMap::new()),
+            order_ttl: None,
+            reprice_expired_orders: false,
+            resting_orders: RwLock::new(Vec::new()),
+            symbol_info: OnceCell::new(),
+            paper_wallet: None,
+            max_data_age: None,
+            min_quote_volume: None,
+            trading_hours: Vec::new(),
+            account_cache_ttl: tokio::time::Duration::ZERO,
+            account_cache: RwLock::new(None),
+            quote_reserve: dec!(0),
+            fee_asset: None,
+            reduce_only: false,
+            state_file: None,
+            signal_log: Arc::new(SignalLog::new(DEFAULT_SIGNAL_LOG_CAPACITY)),
+            signal_smoother: None,
+            notifiers: Vec::new(),
+            approver: None,
+            daily_loss_notified: AtomicBool::new(false),
+            clock,
+            #[cfg(feature = "journal")]
+            journal: None,
         }
     }
 
-    pub async fn run(&mut self, interval_ms: u64) -> Result<()> {
-        info!("Starting trading engine with {} symbols", self.symbols.len());
+    /// How many symbols are fetched and checked at the same time within one
+    /// cycle. Signals are still acted on one symbol at a time.
+    pub fn with_max_concurrent_symbols(mut self, max: usize) -> Self {
+        self.max_concurrent_symbols = max.max(1);
+        self
+    }
 
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(interval_ms));
+    pub fn with_cancel_orders_on_shutdown(mut self, cancel: bool) -> Self {
+        self.cancel_orders_on_shutdown = cancel;
+        self
+    }
 
-        loop {
-            interval.tick().await;
+    /// Frees base asset locked in a symbol's open orders, e.g. its bracket
+    /// exit, by cancelling them before a live sell. Off, sells are sized from
+    /// the free balance alone.
+    pub fn with_cancel_orders_to_sell(mut self, cancel: bool) -> Self {
+        self.cancel_orders_to_sell = cancel;
+        self
+    }
 
-            if let Err(e) = self.run_once().await {
-                error!("Trading cycle error: {}", e);
-            }
-        }
+    /// Places limit orders instead of market orders, priced up to
+    /// `slippage_tolerance_pct` above the current price for buys and below
+    /// it for sells.
+    pub fn with_limit_orders(mut self, slippage_tolerance_pct: Decimal) -> Self {
+        self.limit_slippage_pct = Some(slippage_tolerance_pct);
+        self
     }
 
-    pub async fn run_once(&mut self) -> Result<()> {
-        debug!("Running trading cycle");
+    /// Cancels limit orders still open `ttl` after they were placed, so a
+    /// price that ran away doesn't leave them on the book indefinitely.
+    pub fn with_order_ttl(mut self, ttl: tokio::time::Duration) -> Self {
+        self.order_ttl = Some(ttl);
+        self
+    }
 
-        // Check if we can trade
-        if !self.risk_manager.can_trade() {
-            warn!("Risk limits reached, skipping trading cycle");
+    /// Re-places what an order cancelled by `with_order_ttl` left unfilled,
+    /// at a limit price off the current one.
+    pub fn with_expired_order_repricing(mut self) -> Self {
+        self.reprice_expired_orders = true;
+        self
+    }
+
+    /// Abandons market orders when the price has moved more than
+    /// `tolerance_pct` from the one that produced the signal.
+    pub fn with_slippage_protection(mut self, tolerance_pct: Decimal) -> Self {
+        self.market_slippage_pct = Some(tolerance_pct);
+        self
+    }
+
+    /// In paper mode, sends each simulated order to the exchange's test
+    /// endpoint so filter rejections (LOT_SIZE, NOTIONAL, ...) show up.
+    pub fn with_paper_order_validation(mut self, validate: bool) -> Self {
+        self.validate_paper_orders = validate;
+        self
+    }
+
+    /// Timezone whose midnight starts a new trading day for the daily loss limit.
+    pub fn with_daily_reset_timezone(mut self, tz: Tz) -> Self {
+        self.daily_reset_tz = tz;
+        self
+    }
+
+    /// Asset the account is valued in for the drawdown limit. Balances with
+    /// no direct pair against it are left out of the equity.
+    pub fn with_equity_quote_asset(mut self, asset: impl Into<String>) -> Self {
+        self.equity_quote_asset = asset.into();
+        self
+    }
+
+    /// How often, and how many times, a live order is re-queried after
+    /// placement while waiting for it to fill.
+    pub fn with_fill_polling(mut self, interval: tokio::time::Duration, attempts: u32) -> Self {
+        self.fill_poll_interval = interval;
+        self.fill_poll_attempts = attempts;
+        self
+    }
+
+    /// Routes market orders worth at least `min_notional` (in the quote
+    /// asset) through `executor` instead of placing them in one go. The
+    /// cycle waits out the whole execution, so exits on other symbols are
+    /// held up for as long as the executor's duration.
+    pub fn with_twap(mut self, executor: TwapExecutor, min_notional: Decimal) -> Self {
+        self.twap = Some((executor, min_notional));
+        self
+    }
+
+    /// Minimum time between trades on the same symbol. Buy and sell signals
+    /// inside the window are treated as hold; trailing stop exits are not.
+    pub fn with_cooldown(mut self, cooldown: tokio::time::Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Calls `emergency_stop` once the daily loss exceeds `max_daily_loss_pct`.
+    /// Unlike the risk manager's soft limit, this does not lift at the daily
+    /// reset: trading stays off until re-enabled by hand.
+    pub fn with_circuit_breaker(mut self, max_daily_loss_pct: Decimal) -> Self {
+        self.circuit_breaker_pct = Some(max_daily_loss_pct);
+        self
+    }
+
+    /// Keeps `reserve` USD out of every buy quoted in a stablecoin: orders
+    /// are sized and risk-checked against the free balance above it. Other
+    /// quote assets have no fixed USD value and are spent in full.
+    pub fn with_quote_reserve(mut self, reserve: Decimal) -> Self {
+        self.quote_reserve = reserve;
+        self
+    }
+
+    /// Warns on buys while the account holds less than `min_balance` of
+    /// `asset`, the asset Binance is set to take fees in.
+    pub fn with_fee_asset(mut self, asset: &str, min_balance: Decimal) -> Self {
+        self.fee_asset = Some((asset.to_string(), min_balance));
+        self
+    }
+
+    /// Caps every sell at the base asset the bot holds for the symbol: the
+    /// tracked position when there is one, and never more than the free
+    /// balance.
+    pub fn with_reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    /// Saves the risk manager's state to `path` every `interval` while
+    /// running, and once more at shutdown.
+    pub fn with_state_file(mut self, path: PathBuf, interval: tokio::time::Duration) -> Self {
+        self.state_file = Some((path, interval));
+        self
+    }
+
+    /// Skips symbols whose market data, or newest kline, is older than
+    /// `max_age`, so a stalled feed can't trigger trades.
+    pub fn with_max_data_age(mut self, max_age: tokio::time::Duration) -> Self {
+        self.max_data_age = Some(max_age);
+        self
+    }
+
+    /// Skips symbols quoted in a stable asset whose 24h quote volume is
+    /// below `min_volume` USD, unless a position is open in them.
+    pub fn with_min_24h_quote_volume(mut self, min_volume: Decimal) -> Self {
+        self.min_quote_volume = Some(min_volume);
+        self
+    }
+
+    /// Only acts on signals while the clock is in one of `windows`. Outside
+    /// them, stop-loss, take-profit and trailing stop exits still run.
+    pub fn with_trading_hours(mut self, windows: Vec<TradingWindow>) -> Self {
+        self.trading_hours = windows;
+        self
+    }
+
+    /// In paper mode, reads balances from `wallet` instead of the account
+    /// and applies each simulated order to it.
+    pub fn with_paper_wallet(mut self, wallet: PaperWallet) -> Self {
+        self.paper_wallet = Some(wallet);
+        self
+    }
+
+    /// Reuses the account snapshot for up to `ttl` instead of fetching it
+    /// every cycle. Placing an order, or an order filling or being
+    /// cancelled, always makes the next cycle fetch a fresh one.
+    pub fn with_account_cache_ttl(mut self, ttl: tokio::time::Duration) -> Self {
+        self.account_cache_ttl = ttl;
+        self
+    }
+
+    /// Trades `symbol` on `strategy` instead of the engine's default one.
+    pub fn with_symbol_strategy(mut self, symbol: &str, strategy: Box<dyn Strategy>) -> Self {
+        self.symbol_strategies.insert(symbol.to_string(), strategy);
+        self
+    }
+
+    /// How many recent signals the signal log keeps.
+    pub fn with_signal_log_capacity(mut self, capacity: usize) -> Self {
+        self.signal_log = Arc::new(SignalLog::new(capacity));
+        self
+    }
+
+    /// Acts on signals only once their strength, smoothed across cycles,
+    /// has held above the threshold for long enough. Protective exits are
+    /// not smoothed.
+    pub fn with_signal_smoothing(mut self, smoothing: SignalSmoothing) -> Self {
+        self.signal_smoother = Some(SignalSmoother::new(smoothing));
+        self
+    }
+
+    /// Adds a notifier for fills, rejected orders, the daily loss limit,
+    /// emergency stops and each cycle's signals.
+    pub fn with_notifier(mut self, notifier: Box<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Has `approver` confirm every live order before it is submitted.
+    /// Bracket exits placed after a fill protect it and go out unasked.
+    pub fn with_order_approver(mut self, approver: Box<dyn OrderApprover>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    /// Time source for cooldowns and data age checks; defaults to the risk
+    /// manager's.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    #[cfg(feature = "journal")]
+    pub fn with_journal(mut self, journal: TradeJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Shared risk state, for readers outside the trading loop.
+    pub fn risk_manager(&self) -> Arc<RiskManager> {
+        self.risk_manager.clone()
+    }
+
+    /// Shared handle on the recent signals, e.g. for the HTTP API.
+    pub fn signal_log(&self) -> Arc<SignalLog> {
+        Arc::clone(&self.signal_log)
+    }
+
+    /// Flag that allows trading while set. Checked at the start of each
+    /// cycle, so a cycle already running completes.
+    pub fn trading_enabled(&self) -> Arc<AtomicBool> {
+        self.trading_enabled.clone()
+    }
+
+    /// Kill switch: disables trading and cancels every open order on the
+    /// configured symbols.
+    pub async fn emergency_stop(&self) {
+        error!("Emergency stop: disabling trading and cancelling open orders");
+        self.trading_enabled.store(false, Ordering::SeqCst);
+
+        if !self.paper_trading {
+            self.cancel_open_orders().await;
+        }
+
+        self.notify(Notification::EmergencyStop).await;
+    }
+
+    /// Rebuilds positions on the configured symbols that the risk manager
+    /// doesn't track, e.g. after a restart without a state file, from each
+    /// symbol's order history. Quantities are capped at what the account
+    /// holds, since deposits, withdrawals and fees don't show up in orders,
+    /// and holdings too small to sell are left alone.
+    pub async fn reconcile_positions(&self) -> Result<()> {
+        if self.paper_trading {
             return Ok(());
         }
 
-        // Get account info for balance checks
-        let account = self.client.get_account_info().await?;
+        let balances = self.client.get_account_info().await?.balances;
+        for symbol in &self.symbols {
+            if self.risk_manager.position(symbol).is_some() {
+                continue;
+            }
 
-        for symbol in self.symbols.clone() {
-            if let Err(e) = self.process_symbol(&symbol, &account.balances).await {
-                error!("Error processing {}: {}", symbol, e);
+            let orders = match self
+                .client
+                .get_all_orders(symbol, ORDER_HISTORY_LIMIT)
+                .await
+            {
+                Ok(orders) => orders,
+                Err(e) => {
+                    warn!("Could not fetch order history for {}: {}", symbol, e);
+                    continue;
+                }
+            };
+            let basis = CostBasis::from_orders(&orders);
+            let Some(entry_price) = basis.average_price() else {
+                continue;
+            };
+
+            let info = self.symbol_info(symbol).await?;
+            let held = balance_or_zero(&balances, &info.base_asset).total();
+            let quantity = basis.quantity.min(held);
+            let min_notional = self.risk_manager.min_order_notional_for(&info.quote_asset);
+            if quantity <= dec!(0) || quantity * entry_price < min_notional {
+                continue;
             }
+
+            info!(
+                "Rebuilt {} position from order history: {} at {}",
+                symbol,
+                quantity,
+                entry_price.round_dp(8)
+            );
+            self.risk_manager.open_position(Position::new(
+                symbol,
+                entry_price,
+                quantity,
+                OrderSide::Buy,
+                self.clock.now_ms(),
+            ));
         }
 
         Ok(())
     }
 
-    async fn process_symbol(
-        &self,
-        symbol: &str,
-        balances: &[crate::exchange::Balance],
-    ) -> Result<()> {
-        debug!("Processing symbol: {}", symbol);
+    /// Trips the circuit breaker if the daily loss is past it. Returns
+    /// whether it tripped.
+    async fn check_circuit_breaker(&self) -> bool {
+        let Some(limit) = self.circuit_breaker_pct else {
+            return false;
+        };
 
-        // Get market data
-        let required_history = self.strategy.required_history() as u32;
-        let market_data = self
-            .client
-            .get_market_data(symbol, required_history.max(50))
-            .await?;
+        let daily_loss = self.risk_manager.current_daily_loss();
+        if daily_loss <= limit || !self.trading_enabled.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        error!(
+            "Daily loss {}% exceeds circuit breaker of {}%",
+            daily_loss, limit
+        );
+        self.emergency_stop().await;
+        true
+    }
 
+    pub async fn run(&mut self, interval_ms: u64) -> Result<()> {
         info!(
-            "{}: Current price = {}, Klines = {}",
-            symbol,
-            market_data.current_price,
-            market_data.klines.len()
+            "Starting trading engine with {} symbols on {} candles",
+            self.symbols.len(),
+            self.strategy.kline_interval()
         );
 
-        // Analyze with strategy
-        let signal = self.strategy.analyze(&market_data).await;
+        // A read-only key would fail every order of every cycle
+        if !self.paper_trading {
+            ensure_can_trade(&self.client.get_account_info().await?)?;
+        }
 
-        match &signal {
-            Signal::Buy { strength } => {
-                info!("{}: BUY signal with strength {:.2}", symbol, strength);
-                self.execute_buy(symbol, &market_data, balances, *strength)
-                    .await?;
-            }
-            Signal::Sell { strength } => {
-                info!("{}: SELL signal with strength {:.2}", symbol, strength);
-                self.execute_sell(symbol, &market_data, balances, *strength)
-                    .await?;
-            }
-            Signal::Hold => {
-                debug!("{}: HOLD - no action", symbol);
+        let period = tokio::time::Duration::from_millis(interval_ms);
+        let mut interval = cycle_interval(period);
+
+        let background = self.spawn_background_tasks();
+
+        // Only checked between cycles, so Ctrl-C never interrupts a cycle halfway
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let started = tokio::time::Instant::now();
+                    let result = self.run_once().await;
+                    let elapsed = started.elapsed();
+                    if elapsed > period {
+                        warn!(
+                            "Trading cycle took {:?}, longer than the {:?} interval; skipping missed ticks",
+                            elapsed, period
+                        );
+                    }
+
+                    if let Err(e) = result {
+                        if is_fatal(&e) {
+                            error!("Fatal exchange error, stopping: {}", e);
+                            self.shutdown().await?;
+                            return Err(e);
+                        }
+                        error!("Trading cycle error: {}", e);
+                    }
+                }
+                _ = &mut ctrl_c => {
+                    info!("Shutdown signal received");
+                    break;
+                }
             }
         }
 
-        Ok(())
+        for task in background {
+            task.abort();
+        }
+        self.shutdown().await
     }
 
-    async fn execute_buy(
-        &self,
-        symbol: &str,
-        market_data: &crate::exchange::MarketData,
-        balances: &[crate::exchange::Balance],
-        signal_strength: f64,
-    ) -> Result<()> {
-        // Find quote asset balance (assume USDT for now)
-        let quote_asset = if symbol.ends_with("USDT") {
-            "USDT"
-        } else if symbol.ends_with("BTC") {
-            "BTC"
-        } else {
-            "USDT"
-        };
-
-        let quote_balance = balances
-            .iter()
-            .find(|b| b.asset == quote_asset)
-            .ok_or_else(|| anyhow::anyhow!("Quote balance not found for {}", quote_asset))?;
-
-        // Calculate position size based on signal strength and risk settings
-        let risk_pct = dec!(1) + Decimal::try_from(signal_strength).unwrap_or(dec!(0));
-        let quantity = self.risk_manager.calculate_position_size(
-            quote_balance.free_decimal(),
-            risk_pct,
-            market_data.current_price,
+    /// Like `run`, but driven by the kline WebSocket stream instead of a
+    /// timer: each symbol is analyzed as soon as one of its candles closes,
+    /// on a buffer of candles kept from the stream. Only balances, orders
+    /// and the opening history still go over REST.
+    pub async fn run_streaming(&mut self, websocket: &BinanceWebSocket) -> Result<()> {
+        info!(
+            "Starting trading engine with {} symbols on streamed candles",
+            self.symbols.len()
         );
 
-        if quantity <= dec!(0) {
-            warn!("Calculated quantity is zero or negative, skipping order");
-            return Ok(());
+        // A read-only key would fail every order of every cycle
+        if !self.paper_trading {
+            ensure_can_trade(&self.client.get_account_info().await?)?;
         }
 
-        // Round quantity to appropriate precision (simplified)
-        let quantity = self.round_quantity(quantity, symbol);
+        let mut buffers = self.seed_candle_buffers().await?;
+        let intervals: Vec<(String, String)> = self
+            .symbols
+            .iter()
+            .map(|symbol| {
+                let interval = self.strategy_for(symbol).kline_interval().to_string();
+                (symbol.clone(), interval)
+            })
+            .collect();
+        let mut stream = websocket.subscribe_klines_by_symbol(&intervals).await?;
 
-        let order = OrderRequest::market(symbol, OrderSide::Buy, quantity);
+        let background = self.spawn_background_tasks();
 
-        // Validate with risk manager
-        if let Err(e) = self
-            .risk_manager
-            .validate_order(&order, quote_balance, market_data.current_price)
-        {
-            warn!("Order rejected by risk manager: {}", e);
-            return Ok(());
-        }
+        // As with polling, Ctrl-C is only checked between cycles
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
 
-        // Execute or simulate
-        if self.paper_trading {
-            info!(
-                "[PAPER] Would BUY {} {} at {} (value: {} {})",
-                quantity,
-                symbol,
-                market_data.current_price,
-                quantity * market_data.current_price,
-                quote_asset
-            );
-        } else {
-            info!(
-                "Placing BUY order: {} {} at market price",
-                quantity, symbol
-            );
-            match self.client.place_order(&order).await {
-                Ok(response) => {
-                    info!(
-                        "Order placed successfully: ID={}, Status={}",
-                        response.order_id, response.status
-                    );
-                    self.risk_manager.increment_positions();
+        loop {
+            tokio::select! {
+                message = stream.recv() => {
+                    let Some(message) = message else {
+                        warn!("Kline stream ended, stopping");
+                        break;
+                    };
+                    if let Err(e) = self.on_stream_message(&mut buffers, message).await {
+                        if is_fatal(&e) {
+                            error!("Fatal exchange error, stopping: {}", e);
+                            self.shutdown().await?;
+                            return Err(e);
+                        }
+                        error!("Trading cycle error: {}", e);
+                    }
                 }
-                Err(e) => {
-                    error!("Failed to place order: {}", e);
+                _ = &mut ctrl_c => {
+                    info!("Shutdown signal received");
+                    break;
                 }
             }
         }
 
-        Ok(())
+        for task in background {
+            task.abort();
+        }
+        self.shutdown().await
     }
 
-    async fn execute_sell(
-        &self,
-        symbol: &str,
-        market_data: &crate::exchange::MarketData,
-        balances: &[crate::exchange::Balance],
-        signal_strength: f64,
-    ) -> Result<()> {
-        // Find base asset balance
-        let base_asset = symbol
-            .strip_suffix("USDT")
-            .or_else(|| symbol.strip_suffix("BTC"))
-            .or_else(|| symbol.strip_suffix("ETH"))
-            .unwrap_or(symbol);
+    /// Starts the daily loss reset and, with a state file, periodic state
+    /// saving. The caller aborts them on the way out.
+    fn spawn_background_tasks(&self) -> Vec<tokio::task::JoinHandle<()>> {
+        let mut tasks = vec![tokio::spawn(run_daily_reset(
+            self.risk_manager.clone(),
+            self.daily_reset_tz,
+        ))];
 
-        let base_balance = balances.iter().find(|b| b.asset == base_asset);
+        if let Some((path, every)) = self.state_file.clone() {
+            tasks.push(tokio::spawn(save_state_periodically(
+                self.risk_manager.clone(),
+                path,
+                every,
+            )));
+        }
+        tasks
+    }
 
-        let quantity = match base_balance {
-            Some(b) => {
-                let available = b.free_decimal();
-                if available <= dec!(0) {
-                    debug!("No {} available to sell", base_asset);
+    /// Candle buffers for every symbol, seeded with the same history a
+    /// polling cycle fetches.
+    async fn seed_candle_buffers(&self) -> Result<HashMap<String, CandleBuffer>> {
+        let mut buffers = HashMap::new();
+        for symbol in &self.symbols {
+            let strategy = self.strategy_for(symbol);
+            let capacity = strategy.required_history().max(50);
+            let market_data = self
+                .client
+                .get_market_data(symbol, strategy.kline_interval(), capacity as u32)
+                .await?;
+            buffers.insert(
+                symbol.clone(),
+                CandleBuffer::new(capacity, market_data.klines),
+            );
+        }
+        Ok(buffers)
+    }
+
+    /// Folds a stream message into `buffers`, running a cycle for the
+    /// symbol whenever one of its candles closes.
+    async fn on_stream_message(
+        &self,
+        buffers: &mut HashMap<String, CandleBuffer>,
+        message: WsMessage,
+    ) -> Result<()> {
+        match message {
+            WsMessage::Kline(update) => {
+                let Some(buffer) = buffers.get_mut(&update.symbol) else {
                     return Ok(());
+                };
+                if buffer.apply(&update.kline) {
+                    let market_data = self.streamed_market_data(&update, buffer);
+                    self.run_candle_cycle(market_data).await?;
                 }
-                // Sell portion based on signal strength
-                let sell_pct = Decimal::try_from(signal_strength).unwrap_or(dec!(0.5));
-                available * sell_pct
-            }
-            None => {
-                debug!("No {} balance found", base_asset);
-                return Ok(());
             }
-        };
-
-        if quantity <= dec!(0) {
-            return Ok(());
+            WsMessage::Connected => info!("Kline stream connected"),
+            WsMessage::Disconnected => warn!("Kline stream disconnected, reconnecting"),
+            WsMessage::Error(e) => warn!("Kline stream error: {}", e),
+            _ => {}
         }
+        Ok(())
+    }
 
-        let quantity = self.round_quantity(quantity, symbol);
-
-        let order = OrderRequest::market(symbol, OrderSide::Sell, quantity);
-
-        // Quote balance for validation (not really needed for sells but for consistency)
-        let quote_balance = balances
-            .iter()
-            .find(|b| b.asset == "USDT")
-            .cloned()
-            .unwrap_or(crate::exchange::Balance {
-                asset: "USDT".to_string(),
-                free: "0".to_string(),
-                locked: "0".to_string(),
-            });
+    /// Market data for a symbol whose candle `update` just closed. The close
+    /// stands in for the ticker price, the way the newest kline does when
+    /// polling.
+    fn streamed_market_data(
+        &self,
+        update: &WsKlineUpdate,
+        buffer: &CandleBuffer,
+    ) -> crate::exchange::MarketData {
+        crate::exchange::MarketData {
+            symbol: update.symbol.clone(),
+            current_price: update.kline.close.parse().unwrap_or_default(),
+            klines: buffer.klines(),
+            timestamp: self.clock.now_ms(),
+            mid_price: None,
+        }
+    }
 
-        if let Err(e) = self
-            .risk_manager
-            .validate_order(&order, &quote_balance, market_data.current_price)
-        {
-            warn!("Order rejected by risk manager: {}", e);
+    /// One cycle for a single symbol on streamed `market_data`: the same
+    /// checks, strategy and order handling as `run_once`, without fetching
+    /// candles.
+    async fn run_candle_cycle(&self, market_data: crate::exchange::MarketData) -> Result<()> {
+        let Some(balances) = self.begin_cycle().await? else {
             return Ok(());
-        }
+        };
 
-        if self.paper_trading {
-            info!(
-                "[PAPER] Would SELL {} {} at {} (value: {} USDT)",
-                quantity,
-                symbol,
-                market_data.current_price,
-                quantity * market_data.current_price
-            );
-        } else {
-            info!(
-                "Placing SELL order: {} {} at market price",
-                quantity, symbol
-            );
-            match self.client.place_order(&order).await {
-                Ok(response) => {
-                    info!(
-                        "Order placed successfully: ID={}, Status={}",
-                        response.order_id, response.status
-                    );
-                    self.risk_manager.decrement_positions();
+        let symbol = market_data.symbol.clone();
+        if self.is_tradable(&symbol).await && self.is_liquid(&symbol).await {
+            match self.classify(market_data, false) {
+                Some(Prepared::Exit(market_data, strength)) => {
+                    self.exit_position_logged(&market_data, strength, &balances)
+                        .await;
                 }
-                Err(e) => {
-                    error!("Failed to place order: {}", e);
+                Some(Prepared::Analyze(_)) if !self.in_session() => {
+                    info!("Outside trading hours, skipping signals on {}", symbol);
                 }
+                Some(Prepared::Analyze(market_data)) => {
+                    let signal = self.strategy_for(&symbol).analyze(&market_data).await;
+                    self.record_signal(self.clock.now(), &market_data, &signal);
+                    self.act_on_signal_logged(&market_data, signal, &balances)
+                        .await;
+                }
+                None => {}
             }
         }
 
+        self.end_cycle().await;
         Ok(())
     }
 
-    fn round_quantity(&self, quantity: Decimal, symbol: &str) -> Decimal {
-        // Simplified rounding - in production, fetch from exchange info
-        let precision = if symbol.starts_with("BTC") {
-            5
-        } else if symbol.starts_with("ETH") {
-            4
-        } else {
-            3
-        };
-
-        quantity.round_dp(precision)
-    }
-}
+    /// Winds the engine down: cancels resting orders if configured, flushes
+    /// the trade journal and logs what is still open.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        info!("Shutting down trading engine");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        if self.cancel_orders_on_shutdown && !self.paper_trading {
+            self.cancel_open_orders().await;
+        }
 
-    #[test]
-    fn test_round_quantity() {
-        // This is a simple test to verify the rounding logic
-        let quantity = dec!(0.123456789);
+        #[cfg(feature = "journal")]
+        if let Some(journal) = &self.journal {
+            if let Err(e) = journal.flush() {
+                error!("Failed to flush trade journal: {}", e);
+            }
+        }
 
-        // BTC precision = 5
-        assert_eq!(quantity.round_dp(5), dec!(0.12346));
+        if let Some((path, _)) = &self.state_file {
+            match self.risk_manager.save_state(path) {
+                Ok(()) => info!("Saved state to {}", path.display()),
+                Err(e) => error!("Failed to save state: {:#}", e),
+            }
+        }
 
-        // ETH precision = 4
-        assert_eq!(quantity.round_dp(4), dec!(0.1235));
+        let positions = self.risk_manager.positions();
+        info!(
+            "Shutdown complete: {} open position(s), daily loss {}%",
+            positions.len(),
+            self.risk_manager.current_daily_loss()
+        );
+        for position in positions {
+            info!(
+                "  {} {:?} {} @ {}",
+                position.symbol, position.side, position.quantity, position.entry_price
+            );
+        }
+
+        let performance = self.risk_manager.performance();
+        info!(
+            "Performance: {} trade(s), {} won, {} lost ({:.1}% win rate), total PnL {:.2}%, \
+             avg win {:.2}%, avg loss {:.2}%, profit factor {}",
+            performance.num_trades,
+            performance.wins,
+            performance.losses,
+            performance.win_rate * dec!(100),
+            performance.total_pnl_pct,
+            performance.avg_win,
+            performance.avg_loss,
+            performance
+                .profit_factor
+                .map_or("n/a".to_string(), |pf| pf.round_dp(2).to_string())
+        );
+
+        Ok(())
+    }
+
+    /// Cancels resting orders on the configured symbols, logging failures
+    /// so one symbol can't stop the rest from being cleaned up.
+    async fn cancel_open_orders(&self) {
+        for symbol in &self.symbols {
+            match self.client.cancel_all_open_orders(symbol).await {
+                Ok(cancelled) => {
+                    for order in cancelled {
+                        info!("Cancelled order {} on {}", order.order_id, order.symbol);
+                    }
+                }
+                Err(e) => error!("Failed to cancel open orders on {}: {}", symbol, e),
+            }
+        }
+    }
+
+    pub async fn run_once(&mut self) -> Result<()> {
+        debug!("Running trading cycle");
+
+        let Some(mut balances) = self.begin_cycle().await? else {
+            return Ok(());
+        };
+
+        // Fan out across symbols, keeping at most max_concurrent_symbols in
+        // flight, then analyze every symbol still in play in one batch
+        let prepared: Vec<_> = stream::iter(&self.symbols)
+            .map(|symbol| self.prepare_symbol_logged(symbol, false))
+            .buffer_unordered(self.max_concurrent_symbols)
+            .filter_map(|prepared| async move { prepared })
+            .collect()
+            .await;
+        let (ready, exits) = split_prepared(prepared);
+
+        stream::iter(&exits)
+            .map(|(market_data, strength)| {
+                self.exit_position_logged(market_data, *strength, &balances)
+            })
+            .buffer_unordered(self.max_concurrent_symbols)
+            .collect::<()>()
+            .await;
+
+        if !self.in_session() {
+            info!(
+                "Outside trading hours, skipping signals on {} symbol(s)",
+                ready.len()
+            );
+        } else if !ready.is_empty() {
+            let started = std::time::Instant::now();
+            let (ready, signals) = self.analyze(ready).await;
+            let per_symbol = started.elapsed().as_secs_f64() / ready.len() as f64;
+            let now = self.clock.now();
+            for (market_data, signal) in ready.iter().zip(&signals) {
+                ::metrics::histogram!(ANALYZE_LATENCY).record(per_symbol);
+                self.record_signal(now, market_data, signal);
+            }
+
+            // Signals are acted on one at a time, so each order sees the
+            // positions and balance the ones before it left; side by side,
+            // buys could overshoot max_open_positions or spend the same
+            // quote twice
+            for (market_data, signal) in ready.iter().zip(signals) {
+                let acted = !matches!(signal, Signal::Hold);
+                self.act_on_signal_logged(market_data, signal, &balances)
+                    .await;
+                if !acted {
+                    continue;
+                }
+                match self.trading_balances().await {
+                    Ok(refreshed) => balances = refreshed,
+                    Err(e) => {
+                        warn!(
+                            "Failed to refresh balances, acting on no more signals this cycle: {}",
+                            e
+                        );
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.end_cycle().await;
+        Ok(())
+    }
+
+    /// The checks that open every cycle. Returns the balances to trade
+    /// against, or `None` when trading is off and the cycle should be
+    /// skipped. With the risk limits hit the cycle still runs, for exits
+    /// and sells only.
+    async fn begin_cycle(&self) -> Result<Option<Vec<Balance>>> {
+        self.check_circuit_breaker().await;
+        if !self.trading_enabled.load(Ordering::SeqCst) {
+            info!("Trading disabled, skipping trading cycle");
+            return Ok(None);
+        }
+
+        self.check_resting_orders().await;
+
+        // Check if we can trade
+        if !self.risk_manager.can_trade() {
+            warn!("Risk limits reached, only exits and sells run this cycle");
+            self.notify_daily_loss_limit().await;
+        } else {
+            self.daily_loss_notified.store(false, Ordering::SeqCst);
+        }
+
+        // Get balances for balance checks
+        let balances = match &self.paper_wallet {
+            Some(wallet) if self.paper_trading => wallet.balances(),
+            _ => {
+                let account = self.account_info().await?;
+                if !self.paper_trading {
+                    ensure_can_trade(&account)?;
+                }
+                account.balances
+            }
+        };
+        Ok(Some(balances))
+    }
+
+    /// Balances to trade against: the paper wallet's in paper mode with
+    /// one, otherwise the account's.
+    async fn trading_balances(&self) -> Result<Vec<Balance>, BinanceError> {
+        match &self.paper_wallet {
+            Some(wallet) if self.paper_trading => Ok(wallet.balances()),
+            _ => Ok(self.account_info().await?.balances),
+        }
+    }
+
+    /// The account, from the cache while it is younger than
+    /// `account_cache_ttl`.
+    async fn account_info(&self) -> Result<AccountInfo, BinanceError> {
+        let now = self.clock.now();
+        if let Some((fetched_at, account)) = self.account_cache.read().unwrap().as_ref() {
+            if (now - *fetched_at)
+                .to_std()
+                .is_ok_and(|age| age < self.account_cache_ttl)
+            {
+                return Ok(account.clone());
+            }
+        }
+
+        let account = self.client.get_account_info().await?;
+        if !self.account_cache_ttl.is_zero() {
+            *self.account_cache.write().unwrap() = Some((now, account.clone()));
+        }
+        Ok(account)
+    }
+
+    /// Drops the cached account, whose balances an order has changed or is
+    /// about to.
+    fn invalidate_account_cache(&self) {
+        self.account_cache.write().unwrap().take();
+    }
+
+    async fn end_cycle(&self) {
+        self.update_equity().await;
+
+        // Losses realised this cycle trip the breaker now, not a cycle later
+        self.check_circuit_breaker().await;
+    }
+
+    fn record_signal(
+        &self,
+        timestamp: DateTime<Utc>,
+        market_data: &crate::exchange::MarketData,
+        signal: &Signal,
+    ) {
+        self.signal_log.record(SignalRecord {
+            timestamp,
+            symbol: market_data.symbol.clone(),
+            indicators: self
+                .strategy_for(&market_data.symbol)
+                .debug_state(market_data),
+            signal: signal.clone(),
+        });
+    }
+
+    /// What `run_once` would do right now, for checking a setup before
+    /// going live. Fetches market data and balances and runs every symbol
+    /// through its strategy, sizing and the risk checks, but places no
+    /// orders, paper ones included, and leaves positions and stops alone.
+    pub async fn dry_run_cycle(&self) -> Result<Vec<PlannedAction>> {
+        anyhow::ensure!(
+            self.trading_enabled.load(Ordering::SeqCst),
+            "Trading is disabled, a cycle would do nothing"
+        );
+
+        let balances = self.trading_balances().await?;
+
+        let prepared: Vec<_> = stream::iter(&self.symbols)
+            .map(|symbol| self.prepare_symbol_logged(symbol, true))
+            .buffer_unordered(self.max_concurrent_symbols)
+            .filter_map(|prepared| async move { prepared })
+            .collect()
+            .await;
+        let (ready, exits) = split_prepared(prepared);
+
+        let mut planned = Vec::new();
+        for (market_data, strength) in exits {
+            planned.push((market_data, Signal::Sell { strength }, true));
+        }
+        if !ready.is_empty() && self.in_session() {
+            let (ready, signals) = self.analyze(ready).await;
+            for (market_data, signal) in ready.into_iter().zip(signals) {
+                let signal = match &self.signal_smoother {
+                    Some(smoother) => smoother.peek(&market_data.symbol, &signal),
+                    None => signal,
+                };
+                let signal = self.unless_cooling_down(&market_data.symbol, signal);
+                planned.push((market_data, signal, false));
+            }
+        }
+
+        let mut actions = Vec::new();
+        for (market_data, signal, whole_position) in planned {
+            let decision = self
+                .plan(&market_data, &signal, &balances, whole_position)
+                .await
+                .unwrap_or_else(|e| Decision::Skipped {
+                    reason: e.to_string(),
+                });
+            actions.push(PlannedAction {
+                symbol: market_data.symbol,
+                signal,
+                decision,
+            });
+        }
+        actions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(actions)
+    }
+
+    /// Signals for each symbol in `ready`, paired with its market data.
+    /// Symbols on the default strategy are analyzed in one batch, the rest
+    /// one by one on their own strategies.
+    async fn analyze(
+        &self,
+        ready: Vec<crate::exchange::MarketData>,
+    ) -> (Vec<crate::exchange::MarketData>, Vec<Signal>) {
+        let (mut ready, own): (Vec<_>, Vec<_>) = ready
+            .into_iter()
+            .partition(|market_data| !self.symbol_strategies.contains_key(&market_data.symbol));
+
+        let mut signals = if ready.is_empty() {
+            Vec::new()
+        } else {
+            self.strategy.analyze_batch(&ready).await
+        };
+        for market_data in own {
+            signals.push(
+                self.strategy_for(&market_data.symbol)
+                    .analyze(&market_data)
+                    .await,
+            );
+            ready.push(market_data);
+        }
+        (ready, signals)
+    }
+
+    /// The strategy trading `symbol`.
+    fn strategy_for(&self, symbol: &str) -> &dyn Strategy {
+        self.symbol_strategies
+            .get(symbol)
+            .unwrap_or(&self.strategy)
+            .as_ref()
+    }
+
+    /// Checks the tracked limit orders resting on the book: books whatever
+    /// filled since the last check, stops tracking those that are done and
+    /// cancels those older than `order_ttl`, re-placing the unfilled rest
+    /// with repricing on. Orders that can't be checked are kept and tried
+    /// again next cycle.
+    async fn check_resting_orders(&self) {
+        let now = self.clock.now();
+        let resting: Vec<RestingOrder> = self.resting_orders.read().unwrap().clone();
+
+        for order in resting {
+            let mut status = match self.client.get_order(&order.symbol, order.order_id).await {
+                Ok(status) => Some(status),
+                Err(BinanceError::UnknownOrder { .. }) => None,
+                Err(e) => {
+                    warn!(
+                        "Failed to query order {} on {}: {}",
+                        order.order_id, order.symbol, e
+                    );
+                    continue;
+                }
+            };
+
+            let open = status
+                .as_ref()
+                .is_some_and(|status| !is_final_status(&status.status));
+            let expired_after = self.order_ttl.filter(|ttl| {
+                (now - order.placed_at)
+                    .to_std()
+                    .is_ok_and(|age| age >= *ttl)
+            });
+            if open {
+                let Some(ttl) = expired_after else {
+                    // Still working: book what filled so far and keep checking
+                    if let Some(status) = &status {
+                        let booked = self.book_resting_fill(&order, status).await;
+                        if let Some(resting) = self
+                            .resting_orders
+                            .write()
+                            .unwrap()
+                            .iter_mut()
+                            .find(|resting| resting.order_id == order.order_id)
+                        {
+                            resting.booked = booked;
+                        }
+                    }
+                    continue;
+                };
+
+                match self
+                    .client
+                    .cancel_order(&order.symbol, order.order_id)
+                    .await
+                {
+                    Ok(_) => info!(
+                        "Cancelled order {} on {} after {:?} on the book",
+                        order.order_id, order.symbol, ttl
+                    ),
+                    // Filled or cancelled since it was queried
+                    Err(BinanceError::UnknownOrder { .. }) => {}
+                    Err(e) => {
+                        warn!(
+                            "Failed to cancel expired order {} on {}: {}",
+                            order.order_id, order.symbol, e
+                        );
+                        continue;
+                    }
+                }
+
+                // It may have filled further before the cancel landed
+                match self.client.get_order(&order.symbol, order.order_id).await {
+                    Ok(after_cancel) => status = Some(after_cancel),
+                    Err(e) => warn!(
+                        "Failed to re-query order {} on {}, booking its fills as of before the cancel: {}",
+                        order.order_id, order.symbol, e
+                    ),
+                }
+            }
+
+            self.resting_orders
+                .write()
+                .unwrap()
+                .retain(|resting| resting.order_id != order.order_id);
+            // Filled or cancelled, either way its balance moved
+            self.invalidate_account_cache();
+
+            let Some(status) = status else {
+                continue;
+            };
+            self.book_resting_fill(&order, &status).await;
+            if open && self.reprice_expired_orders {
+                if let Err(e) = self.reprice_remainder(&order, &status).await {
+                    warn!(
+                        "Failed to re-place the rest of order {} on {}: {}",
+                        order.order_id, order.symbol, e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Books what `order` filled on the book beyond what was already
+    /// booked, as `status` reports it, and returns all it has filled.
+    async fn book_resting_fill(&self, order: &RestingOrder, status: &QueryOrderResponse) -> Fill {
+        let executed: Decimal = status.executed_qty.parse().unwrap_or_default();
+        let quantity = executed - order.booked.quantity;
+        if quantity <= dec!(0) {
+            return order.booked;
+        }
+
+        let booked_quote = order.booked.quantity * order.booked.avg_price;
+        let (avg_price, total_quote) = match status.cummulative_quote_qty.parse::<Decimal>() {
+            Ok(quote) if quote > booked_quote => ((quote - booked_quote) / quantity, quote),
+            _ => {
+                let price: Decimal = status.price.parse().unwrap_or_default();
+                (price, booked_quote + quantity * price)
+            }
+        };
+        info!(
+            "{} order {} on {} filled {} more at {} while resting",
+            order.side, order.order_id, order.symbol, quantity, avg_price
+        );
+        self.book_fill(
+            &order.symbol,
+            order.side,
+            &Fill {
+                quantity,
+                avg_price,
+            },
+            Some(order.order_id),
+        )
+        .await;
+        self.invalidate_account_cache();
+
+        Fill {
+            quantity: executed,
+            avg_price: total_quote / executed,
+        }
+    }
+
+    /// Re-places the part of the cancelled `order` that `status` shows
+    /// unfilled, as a limit order off the current price.
+    async fn reprice_remainder(
+        &self,
+        order: &RestingOrder,
+        status: &QueryOrderResponse,
+    ) -> Result<()> {
+        let orig_qty: Decimal = status.orig_qty.parse()?;
+        let executed: Decimal = status.executed_qty.parse()?;
+        let quantity = self.round_quantity(orig_qty - executed, &order.symbol);
+        if quantity <= dec!(0) {
+            return Ok(());
+        }
+
+        let current_price = self.execution_price(&order.symbol, order.side).await?;
+        let request = self.entry_order(&order.symbol, order.side, quantity, current_price);
+        info!(
+            "Re-placing {} {} {} at {}",
+            order.side,
+            quantity,
+            order.symbol,
+            describe_price(&request)
+        );
+
+        let quote_asset = self.symbol_info(&order.symbol).await?.quote_asset.as_str();
+        let account = self.account_info().await?;
+        let quote_balance = self.spendable(quote_balance(&account.balances, quote_asset)?);
+        if let Some((fill, order_id)) = self
+            .submit(&request, quantity, current_price, &quote_balance)
+            .await
+        {
+            self.book_fill(&order.symbol, order.side, &fill, order_id)
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Books a live fill that came in outside the signal that placed the
+    /// order, as the buy and sell paths do for their own.
+    async fn book_fill(&self, symbol: &str, side: OrderSide, fill: &Fill, _order_id: Option<u64>) {
+        self.notify_fill(symbol, side, fill.quantity, fill.avg_price, false)
+            .await;
+        let timestamp = self.clock.now().timestamp_millis() as u64;
+        #[cfg(feature = "journal")]
+        self.journal_trade(
+            symbol,
+            side,
+            fill.quantity,
+            fill.avg_price,
+            timestamp,
+            _order_id,
+        );
+
+        match side {
+            OrderSide::Buy => {
+                let entry_price = self.track_buy_fill(symbol, fill, timestamp);
+                self.place_exit_orders(symbol, fill.quantity, entry_price)
+                    .await;
+            }
+            OrderSide::Sell => self.track_sell_fill(symbol, fill),
+        }
+    }
+
+    /// Values the account, or the paper wallet, and feeds it to the drawdown
+    /// limit. On failure the limit keeps the last equity it saw.
+    async fn update_equity(&self) {
+        if self.risk_manager.max_drawdown_pct().is_none() {
+            return;
+        }
+
+        let balances = match &self.paper_wallet {
+            Some(wallet) if self.paper_trading => wallet.balances(),
+            _ => match self.account_info().await {
+                Ok(account) => account.balances,
+                Err(e) => {
+                    warn!("Could not fetch balances to value equity: {}", e);
+                    return;
+                }
+            },
+        };
+        let prices: HashMap<String, Decimal> = match self.client.get_all_ticker_prices().await {
+            Ok(tickers) => tickers
+                .into_iter()
+                .map(|ticker| {
+                    let price = ticker.price_decimal();
+                    (ticker.symbol, price)
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Could not fetch prices to value equity: {}", e);
+                return;
+            }
+        };
+
+        let quote = &self.equity_quote_asset;
+        let (equity, unpriced) = value_balances(&balances, quote, &prices);
+        // Dust in delisted or exotic assets would warn on every cycle
+        for balance in unpriced {
+            debug!(
+                "No {}{} pair, leaving {} {} out of equity",
+                balance.asset,
+                quote,
+                balance.total(),
+                balance.asset
+            );
+        }
+        self.risk_manager.update_equity(equity);
+    }
+
+    /// Prepares one symbol, logging instead of propagating errors so a
+    /// failure on one symbol never aborts the rest of the cycle.
+    async fn prepare_symbol_logged(&self, symbol: &str, dry_run: bool) -> Option<Prepared> {
+        self.prepare_symbol(symbol, dry_run)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Error processing {}: {}", symbol, e);
+                None
+            })
+    }
+
+    /// Fetches `symbol`'s candles and checks everything that doesn't need
+    /// the strategy: trading status, 24h volume, stale data and protective
+    /// exits. `None` when the symbol sits this cycle out. A dry run leaves
+    /// trailing stops' peak prices where they are.
+    async fn prepare_symbol(&self, symbol: &str, dry_run: bool) -> Result<Option<Prepared>> {
+        debug!("Processing symbol: {}", symbol);
+
+        if !self.is_tradable(symbol).await {
+            return Ok(None);
+        }
+
+        // Only needed for the volume minimum and the log line, so a failed
+        // fetch is not worth the cycle
+        let ticker = self.client.get_24h_ticker(symbol).await;
+        if let Ok(ticker) = &ticker {
+            if !self.has_min_volume(symbol, ticker).await {
+                return Ok(None);
+            }
+        }
+
+        // Get market data
+        let strategy = self.strategy_for(symbol);
+        let required_history = strategy.required_history() as u32;
+        let market_data = self
+            .client
+            .get_market_data(symbol, strategy.kline_interval(), required_history.max(50))
+            .await?;
+
+        let change = match ticker {
+            Ok(ticker) => format!(", 24h change = {}%", ticker.price_change_pct_decimal()),
+            Err(e) => {
+                debug!("{}: no 24h ticker: {}", symbol, e);
+                String::new()
+            }
+        };
+        info!(
+            "{}: Current price = {}{}, Klines = {}",
+            symbol,
+            market_data.current_price,
+            change,
+            market_data.klines.len()
+        );
+
+        Ok(self.classify(market_data, dry_run))
+    }
+
+    /// Whether `symbol` is open for spot trading. Halted or delisted
+    /// symbols only earn cryptic order rejections. Without exchange info
+    /// there is nothing to go on, so it's traded as before.
+    async fn is_tradable(&self, symbol: &str) -> bool {
+        let Ok(info) = self.symbol_info(symbol).await else {
+            return true;
+        };
+
+        if !info.is_trading() {
+            warn!("{}: status is {}, skipping", symbol, info.status);
+            return false;
+        }
+        if !info.is_spot_trading_allowed {
+            warn!("{}: spot trading is not allowed, skipping", symbol);
+            return false;
+        }
+        true
+    }
+
+    /// Whether `symbol` traded enough over the last 24 hours, per `ticker`,
+    /// to be worth entering. Open positions are always managed.
+    async fn has_min_volume(&self, symbol: &str, ticker: &Ticker24h) -> bool {
+        let Some(min_volume) = self.min_quote_volume else {
+            return true;
+        };
+        let Ok(info) = self.symbol_info(symbol).await else {
+            return true;
+        };
+        if !self.risk_manager.is_stable_quote(&info.quote_asset)
+            || self.risk_manager.position(symbol).is_some()
+        {
+            return true;
+        }
+
+        let volume = ticker.quote_volume_decimal();
+        if volume < min_volume {
+            warn!(
+                "{}: 24h volume of {} {} is below the {} minimum, skipping",
+                symbol,
+                volume.round_dp(0),
+                info.quote_asset,
+                min_volume
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Fetches `symbol`'s 24h ticker for `has_min_volume` when there is a
+    /// minimum to check.
+    async fn is_liquid(&self, symbol: &str) -> bool {
+        if self.min_quote_volume.is_none() {
+            return true;
+        }
+
+        match self.client.get_24h_ticker(symbol).await {
+            Ok(ticker) => self.has_min_volume(symbol, &ticker).await,
+            Err(e) => {
+                debug!("{}: no 24h ticker: {}", symbol, e);
+                true
+            }
+        }
+    }
+
+    /// Whether the clock is inside the configured trading hours.
+    fn in_session(&self) -> bool {
+        let now = self.clock.now();
+        self.trading_hours.is_empty() || self.trading_hours.iter().any(|w| w.contains(now))
+    }
+
+    /// Sorts fresh `market_data` into a protective exit or a decision for
+    /// the strategy; `None` when it's too stale to act on.
+    fn classify(
+        &self,
+        market_data: crate::exchange::MarketData,
+        dry_run: bool,
+    ) -> Option<Prepared> {
+        let symbol = market_data.symbol.as_str();
+        if let Some(reason) = self.stale_data(&market_data, self.clock.now_ms()) {
+            warn!("{}: {}, holding", symbol, reason);
+            return None;
+        }
+
+        // A triggered trailing stop exits the whole position regardless of signal
+        let trailing_stop_hit = if dry_run {
+            self.trailing_stop_hit(&market_data)
+        } else {
+            let prices = HashMap::from([(symbol.to_string(), market_data.valuation_price())]);
+            !self.risk_manager.update_trailing_stops(&prices).is_empty()
+        };
+        if trailing_stop_hit {
+            info!("{}: trailing stop hit, selling position", symbol);
+            return Some(Prepared::Exit(market_data, Decimal::ONE));
+        }
+
+        // So does reaching the stop-loss or take-profit level
+        if let Some(exit) = self.check_exits(&market_data) {
+            let strength = exit.strength();
+            return Some(Prepared::Exit(market_data, strength));
+        }
+
+        Some(Prepared::Analyze(market_data))
+    }
+
+    /// Whether the price has crossed the trailing stop of the position on
+    /// `market_data`'s symbol. Unlike `RiskManager::update_trailing_stops`
+    /// this doesn't move the peak, which can't change the answer: a new peak
+    /// is always above the stop it raises.
+    fn trailing_stop_hit(&self, market_data: &crate::exchange::MarketData) -> bool {
+        let symbol = &market_data.symbol;
+        let (Some(stop), Some(position)) = (
+            self.risk_manager.trailing_stop_price(symbol),
+            self.risk_manager.position(symbol),
+        ) else {
+            return false;
+        };
+
+        let price = market_data.valuation_price();
+        match position.side {
+            OrderSide::Buy => price <= stop,
+            OrderSide::Sell => price >= stop,
+        }
+    }
+
+    async fn exit_position_logged(
+        &self,
+        market_data: &crate::exchange::MarketData,
+        strength: Decimal,
+        balances: &[crate::exchange::Balance],
+    ) {
+        let symbol = market_data.symbol.as_str();
+        if let Err(e) = self
+            .execute_sell(symbol, market_data, balances, strength, true)
+            .await
+        {
+            error!("Error processing {}: {}", symbol, e);
+        }
+    }
+
+    async fn act_on_signal_logged(
+        &self,
+        market_data: &crate::exchange::MarketData,
+        signal: Signal,
+        balances: &[crate::exchange::Balance],
+    ) {
+        if let Err(e) = self.act_on_signal(market_data, signal, balances).await {
+            error!("Error processing {}: {}", market_data.symbol, e);
+        }
+    }
+
+    /// Trades on the strategy's `signal` for the symbol of `market_data`.
+    async fn act_on_signal(
+        &self,
+        market_data: &crate::exchange::MarketData,
+        signal: Signal,
+        balances: &[crate::exchange::Balance],
+    ) -> Result<()> {
+        let symbol = market_data.symbol.as_str();
+        let signal = match &self.signal_smoother {
+            Some(smoother) => smoother.update(symbol, &signal),
+            None => signal,
+        };
+        let signal = self.unless_cooling_down(symbol, signal);
+        let signal = self.unless_risk_limited(symbol, signal);
+
+        self.notify(Notification::Signal {
+            symbol: symbol.to_string(),
+            signal: signal.clone(),
+            price: market_data.current_price,
+            timestamp: market_data.timestamp,
+        })
+        .await;
+
+        match &signal {
+            Signal::Buy { strength } => {
+                info!("{}: BUY signal with strength {:.2}", symbol, strength);
+                self.execute_buy(symbol, market_data, balances, *strength)
+                    .await?;
+            }
+            Signal::Sell { strength } => {
+                info!("{}: SELL signal with strength {:.2}", symbol, strength);
+                self.execute_sell(symbol, market_data, balances, *strength, false)
+                    .await?;
+            }
+            Signal::Hold => {
+                debug!("{}: HOLD - no action", symbol);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `signal`, or `Hold` while `symbol` is in its cooldown.
+    fn unless_cooling_down(&self, symbol: &str, signal: Signal) -> Signal {
+        if matches!(signal, Signal::Hold) || !self.in_cooldown(symbol) {
+            return signal;
+        }
+
+        debug!(
+            "{}: {:?} ignored, traded within the last {:?}",
+            symbol, signal, self.cooldown
+        );
+        Signal::Hold
+    }
+
+    /// `signal`, or `Hold` for a buy the risk limits don't allow on
+    /// `symbol`.
+    fn unless_risk_limited(&self, symbol: &str, signal: Signal) -> Signal {
+        if !matches!(signal, Signal::Buy { .. }) || self.risk_manager.can_buy(symbol) {
+            return signal;
+        }
+
+        debug!("{}: {:?} ignored, risk limits reached", symbol, signal);
+        Signal::Hold
+    }
+
+    /// Sizes and risk-checks the order acting on `signal` would take for the
+    /// symbol of `market_data`, without placing anything. `whole_position`
+    /// marks a protective exit.
+    async fn plan(
+        &self,
+        market_data: &crate::exchange::MarketData,
+        signal: &Signal,
+        balances: &[crate::exchange::Balance],
+        whole_position: bool,
+    ) -> Result<Decision> {
+        let symbol = market_data.symbol.as_str();
+        match *signal {
+            Signal::Buy { strength } => {
+                self.plan_buy(symbol, market_data, balances, strength).await
+            }
+            Signal::Sell { strength } => {
+                self.plan_sell(symbol, market_data, balances, strength, whole_position)
+                    .await
+            }
+            Signal::Hold => Ok(Decision::Hold),
+        }
+    }
+
+    /// The order to send for `decision`, if any. Risk rejections are logged
+    /// and notified.
+    async fn order_to_place(&self, decision: Decision) -> Option<(OrderRequest, Decimal)> {
+        match decision {
+            Decision::Place { order, .. } if self.already_placed(&order) => {
+                info!(
+                    "{} {} order for this candle already placed, not placing it again",
+                    order.side, order.symbol
+                );
+                None
+            }
+            Decision::Place { order, quantity } => Some((order, quantity)),
+            Decision::Rejected { order, error } => {
+                warn!("Order rejected by risk manager: {}", error);
+                self.notify_risk_rejection(&order, error).await;
+                None
+            }
+            Decision::Hold | Decision::Skipped { .. } => None,
+        }
+    }
+
+    /// Sizes a buy on `symbol` from the spendable quote balance and checks
+    /// it with the risk manager.
+    async fn plan_buy(
+        &self,
+        symbol: &str,
+        market_data: &crate::exchange::MarketData,
+        balances: &[crate::exchange::Balance],
+        signal_strength: Decimal,
+    ) -> Result<Decision> {
+        let quote_asset = self.symbol_info(symbol).await?.quote_asset.as_str();
+        let quote_balance = &self.spendable(quote_balance(balances, quote_asset)?);
+
+        let scaling = self.risk_manager.position_scaling();
+        if let (Some(scaling), Some(position)) = (scaling, self.risk_manager.position(symbol)) {
+            if position.leg_count() >= scaling.max_position_legs {
+                info!(
+                    "{}: position already has {} legs, not adding another",
+                    symbol,
+                    position.leg_count()
+                );
+                return Ok(Decision::Skipped {
+                    reason: format!("position already has {} legs", position.leg_count()),
+                });
+            }
+        }
+
+        // Calculate position size based on signal strength and risk settings,
+        // at the price the order may actually fill at
+        let entry_price = self
+            .limit_price(symbol, OrderSide::Buy, market_data.current_price)
+            .unwrap_or(market_data.current_price);
+        let quantity = match self.strategy_for(symbol).fixed_quote_amount() {
+            Some(quote_amount) => quote_amount / entry_price,
+            None => {
+                let atr = self.risk_manager.atr_sizing().and_then(|sizing| {
+                    calculate_atr(&market_data.klines, sizing.period).map(|atr| (sizing, atr))
+                });
+
+                // Every branch is capped at the symbol's own max_position_pct
+                match (scaling, atr) {
+                    (Some(scaling), _) => self.risk_manager.calculate_position_size(
+                        symbol,
+                        quote_balance.free_decimal(),
+                        scaling.leg_size_pct,
+                        entry_price,
+                    ),
+                    (None, Some((sizing, atr))) => self.risk_manager.calculate_position_size_atr(
+                        symbol,
+                        quote_balance.free_decimal(),
+                        sizing.risk_pct,
+                        entry_price,
+                        atr,
+                        sizing.multiplier,
+                    ),
+                    (None, None) => self.risk_manager.calculate_position_size(
+                        symbol,
+                        quote_balance.free_decimal(),
+                        dec!(1) + signal_strength,
+                        entry_price,
+                    ),
+                }
+            }
+        };
+
+        let quantity = match self.risk_manager.max_order_notional_for(quote_asset) {
+            Some(max_notional) => quantity.min(max_notional / entry_price),
+            None => quantity,
+        };
+
+        if quantity <= dec!(0) {
+            warn!("Calculated quantity is zero or negative, skipping order");
+            return Ok(Decision::Skipped {
+                reason: "nothing to spend".to_string(),
+            });
+        }
+
+        // Left silent, a budget that rounds to nothing never trades and
+        // never says why
+        let min_qty = self.symbol_info(symbol).await?.min_qty();
+        let rounded = self.round_quantity(quantity, symbol);
+        if rounded <= dec!(0) || min_qty.is_some_and(|min_qty| rounded < min_qty) {
+            let min_qty = match min_qty {
+                Some(min_qty) => min_qty.normalize().to_string(),
+                None => "unknown".to_string(),
+            };
+            let reason = format!(
+                "balance only buys {}, below the minimum tradable size (minQty {})",
+                quantity.round_dp(8).normalize(),
+                min_qty
+            );
+            warn!("{}: {}, skipping", symbol, reason);
+            return Ok(Decision::Skipped { reason });
+        }
+
+        // Market buys spend the budget exactly; rounding a base quantity
+        // would drift from it and can trip the exchange's notional filter
+        let order = match self.limit_slippage_pct {
+            Some(_) => self.entry_order(
+                symbol,
+                OrderSide::Buy,
+                self.round_quantity(quantity, symbol),
+                market_data.current_price,
+            ),
+            None => OrderRequest::market_quote(
+                symbol,
+                OrderSide::Buy,
+                (quantity * entry_price)
+                    .round_dp(QUOTE_PRECISION)
+                    .normalize(),
+            ),
+        }
+        .with_client_order_id(client_order_id(
+            symbol,
+            OrderSide::Buy,
+            signal_candle(market_data),
+        ));
+
+        // Round quantity to appropriate precision (simplified)
+        let quantity = self.round_quantity(quantity, symbol);
+
+        if let Err(e) = self
+            .risk_manager
+            .validate_order(&order, quote_balance, market_data.current_price)
+        {
+            return Ok(Decision::Rejected { order, error: e });
+        }
+
+        Ok(Decision::Place { order, quantity })
+    }
+
+    async fn execute_buy(
+        &self,
+        symbol: &str,
+        market_data: &crate::exchange::MarketData,
+        balances: &[crate::exchange::Balance],
+        signal_strength: Decimal,
+    ) -> Result<()> {
+        if let Some(warning) = self.fee_asset_warning(balances) {
+            warn!("{}", warning);
+        }
+
+        let decision = self
+            .plan_buy(symbol, market_data, balances, signal_strength)
+            .await?;
+        let Some((order, quantity)) = self.order_to_place(decision).await else {
+            return Ok(());
+        };
+        let quote_asset = self.symbol_info(symbol).await?.quote_asset.as_str();
+        let quote_balance = &self.spendable(quote_balance(balances, quote_asset)?);
+
+        if !self
+            .within_slippage(&order, market_data.current_price)
+            .await
+        {
+            return Ok(());
+        }
+
+        // Execute or simulate
+        if self.paper_trading {
+            let validation = match self.validate_paper_order(&order).await {
+                Ok(note) => note,
+                Err(e) => {
+                    warn!(
+                        "[PAPER] BUY {} {} rejected by exchange: {}",
+                        quantity, symbol, e
+                    );
+                    self.notify_rejection(&order, e.to_string()).await;
+                    return Ok(());
+                }
+            };
+
+            let mut price = order.price.unwrap_or(market_data.current_price);
+            if let Some(wallet) = &self.paper_wallet {
+                let base_asset = self.symbol_info(symbol).await?.base_asset.as_str();
+                match wallet.fill_order(
+                    &order,
+                    base_asset,
+                    quote_asset,
+                    quantity,
+                    market_data.current_price,
+                ) {
+                    Ok(fill) => price = fill.price,
+                    Err(e) => {
+                        warn!("[PAPER] BUY {} {} skipped: {}", quantity, symbol, e);
+                        return Ok(());
+                    }
+                }
+            }
+
+            info!(
+                "[PAPER] Would BUY {} {} at {} (value: {} {}){}",
+                quantity,
+                symbol,
+                price,
+                quantity * price,
+                quote_asset,
+                validation
+            );
+            self.record_order_placed(&order);
+            self.notify_fill(symbol, OrderSide::Buy, quantity, price, true)
+                .await;
+            #[cfg(feature = "journal")]
+            self.journal_trade(
+                symbol,
+                OrderSide::Buy,
+                quantity,
+                price,
+                market_data.timestamp,
+                None,
+            );
+
+            let fill = Fill {
+                quantity,
+                avg_price: price,
+            };
+            let entry_price = self.track_buy_fill(symbol, &fill, market_data.timestamp);
+            self.place_exit_orders(symbol, quantity, entry_price).await;
+        } else {
+            info!(
+                "Placing BUY order: {} {} at {}",
+                quantity,
+                symbol,
+                describe_price(&order)
+            );
+            // Nothing filled (yet), so there is no position to protect
+            let Some((fill, _order_id)) = self
+                .submit(&order, quantity, market_data.current_price, quote_balance)
+                .await
+            else {
+                return Ok(());
+            };
+            self.notify_fill(symbol, OrderSide::Buy, fill.quantity, fill.avg_price, false)
+                .await;
+
+            #[cfg(feature = "journal")]
+            self.journal_trade(
+                symbol,
+                OrderSide::Buy,
+                fill.quantity,
+                fill.avg_price,
+                market_data.timestamp,
+                _order_id,
+            );
+
+            let entry_price = self.track_buy_fill(symbol, &fill, market_data.timestamp);
+            self.place_exit_orders(symbol, fill.quantity, entry_price)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Opens a position on a filled buy, or adds it to the open one as a
+    /// leg, and returns the entry price to set its exits from. Exits for an
+    /// added leg are set off the whole position's average entry, as the
+    /// stop and take-profit checks are.
+    fn track_buy_fill(&self, symbol: &str, fill: &Fill, timestamp: u64) -> Decimal {
+        self.risk_manager.open_position(Position::new(
+            symbol,
+            fill.avg_price,
+            fill.quantity,
+            OrderSide::Buy,
+            timestamp,
+        ));
+
+        self.risk_manager
+            .position(symbol)
+            .map_or(fill.avg_price, |position| position.entry_price)
+    }
+
+    /// Takes a filled sell off the tracked position and books its PnL.
+    /// Whatever wasn't sold stays tracked, under the stops and caps.
+    fn track_sell_fill(&self, symbol: &str, fill: &Fill) {
+        let Some(sold) = self.risk_manager.reduce_position(symbol, fill.quantity) else {
+            return;
+        };
+
+        let pnl_pct = self
+            .risk_manager
+            .record_trade_result(sold.pnl_pct(fill.avg_price));
+        let action = match self.risk_manager.position(symbol) {
+            Some(_) => "Reduced",
+            None => "Closed",
+        };
+        info!(
+            "{} {} position, PnL {:.2}% after fees on the {} sold",
+            action, symbol, pnl_pct, sold.quantity
+        );
+    }
+
+    /// The newest leg of `symbol`'s position, which a strategy sell peels
+    /// off under position scaling. `None` sells by signal strength instead;
+    /// protective exits, marked by `whole_position`, take everything.
+    fn leg_to_sell(&self, symbol: &str, whole_position: bool) -> Option<Decimal> {
+        match self.risk_manager.position_scaling() {
+            Some(_) if !whole_position => self
+                .risk_manager
+                .position(symbol)
+                .and_then(|position| position.newest_leg()),
+            _ => None,
+        }
+    }
+
+    /// Sizes a sell on `symbol` from the free base balance and checks it
+    /// with the risk manager.
+    async fn plan_sell(
+        &self,
+        symbol: &str,
+        market_data: &crate::exchange::MarketData,
+        balances: &[crate::exchange::Balance],
+        signal_strength: Decimal,
+        whole_position: bool,
+    ) -> Result<Decision> {
+        let leg = self.leg_to_sell(symbol, whole_position);
+        let base_asset = self.symbol_info(symbol).await?.base_asset.as_str();
+
+        let base_balance = balances.iter().find(|b| b.asset == base_asset);
+
+        let Some(available) = base_balance.map(|b| b.free_decimal()) else {
+            debug!("No {} balance found", base_asset);
+            return Ok(Decision::Skipped {
+                reason: format!("no {} balance", base_asset),
+            });
+        };
+        if available <= dec!(0) {
+            debug!("No {} available to sell", base_asset);
+            return Ok(Decision::Skipped {
+                reason: format!("no {} available to sell", base_asset),
+            });
+        }
+
+        let quantity = match leg {
+            Some(leg) => leg.min(available),
+            None => {
+                // Sell portion based on signal strength
+                available * signal_strength
+            }
+        };
+
+        if quantity <= dec!(0) {
+            return Ok(Decision::Skipped {
+                reason: "nothing to sell".to_string(),
+            });
+        }
+
+        let quantity = self.round_quantity(quantity, symbol);
+        let quantity = self.avoid_dust(symbol, quantity, available, market_data.current_price);
+
+        let mut order = self
+            .entry_order(symbol, OrderSide::Sell, quantity, market_data.current_price)
+            .with_client_order_id(client_order_id(
+                symbol,
+                OrderSide::Sell,
+                signal_candle(market_data),
+            ));
+        if self.reduce_only {
+            order = order.with_reduce_only();
+        }
+        let Some(order) = self.clamp_reduce_only(order, available) else {
+            return Ok(Decision::Skipped {
+                reason: "nothing held to reduce".to_string(),
+            });
+        };
+        let quantity = order.quantity;
+
+        // Quote balance for validation (not really needed for sells but for consistency)
+        let quote_asset = self.symbol_info(symbol).await?.quote_asset.as_str();
+        let quote_balance = balance_or_zero(balances, quote_asset);
+
+        if let Err(e) = self
+            .risk_manager
+            .validate_order(&order, &quote_balance, market_data.current_price)
+        {
+            return Ok(Decision::Rejected { order, error: e });
+        }
+
+        Ok(Decision::Place { order, quantity })
+    }
+
+    /// Balances to size a live sell on `symbol` from. Base asset locked in
+    /// open orders can't be sold: with `cancel_orders_to_sell` the symbol's
+    /// open orders are cancelled and the balances fetched again, otherwise
+    /// the sell is left to the free amount.
+    async fn balances_for_sell(&self, symbol: &str, balances: &[Balance]) -> Result<Vec<Balance>> {
+        let base_asset = self.symbol_info(symbol).await?.base_asset.as_str();
+        let locked = balances
+            .iter()
+            .find(|b| b.asset == base_asset)
+            .map_or(dec!(0), Balance::locked_decimal);
+        if self.paper_trading || locked <= dec!(0) {
+            return Ok(balances.to_vec());
+        }
+
+        if !self.cancel_orders_to_sell {
+            warn!(
+                "{} {} is locked in open orders on {}, selling from the free balance only",
+                locked, base_asset, symbol
+            );
+            return Ok(balances.to_vec());
+        }
+
+        info!(
+            "Cancelling open orders on {} to free {} {} for the sell",
+            symbol, locked, base_asset
+        );
+        for order in self.client.cancel_all_open_orders(symbol).await? {
+            info!("Cancelled order {} on {}", order.order_id, order.symbol);
+        }
+        self.resting_orders
+            .write()
+            .unwrap()
+            .retain(|resting| resting.symbol != symbol);
+        self.invalidate_account_cache();
+        Ok(self.account_info().await?.balances)
+    }
+
+    async fn execute_sell(
+        &self,
+        symbol: &str,
+        market_data: &crate::exchange::MarketData,
+        balances: &[crate::exchange::Balance],
+        signal_strength: Decimal,
+        whole_position: bool,
+    ) -> Result<()> {
+        let balances = &self.balances_for_sell(symbol, balances).await?;
+        let decision = self
+            .plan_sell(
+                symbol,
+                market_data,
+                balances,
+                signal_strength,
+                whole_position,
+            )
+            .await?;
+        let Some((order, quantity)) = self.order_to_place(decision).await else {
+            return Ok(());
+        };
+        let info = self.symbol_info(symbol).await?;
+        let base_asset = info.base_asset.as_str();
+        let quote_balance = balance_or_zero(balances, &info.quote_asset);
+
+        if !self
+            .within_slippage(&order, market_data.current_price)
+            .await
+        {
+            return Ok(());
+        }
+
+        if self.paper_trading {
+            let validation = match self.validate_paper_order(&order).await {
+                Ok(note) => note,
+                Err(e) => {
+                    warn!(
+                        "[PAPER] SELL {} {} rejected by exchange: {}",
+                        quantity, symbol, e
+                    );
+                    self.notify_rejection(&order, e.to_string()).await;
+                    return Ok(());
+                }
+            };
+
+            let mut price = order.price.unwrap_or(market_data.current_price);
+            let mut paper_pnl = None;
+            let quote_asset = self.symbol_info(symbol).await?.quote_asset.as_str();
+            if let Some(wallet) = &self.paper_wallet {
+                match wallet.fill_order(
+                    &order,
+                    base_asset,
+                    quote_asset,
+                    quantity,
+                    market_data.current_price,
+                ) {
+                    Ok(fill) => {
+                        price = fill.price;
+                        paper_pnl = fill
+                            .realized_pnl
+                            .map(|pnl| (pnl, wallet.realized_pnl(quote_asset)));
+                    }
+                    Err(e) => {
+                        warn!("[PAPER] SELL {} {} skipped: {}", quantity, symbol, e);
+                        return Ok(());
+                    }
+                }
+            }
+
+            info!(
+                "[PAPER] Would SELL {} {} at {} (value: {} {}){}",
+                quantity,
+                symbol,
+                price,
+                quantity * price,
+                quote_asset,
+                validation
+            );
+            if let Some((pnl, total)) = paper_pnl {
+                info!(
+                    "[PAPER] {} PnL {} {} (total {} {})",
+                    symbol,
+                    pnl.round_dp(2),
+                    quote_asset,
+                    total.round_dp(2),
+                    quote_asset
+                );
+            }
+            self.record_order_placed(&order);
+            self.notify_fill(symbol, OrderSide::Sell, quantity, price, true)
+                .await;
+            #[cfg(feature = "journal")]
+            self.journal_trade(
+                symbol,
+                OrderSide::Sell,
+                quantity,
+                price,
+                market_data.timestamp,
+                None,
+            );
+
+            self.track_sell_fill(
+                symbol,
+                &Fill {
+                    quantity,
+                    avg_price: price,
+                },
+            );
+        } else {
+            info!(
+                "Placing SELL order: {} {} at {}",
+                quantity,
+                symbol,
+                describe_price(&order)
+            );
+            let Some((fill, _order_id)) = self
+                .submit(&order, quantity, market_data.current_price, &quote_balance)
+                .await
+            else {
+                return Ok(());
+            };
+            self.notify_fill(
+                symbol,
+                OrderSide::Sell,
+                fill.quantity,
+                fill.avg_price,
+                false,
+            )
+            .await;
+
+            #[cfg(feature = "journal")]
+            self.journal_trade(
+                symbol,
+                OrderSide::Sell,
+                fill.quantity,
+                fill.avg_price,
+                market_data.timestamp,
+                _order_id,
+            );
+
+            self.track_sell_fill(symbol, &fill);
+        }
+
+        Ok(())
+    }
+
+    /// Why `market_data` is too old to act on at `now_ms`, if it is.
+    fn stale_data(&self, market_data: &crate::exchange::MarketData, now_ms: u64) -> Option<String> {
+        let max_age = self.max_data_age?.as_millis() as u64;
+
+        let age = now_ms.saturating_sub(market_data.timestamp);
+        if age > max_age {
+            return Some(format!("market data is {}ms old", age));
+        }
+
+        // The newest kline is the candle still forming, so its close time
+        // only falls behind when the feed has stopped updating
+        let newest = market_data.klines.last()?;
+        let kline_age = now_ms.saturating_sub(newest.close_time);
+        if kline_age > max_age {
+            return Some(format!("newest kline closed {}ms ago", kline_age));
+        }
+
+        None
+    }
+
+    /// Sell signal for an open position whose price has reached its stop-loss
+    /// or take-profit level, bypassing the strategy. Covers positions whose
+    /// OCO exit is missing or was never placed.
+    fn check_exits(&self, market_data: &crate::exchange::MarketData) -> Option<Signal> {
+        let position = self.risk_manager.position(&market_data.symbol)?;
+        let exits = self.risk_manager.exit_prices(position.entry_price)?;
+        let price = market_data.valuation_price();
+
+        if price <= exits.stop_price {
+            info!(
+                "{}: stop-loss {} hit at {}, selling position",
+                market_data.symbol, exits.stop_price, price
+            );
+        } else if price >= exits.take_profit_price {
+            info!(
+                "{}: take-profit {} hit at {}, selling position",
+                market_data.symbol, exits.take_profit_price, price
+            );
+        } else {
+            return None;
+        }
+
+        Some(Signal::Sell {
+            strength: Decimal::ONE,
+        })
+    }
+
+    /// Price an order on `side` would trade at now: the best opposite quote
+    /// with order book pricing, otherwise the last trade price.
+    async fn execution_price(&self, symbol: &str, side: OrderSide) -> Result<Decimal> {
+        if !self.client.order_book_pricing() {
+            return Ok(self.client.get_ticker_price(symbol).await?.price_decimal());
+        }
+
+        let book = self
+            .client
+            .get_order_book(symbol, ORDER_BOOK_PRICING_DEPTH)
+            .await?;
+        let price = match side {
+            OrderSide::Buy => book.best_ask(),
+            OrderSide::Sell => book.best_bid(),
+        };
+        price.ok_or_else(|| anyhow::anyhow!("{} order book is empty", symbol))
+    }
+
+    /// Re-reads the price before a market order and refuses the order when
+    /// it has moved too far from `signal_price`, the price the signal was
+    /// generated at. With order book pricing, the price is the best quote
+    /// the order would fill against. Limit orders carry their own price bound
+    /// and pass.
+    async fn within_slippage(&self, order: &OrderRequest, signal_price: Decimal) -> bool {
+        let Some(tolerance_pct) = self.market_slippage_pct else {
+            return true;
+        };
+        if !matches!(order.order_type, OrderType::Market) {
+            return true;
+        }
+
+        let current_price = match self.execution_price(&order.symbol, order.side).await {
+            Ok(price) => price,
+            Err(e) => {
+                warn!(
+                    "Could not re-check {} price before {} order, skipping: {}",
+                    order.symbol, order.side, e
+                );
+                return false;
+            }
+        };
+
+        match check_slippage(signal_price, current_price, tolerance_pct) {
+            Ok(slippage_pct) => {
+                debug!(
+                    "{} slippage {}% within tolerance",
+                    order.symbol, slippage_pct
+                );
+                true
+            }
+            Err(e) => {
+                warn!("{} {} order aborted: {}", order.side, order.symbol, e);
+                false
+            }
+        }
+    }
+
+    /// Places `order` live once the approver, if any, agrees, through the
+    /// TWAP executor when it is large enough, and waits for it to fill.
+    /// Returns what filled and the order id (none for TWAP, which places
+    /// several), or `None` when nothing did.
+    async fn submit(
+        &self,
+        order: &OrderRequest,
+        requested_qty: Decimal,
+        current_price: Decimal,
+        quote_balance: &Balance,
+    ) -> Option<(Fill, Option<u64>)> {
+        if let Some(approver) = &self.approver {
+            if !approver.approve(order).await {
+                info!("{} {} order declined", order.side, order.symbol);
+                return None;
+            }
+        }
+
+        if let Some(twap) = self.twap_for(order, current_price) {
+            let floor_quantity = |quantity| self.floor_quantity(quantity, &order.symbol);
+            let twap_fill = twap
+                .execute(
+                    self.client.as_ref(),
+                    &self.risk_manager,
+                    order,
+                    &floor_quantity,
+                    quote_balance,
+                    current_price,
+                )
+                .await;
+            if twap_fill.children_filled > 0 {
+                self.record_order_placed(order);
+            }
+            if twap_fill.quantity <= dec!(0) {
+                return None;
+            }
+
+            let avg_price = if twap_fill.quote_qty > dec!(0) {
+                twap_fill.quote_qty / twap_fill.quantity
+            } else {
+                current_price
+            };
+            return Some((
+                Fill {
+                    quantity: twap_fill.quantity,
+                    avg_price,
+                },
+                None,
+            ));
+        }
+
+        match self.client.place_order(order).await {
+            Ok(response) => {
+                info!(
+                    "Order placed successfully: ID={}, Status={}",
+                    response.order_id, response.status
+                );
+                self.record_order_placed(order);
+
+                let fill = self
+                    .await_fill(&response, requested_qty, current_price)
+                    .await;
+                // Whatever fills of a limit order later is booked as it
+                // comes, by check_resting_orders
+                if matches!(order.order_type, OrderType::Limit) && fill.quantity < order.quantity {
+                    self.resting_orders.write().unwrap().push(RestingOrder {
+                        symbol: order.symbol.clone(),
+                        order_id: response.order_id,
+                        side: order.side,
+                        placed_at: self.clock.now(),
+                        booked: Fill {
+                            quantity: fill.quantity.max(dec!(0)),
+                            avg_price: fill.avg_price,
+                        },
+                    });
+                }
+                if fill.quantity <= dec!(0) {
+                    // A limit order resting on the book
+                    info!(
+                        "{} order {} for {} not filled yet",
+                        order.side, response.order_id, order.symbol
+                    );
+                    return None;
+                }
+
+                Some((fill, Some(response.order_id)))
+            }
+            Err(e) if e.is_retryable() => {
+                warn!(
+                    "Order for {} failed, will retry next cycle: {}",
+                    order.symbol, e
+                );
+                None
+            }
+            Err(e) => {
+                error!("Failed to place order: {}", e);
+                self.notify_rejection(order, e.to_string()).await;
+                None
+            }
+        }
+    }
+
+    /// The TWAP executor, if configured and `order` is a market order at or
+    /// above its notional threshold.
+    fn twap_for(&self, order: &OrderRequest, current_price: Decimal) -> Option<&TwapExecutor> {
+        let (twap, min_notional) = self.twap.as_ref()?;
+        if !matches!(order.order_type, OrderType::Market) {
+            return None;
+        }
+
+        let notional = order
+            .quote_order_qty
+            .unwrap_or(order.quantity * current_price);
+        (notional >= *min_notional).then_some(twap)
+    }
+
+    /// Re-queries a freshly placed order until it reaches a final status or
+    /// polling gives up, and reports what actually executed. Falls back to the
+    /// requested quantity and `fallback_price` when the exchange omits them.
+    async fn await_fill(
+        &self,
+        response: &OrderResponse,
+        requested_qty: Decimal,
+        fallback_price: Decimal,
+    ) -> Fill {
+        let mut status = response.status.clone();
+        let mut orig_qty = response.orig_qty.clone();
+        let mut executed_qty = response.executed_qty.clone();
+        let mut quote_qty = response.cummulative_quote_qty.clone();
+
+        let mut polls = 0;
+        while !is_final_status(&status) && polls < self.fill_poll_attempts {
+            tokio::time::sleep(self.fill_poll_interval).await;
+            polls += 1;
+
+            match self
+                .client
+                .get_order(&response.symbol, response.order_id)
+                .await
+            {
+                Ok(order) => {
+                    debug!(
+                        "Order {} for {}: {} ({} filled)",
+                        order.order_id, order.symbol, order.status, order.executed_qty
+                    );
+                    status = order.status;
+                    orig_qty = order.orig_qty;
+                    executed_qty = order.executed_qty;
+                    quote_qty = order.cummulative_quote_qty;
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to query order {} for {}: {}",
+                        response.order_id, response.symbol, e
+                    );
+                    break;
+                }
+            }
+        }
+
+        let quantity: Decimal = executed_qty.parse().unwrap_or(requested_qty);
+        let avg_price = match quote_qty.parse::<Decimal>() {
+            Ok(quote) if quantity > dec!(0) && quote > dec!(0) => quote / quantity,
+            _ => fallback_price,
+        };
+
+        match status.as_str() {
+            "FILLED" | "NEW" => {}
+            "PARTIALLY_FILLED" => warn!(
+                "Order {} for {} partially filled: {} of {} at {}, remainder left open",
+                response.order_id, response.symbol, quantity, orig_qty, avg_price
+            ),
+            other if quantity > dec!(0) => warn!(
+                "Order {} for {} {} after filling {} of {} at {}",
+                response.order_id, response.symbol, other, quantity, orig_qty, avg_price
+            ),
+            other => warn!(
+                "Order {} for {} {} without filling",
+                response.order_id, response.symbol, other
+            ),
+        }
+
+        Fill {
+            quantity,
+            avg_price,
+        }
+    }
+
+    /// Places an OCO sell bracketing a freshly opened long position with the
+    /// configured stop-loss and take-profit levels.
+    async fn place_exit_orders(&self, symbol: &str, quantity: Decimal, entry_price: Decimal) {
+        let exits = match self.risk_manager.exit_prices(entry_price) {
+            Some(exits) => exits,
+            None => return,
+        };
+
+        // Keep the exit prices at the precision the exchange quotes the symbol in
+        let precision = entry_price.scale();
+        let order = OcoOrderRequest::exit_long(
+            symbol,
+            quantity,
+            self.round_price(exits.take_profit_price, symbol)
+                .round_dp(precision),
+            self.round_price(exits.stop_price, symbol)
+                .round_dp(precision),
+            self.round_price(exits.stop_limit_price, symbol)
+                .round_dp(precision),
+        );
+
+        if self.paper_trading {
+            info!(
+                "[PAPER] Would place OCO SELL {} {}: take-profit {}, stop {} (limit {})",
+                order.quantity, symbol, order.price, order.stop_price, order.stop_limit_price
+            );
+            return;
+        }
+
+        info!(
+            "Placing OCO SELL {} {}: take-profit {}, stop {} (limit {})",
+            order.quantity, symbol, order.price, order.stop_price, order.stop_limit_price
+        );
+        match self.client.place_oco_order(&order).await {
+            Ok(response) => {
+                self.invalidate_account_cache();
+                info!(
+                    "OCO order placed successfully: ListID={}, Status={}",
+                    response.order_list_id, response.list_order_status
+                );
+            }
+            Err(e) => {
+                error!("Failed to place OCO order: {}", e);
+            }
+        }
+    }
+
+    /// Checks a simulated order against the exchange when paper order
+    /// validation is on. Returns a note for the `[PAPER]` log line, or the
+    /// exchange's rejection.
+    async fn validate_paper_order(
+        &self,
+        order: &OrderRequest,
+    ) -> Result<&'static str, BinanceError> {
+        if !self.validate_paper_orders {
+            return Ok("");
+        }
+
+        match self.client.place_test_order(order).await {
+            Ok(()) => Ok(" [accepted by exchange]"),
+            Err(e) if e.is_retryable() => {
+                warn!("Could not validate paper order for {}: {}", order.symbol, e);
+                Ok(" [not validated]")
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Builds the order for a signal in the configured order type.
+    fn entry_order(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        current_price: Decimal,
+    ) -> OrderRequest {
+        match self.limit_price(symbol, side, current_price) {
+            Some(price) => OrderRequest::limit(symbol, side, quantity, price),
+            None => OrderRequest::market(symbol, side, quantity),
+        }
+    }
+
+    /// Price for a limit order on `side` of `symbol`, or `None` when placing
+    /// market orders.
+    fn limit_price(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        current_price: Decimal,
+    ) -> Option<Decimal> {
+        let offset = current_price * self.limit_slippage_pct? / dec!(100);
+        let price = match side {
+            OrderSide::Buy => current_price + offset,
+            OrderSide::Sell => current_price - offset,
+        };
+
+        // Stay at the precision the exchange quotes the symbol in
+        Some(
+            self.round_price(price, symbol)
+                .round_dp(current_price.scale()),
+        )
+    }
+
+    /// Snaps `price` to `symbol`'s tick size, as Binance rejects other order
+    /// prices with -1013. Leaves it alone until exchange info is cached.
+    fn round_price(&self, price: Decimal, symbol: &str) -> Decimal {
+        match self.symbol_info.get().and_then(|info| info.get(symbol)) {
+            Some(info) => info.round_price(price),
+            None => price,
+        }
+    }
+
+    /// Whether `symbol` traded less than the cooldown ago.
+    fn in_cooldown(&self, symbol: &str) -> bool {
+        self.last_trade_time
+            .read()
+            .unwrap()
+            .get(symbol)
+            .is_some_and(|traded_at| {
+                // A clock that went backwards keeps the cooldown running
+                (self.clock.now() - *traded_at)
+                    .to_std()
+                    .map_or(true, |elapsed| elapsed < self.cooldown)
+            })
+    }
+
+    /// Counts a placed order, starts the symbol's cooldown, remembers its
+    /// client order id and drops the cached account.
+    fn record_order_placed(&self, order: &OrderRequest) {
+        let (symbol, side) = (order.symbol.as_str(), order.side);
+        self.invalidate_account_cache();
+        self.last_trade_time
+            .write()
+            .unwrap()
+            .insert(symbol.to_string(), self.clock.now());
+        if let Some(id) = &order.client_order_id {
+            self.placed_order_ids
+                .write()
+                .unwrap()
+                .insert((symbol.to_string(), side), id.clone());
+        }
+
+        ::metrics::counter!(
+            ORDERS_PLACED,
+            "symbol" => symbol.to_string(),
+            "side" => side.to_string(),
+            "paper" => self.paper_trading.to_string()
+        )
+        .increment(1);
+    }
+
+    /// Whether an order with `order`'s client order id was already placed.
+    fn already_placed(&self, order: &OrderRequest) -> bool {
+        order.client_order_id.as_ref().is_some_and(|id| {
+            self.placed_order_ids
+                .read()
+                .unwrap()
+                .get(&(order.symbol.clone(), order.side))
+                == Some(id)
+        })
+    }
+
+    async fn notify(&self, notification: Notification) {
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify(&notification).await {
+                warn!("Failed to send notification: {:#}", e);
+            }
+        }
+    }
+
+    async fn notify_fill(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        price: Decimal,
+        paper: bool,
+    ) {
+        self.notify(Notification::OrderFilled {
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            price,
+            paper,
+        })
+        .await;
+    }
+
+    async fn notify_rejection(&self, order: &OrderRequest, reason: String) {
+        self.notify(Notification::OrderRejected {
+            symbol: order.symbol.clone(),
+            side: order.side,
+            reason,
+        })
+        .await;
+    }
+
+    /// Reports the daily loss limit as its own event, since it blocks every
+    /// order until the reset rather than just this one.
+    async fn notify_risk_rejection(&self, order: &OrderRequest, error: RiskError) {
+        match error {
+            RiskError::DailyLossExceeded { .. } => self.notify_daily_loss_limit().await,
+            e => self.notify_rejection(order, e.to_string()).await,
+        }
+    }
+
+    /// Notifies once each time the daily loss limit starts blocking trades.
+    async fn notify_daily_loss_limit(&self) {
+        let loss_pct = self.risk_manager.current_daily_loss();
+        let limit_pct = self.risk_manager.max_daily_loss_pct();
+        if loss_pct < limit_pct || self.daily_loss_notified.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        self.notify(Notification::DailyLossLimit {
+            loss_pct,
+            limit_pct,
+        })
+        .await;
+    }
+
+    #[cfg(feature = "journal")]
+    fn journal_trade(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        price: Decimal,
+        timestamp: u64,
+        order_id: Option<u64>,
+    ) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+
+        let record = TradeRecord {
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            price,
+            timestamp,
+            paper: self.paper_trading,
+            order_id,
+        };
+
+        if let Err(e) = journal.record(&record) {
+            error!("Failed to journal trade: {}", e);
+        }
+    }
+
+    /// Base and quote assets of `symbol` as listed by the exchange. Exchange
+    /// info is fetched once, on first use, for all configured symbols.
+    async fn symbol_info(&self, symbol: &str) -> Result<&SymbolInfo> {
+        let info = self
+            .symbol_info
+            .get_or_try_init(|| async {
+                let exchange_info = self.client.get_exchange_info().await?;
+                Ok::<_, BinanceError>(
+                    exchange_info
+                        .symbols
+                        .into_iter()
+                        .filter(|s| self.symbols.contains(&s.symbol))
+                        .map(|s| (s.symbol.clone(), s))
+                        .collect(),
+                )
+            })
+            .await?;
+
+        info.get(symbol)
+            .ok_or_else(|| anyhow::anyhow!("{} is not listed in exchange info", symbol))
+    }
+
+    /// `balance` less the quote reserve, which buys must leave alone.
+    fn spendable(&self, balance: &Balance) -> Balance {
+        if !self.risk_manager.is_stable_quote(&balance.asset) {
+            return balance.clone();
+        }
+
+        let free = (balance.free_decimal() - self.quote_reserve).max(dec!(0));
+        Balance {
+            free: free.to_string(),
+            ..balance.clone()
+        }
+    }
+
+    /// Why fees may soon stop coming out of the fee asset, if its live
+    /// balance is below the configured minimum. Paper trading pays its
+    /// simulated fees in the quote asset.
+    fn fee_asset_warning(&self, balances: &[Balance]) -> Option<String> {
+        let (fee_asset, min_balance) = self.fee_asset.as_ref()?;
+        if self.paper_trading {
+            return None;
+        }
+
+        let balance = balances
+            .iter()
+            .find(|b| &b.asset == fee_asset)
+            .map(Balance::free_decimal)
+            .unwrap_or_default();
+        (balance < *min_balance).then(|| {
+            format!(
+                "{} balance {} is below {}; fees may be taken from traded assets instead",
+                fee_asset, balance, min_balance
+            )
+        })
+    }
+
+    /// Rounds `quantity` down to `symbol`'s LOT_SIZE step once exchange
+    /// info is cached, and to a fixed precision before that.
+    fn round_quantity(&self, quantity: Decimal, symbol: &str) -> Decimal {
+        self.symbol_info
+            .get()
+            .and_then(|info| info.get(symbol))
+            .and_then(|info| info.floor_quantity(quantity))
+            .unwrap_or_else(|| quantity.round_dp(self.quantity_precision(symbol)))
+    }
+
+    /// Sells all of `available` instead of `quantity` when the rest would be
+    /// too small to ever sell: under the symbol's LOT_SIZE minimum, or worth
+    /// less than the minimum order value at `price`.
+    fn avoid_dust(
+        &self,
+        symbol: &str,
+        quantity: Decimal,
+        available: Decimal,
+        price: Decimal,
+    ) -> Decimal {
+        let remainder = available - quantity;
+        if remainder <= dec!(0) {
+            return quantity;
+        }
+
+        let info = self.symbol_info.get().and_then(|info| info.get(symbol));
+        let min_qty = info.and_then(SymbolInfo::min_qty).unwrap_or_default();
+        let min_notional = info
+            .and_then(SymbolInfo::min_notional)
+            .unwrap_or_default()
+            .max(info.map_or(dec!(0), |info| {
+                self.risk_manager.min_order_notional_for(&info.quote_asset)
+            }));
+        if remainder >= min_qty && remainder * price >= min_notional {
+            return quantity;
+        }
+
+        let everything = self.floor_quantity(available, symbol);
+        if everything > quantity {
+            info!(
+                "{}: selling all {} instead of {}, the {} left would be too small to sell",
+                symbol, everything, quantity, remainder
+            );
+        }
+        everything.max(quantity)
+    }
+
+    /// Clamps a reduce-only sell to what the bot holds of `symbol`: the
+    /// tracked long position, if any, and at most `free`, the free base
+    /// balance. The cap is rounded down so rounding can't push the order
+    /// back over it. `None` when nothing is left to sell.
+    fn clamp_reduce_only(&self, mut order: OrderRequest, free: Decimal) -> Option<OrderRequest> {
+        let held = match self.risk_manager.position(&order.symbol) {
+            Some(position) if position.side != order.side => position.quantity.min(free),
+            _ => free,
+        };
+        let held = self.floor_quantity(held, &order.symbol);
+        let requested = order.quantity;
+        if order.clamp_to_held(held) {
+            if order.quantity <= dec!(0) {
+                debug!(
+                    "Reduce-only {:?} {} {} skipped, nothing held",
+                    order.side, requested, order.symbol
+                );
+                return None;
+            }
+            info!(
+                "Reduce-only {:?} {} {} clamped to the {} held",
+                order.side, requested, order.symbol, order.quantity
+            );
+        }
+        Some(order)
+    }
+
+    /// Like `round_quantity`, but never rounds up.
+    fn floor_quantity(&self, quantity: Decimal, symbol: &str) -> Decimal {
+        self.symbol_info
+            .get()
+            .and_then(|info| info.get(symbol))
+            .and_then(|info| info.floor_quantity(quantity))
+            .unwrap_or_else(|| {
+                quantity.round_dp_with_strategy(
+                    self.quantity_precision(symbol),
+                    RoundingStrategy::ToZero,
+                )
+            })
+    }
+
+    fn quantity_precision(&self, symbol: &str) -> u32 {
+        // Simplified rounding - in production, fetch from exchange info
+        if symbol.starts_with("BTC") {
+            5
+        } else if symbol.starts_with("ETH") {
+            4
+        } else {
+            3
+        }
+    }
+}
+
+/// A symbol after the checks that don't need its strategy.
+enum Prepared {
+    /// Up to the strategy
+    Analyze(crate::exchange::MarketData),
+    /// A protective exit fired: sell the whole position at this strength
+    Exit(crate::exchange::MarketData, Decimal),
+}
+
+/// Splits `prepared` into the symbols left to the strategy and the exits.
+fn split_prepared(
+    prepared: Vec<Prepared>,
+) -> (
+    Vec<crate::exchange::MarketData>,
+    Vec<(crate::exchange::MarketData, Decimal)>,
+) {
+    let mut ready = Vec::new();
+    let mut exits = Vec::new();
+    for prepared in prepared {
+        match prepared {
+            Prepared::Analyze(market_data) => ready.push(market_data),
+            Prepared::Exit(market_data, strength) => exits.push((market_data, strength)),
+        }
+    }
+    (ready, exits)
+}
+
+/// The free balance of `quote_asset` a buy is sized from.
+fn quote_balance<'a>(balances: &'a [Balance], quote_asset: &str) -> Result<&'a Balance> {
+    balances
+        .iter()
+        .find(|b| b.asset == quote_asset)
+        .ok_or_else(|| anyhow::anyhow!("Quote balance not found for {}", quote_asset))
+}
+
+/// The balance of `asset`, or an empty one if the account holds none.
+fn balance_or_zero(balances: &[Balance], asset: &str) -> Balance {
+    balances
+        .iter()
+        .find(|b| b.asset == asset)
+        .cloned()
+        .unwrap_or(Balance {
+            asset: asset.to_string(),
+            free: "0".to_string(),
+            locked: "0".to_string(),
+        })
+}
+
+/// Ticks every `period` for the trading loop. A cycle that overruns is
+/// followed by a single catch-up tick, then the schedule resumes on the
+/// original grid instead of firing every missed tick back to back.
+fn cycle_interval(period: tokio::time::Duration) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    interval
+}
+
+/// Saves `risk_manager`'s state to `path` every `every`, so a crash loses at
+/// most one interval of position tracking.
+async fn save_state_periodically(
+    risk_manager: Arc<RiskManager>,
+    path: PathBuf,
+    every: tokio::time::Duration,
+) {
+    let mut interval = tokio::time::interval(every);
+    // The first tick completes immediately, before anything has changed
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        if let Err(e) = risk_manager.save_state(&path) {
+            warn!("Failed to save state: {:#}", e);
+        }
+    }
+}
+
+fn describe_price(order: &OrderRequest) -> String {
+    match order.price {
+        Some(price) => format!("limit {}", price),
+        None => "market price".to_string(),
+    }
+}
+
+/// Whether an order status can no longer change.
+fn is_final_status(status: &str) -> bool {
+    matches!(
+        status,
+        "FILLED" | "CANCELED" | "REJECTED" | "EXPIRED" | "EXPIRED_IN_MATCH"
+    )
+}
+
+/// Open time of the newest candle in `market_data`, which identifies the
+/// signal a trade on it answers.
+fn signal_candle(market_data: &crate::exchange::MarketData) -> u64 {
+    market_data
+        .klines
+        .last()
+        .map_or(market_data.timestamp, |kline| kline.open_time)
+}
+
+/// Client order id for a `side` trade on `symbol` prompted by the candle
+/// opened at `candle_open_time`. The same intent always gets the same id, so
+/// the engine can skip a repeat, and a request that failed midway can be
+/// looked up before it is resent.
+pub(super) fn client_order_id(symbol: &str, side: OrderSide, candle_open_time: u64) -> String {
+    name_uuid(&format!("{}:{}:{}", symbol, side, candle_open_time))
+}
+
+/// UUID (version 8, name-based) from the SHA-256 of `name`.
+pub(super) fn name_uuid(name: &str) -> String {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&Sha256::digest(name.as_bytes())[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x80;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Fails when `account` may not place orders, as with a read-only API key.
+fn ensure_can_trade(account: &AccountInfo) -> Result<()> {
+    anyhow::ensure!(
+        account.can_trade,
+        "Trading is not permitted for this account or API key; enable spot \
+         trading on the key or run with --paper"
+    );
+    Ok(())
+}
+
+/// A cycle error the engine cannot recover from by simply trying again.
+fn is_fatal(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<BinanceError>()
+        .is_some_and(BinanceError::is_fatal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::{ClientConfig, Environment, ExchangeCredentials};
+    use crate::exchange::mock::MockExchange;
+    use crate::exchange::{BinanceClient, MarketData, OrderType, RetryPolicy};
+    use crate::risk::PositionScaling;
+    use mockito::Matcher;
+
+    fn create_engine(server_url: &str, paper_trading: bool) -> TradingEngine {
+        let credentials = ExchangeCredentials {
+            api_key: "key".to_string(),
+            secret_key: "secret".to_string(),
+            environment: Environment::Testnet,
+        };
+        let client = BinanceClient::new(credentials, &ClientConfig::default())
+            .unwrap()
+            .with_base_url(server_url)
+            .with_retry_policy(RetryPolicy::new(1, std::time::Duration::from_millis(1)));
+
+        TradingEngine::new(
+            Box::new(client),
+            RiskManager::new(dec!(2), dec!(5), 3),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCUSDT".to_string()],
+            paper_trading,
+        )
+        .with_fill_polling(std::time::Duration::from_millis(1), 3)
+    }
+
+    fn order_response(status: &str, executed_qty: &str) -> OrderResponse {
+        serde_json::from_str(&format!(
+            r#"{{"symbol": "BTCUSDT", "orderId": 9, "clientOrderId": "c9", "transactTime": 0,
+                "price": "0", "origQty": "1", "executedQty": "{}", "cummulativeQuoteQty": "0",
+                "status": "{}", "timeInForce": "GTC", "type": "MARKET", "side": "BUY"}}"#,
+            executed_qty, status
+        ))
+        .unwrap()
+    }
+
+    fn query_order_json(status: &str, executed_qty: &str, quote_qty: &str) -> String {
+        format!(
+            r#"{{"symbol": "BTCUSDT", "orderId": 9, "clientOrderId": "c9",
+                "price": "0", "origQty": "1", "executedQty": "{}",
+                "cummulativeQuoteQty": "{}", "status": "{}", "timeInForce": "GTC",
+                "type": "MARKET", "side": "BUY", "time": 0, "updateTime": 0}}"#,
+            executed_qty, quote_qty, status
+        )
+    }
+
+    fn cancel_json(order_id: u64) -> String {
+        format!(
+            r#"{{"symbol": "BTCUSDT", "orderId": {}, "clientOrderId": "c{}",
+                "status": "CANCELED"}}"#,
+            order_id, order_id
+        )
+    }
+
+    const ACCOUNT_JSON: &str = r#"{
+        "makerCommission": 10, "takerCommission": 10,
+        "buyerCommission": 0, "sellerCommission": 0,
+        "canTrade": true, "canWithdraw": false, "canDeposit": false,
+        "updateTime": 0, "accountType": "SPOT",
+        "balances": [{"asset": "USDT", "free": "10000", "locked": "0"}]
+    }"#;
+
+    fn klines_json(closes: &[&str]) -> String {
+        let klines: Vec<String> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                format!(
+                    r#"[{},"{c}","{c}","{c}","{c}","100",{},"10000",100,"50","5000","0"]"#,
+                    i * 3600000,
+                    (i + 1) * 3600000,
+                    c = c
+                )
+            })
+            .collect();
+        format!("[{}]", klines.join(","))
+    }
+
+    /// `(symbol, base, quote)` triples as an exchangeInfo response.
+    fn exchange_info_json(symbols: &[(&str, &str, &str)]) -> String {
+        let symbols: Vec<String> = symbols
+            .iter()
+            .map(|(symbol, base, quote)| {
+                format!(
+                    r#"{{"symbol": "{}", "status": "TRADING", "baseAsset": "{}",
+                        "quoteAsset": "{}", "baseAssetPrecision": 8, "quotePrecision": 8}}"#,
+                    symbol, base, quote
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"timezone": "UTC", "serverTime": 0, "symbols": [{}]}}"#,
+            symbols.join(",")
+        )
+    }
+
+    async fn mock_exchange_info(server: &mut mockito::ServerGuard) -> mockito::Mock {
+        server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_body(exchange_info_json(&[("BTCUSDT", "BTC", "USDT")]))
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_run_once_processes_all_symbols_concurrently() {
+        let mut server = mockito::Server::new_async().await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+
+        // One symbol fails up front; the others must still be processed
+        let _failing = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BADUSDT")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let mut klines_mocks = Vec::new();
+        for symbol in ["BTCUSDT", "ETHUSDT", "SOLUSDT"] {
+            server
+                .mock(
+                    "GET",
+                    format!("/api/v3/ticker/price?symbol={}", symbol).as_str(),
+                )
+                .with_body(format!(r#"{{"symbol": "{}", "price": "10"}}"#, symbol))
+                .create_async()
+                .await;
+            klines_mocks.push(
+                server
+                    .mock(
+                        "GET",
+                        Matcher::Regex(format!("^/api/v3/klines\\?symbol={}&", symbol)),
+                    )
+                    .with_body(klines_json(&["10", "10", "10", "10", "10", "10"]))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let mut engine = create_engine(&server.url(), true).with_max_concurrent_symbols(2);
+        engine.symbols = vec![
+            "BADUSDT".to_string(),
+            "BTCUSDT".to_string(),
+            "ETHUSDT".to_string(),
+            "SOLUSDT".to_string(),
+        ];
+
+        engine.run_once().await.unwrap();
+
+        for mock in klines_mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_open_orders_in_one_request() {
+        let mut server = mockito::Server::new_async().await;
+        let cancel = server
+            .mock(
+                "DELETE",
+                Matcher::Regex("^/api/v3/openOrders\\?symbol=BTCUSDT&".to_string()),
+            )
+            .with_body(format!("[{},{}]", cancel_json(1), cancel_json(2)))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false).with_cancel_orders_on_shutdown(true);
+        engine.shutdown().await.unwrap();
+
+        cancel.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_saves_state_file() {
+        let path = std::env::temp_dir().join(format!(
+            "cryptobot-engine-state-{}.json",
+            std::process::id()
+        ));
+        let mut engine = create_engine("http://127.0.0.1:1", true)
+            .with_state_file(path.clone(), std::time::Duration::from_secs(60));
+        engine.risk_manager.open_position(Position::new(
+            "BTCUSDT",
+            dec!(30000),
+            dec!(0.5),
+            OrderSide::Buy,
+            0,
+        ));
+
+        engine.shutdown().await.unwrap();
+
+        let restored = RiskManager::new(dec!(2), dec!(5), 3);
+        let state = restored.load_state(&path, Tz::UTC).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(state.positions, engine.risk_manager.positions());
+        assert_eq!(restored.position("BTCUSDT").unwrap().quantity, dec!(0.5));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_leaves_orders_when_disabled() {
+        let mut server = mockito::Server::new_async().await;
+        let open = server
+            .mock("DELETE", Matcher::Regex("^/api/v3/openOrders".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false);
+        engine.shutdown().await.unwrap();
+
+        open.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_run_once_skips_cycle_while_trading_disabled() {
+        let mut server = mockito::Server::new_async().await;
+        let account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false);
+        engine.trading_enabled().store(false, Ordering::SeqCst);
+        engine.run_once().await.unwrap();
+
+        account.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_cancels_orders_and_disables_trading() {
+        let mut server = mockito::Server::new_async().await;
+        let cancel = server
+            .mock("DELETE", Matcher::Regex("^/api/v3/openOrders".to_string()))
+            .with_body(format!("[{}]", cancel_json(1)))
+            .expect(1)
+            .create_async()
+            .await;
+        let account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false).with_circuit_breaker(dec!(3));
+
+        // Within the soft 5% limit but past the 3% breaker
+        engine.risk_manager.record_trade_result(dec!(-4));
+        assert!(engine.risk_manager.can_trade());
+        engine.run_once().await.unwrap();
+
+        cancel.assert_async().await;
+        account.assert_async().await;
+        assert!(!engine.trading_enabled().load(Ordering::SeqCst));
+
+        // Stays off after the daily reset clears the loss
+        engine.risk_manager.reset_daily_stats();
+        engine.run_once().await.unwrap();
+        account.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_symbol_info_resolves_assets_from_exchange_info() {
+        let mut server = mockito::Server::new_async().await;
+        let exchange_info = server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_body(exchange_info_json(&[
+                ("ETHBTC", "ETH", "BTC"),
+                ("BNBETH", "BNB", "ETH"),
+                ("SOLUSDT", "SOL", "USDT"),
+                ("XRPUSDT", "XRP", "USDT"),
+            ]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), true);
+        engine.symbols = vec![
+            "ETHBTC".to_string(),
+            "BNBETH".to_string(),
+            "SOLUSDT".to_string(),
+        ];
+
+        for (symbol, base, quote) in [
+            ("ETHBTC", "ETH", "BTC"),
+            ("BNBETH", "BNB", "ETH"),
+            ("SOLUSDT", "SOL", "USDT"),
+        ] {
+            let info = engine.symbol_info(symbol).await.unwrap();
+            assert_eq!(info.base_asset, base);
+            assert_eq!(info.quote_asset, quote);
+        }
+
+        // Listed on the exchange but not configured, so not loaded
+        assert!(engine.symbol_info("XRPUSDT").await.is_err());
+        exchange_info.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_buy_uses_listed_quote_asset_balance() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_body(exchange_info_json(&[("BNBETH", "BNB", "ETH")]))
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), true);
+        engine.symbols = vec!["BNBETH".to_string()];
+        let market_data = MarketData {
+            symbol: "BNBETH".to_string(),
+            current_price: dec!(0.2),
+            klines: Vec::new(),
+            timestamp: 0,
+            mid_price: None,
+        };
+        // Plenty of USDT, but BNBETH is paid for in ETH
+        let balances = vec![Balance {
+            asset: "USDT".to_string(),
+            free: "10000".to_string(),
+            locked: "0".to_string(),
+        }];
+
+        let err = engine
+            .execute_buy("BNBETH", &market_data, &balances, Decimal::ONE)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Quote balance not found for ETH");
+    }
+
+    #[test]
+    fn test_entry_order_matches_order_type() {
+        let engine = create_engine("http://127.0.0.1:1", true);
+        let order = engine.entry_order("BTCUSDT", OrderSide::Buy, dec!(0.01), dec!(30000.00));
+        assert!(matches!(order.order_type, OrderType::Market));
+        assert_eq!(order.price, None);
+
+        let engine = engine.with_limit_orders(dec!(0.1));
+        let buy = engine.entry_order("BTCUSDT", OrderSide::Buy, dec!(0.01), dec!(30000.00));
+        assert!(matches!(buy.order_type, OrderType::Limit));
+        assert_eq!(buy.price, Some(dec!(30030.00)));
+
+        let sell = engine.entry_order("BTCUSDT", OrderSide::Sell, dec!(0.01), dec!(30000.00));
+        assert!(matches!(sell.order_type, OrderType::Limit));
+        assert_eq!(sell.price, Some(dec!(29970.00)));
+    }
+
+    #[tokio::test]
+    async fn test_limit_prices_snap_to_tick_size() {
+        let mut server = mockito::Server::new_async().await;
+        let symbol = |symbol: &str, tick_size: &str| {
+            format!(
+                r#"{{"symbol": "{}", "status": "TRADING", "baseAsset": "X", "quoteAsset": "USDT",
+                    "baseAssetPrecision": 8, "quotePrecision": 8, "filters": [
+                    {{"filterType": "PRICE_FILTER", "minPrice": "0", "maxPrice": "0", "tickSize": "{}"}},
+                    {{"filterType": "LOT_SIZE", "minQty": "0.1", "maxQty": "1000", "stepSize": "0.1"}}]}}"#,
+                symbol, tick_size
+            )
+        };
+        let _exchange_info = server
+            .mock("GET", "/api/v3/exchangeInfo")
+            .with_body(format!(
+                r#"{{"timezone": "UTC", "serverTime": 0, "symbols": [{},{},{}]}}"#,
+                symbol("BTCUSDT", "0.01000000"),
+                symbol("DOGEUSDT", "0.00010000"),
+                symbol("ETHUSDT", "0.00000000")
+            ))
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), true).with_limit_orders(dec!(0.1));
+        engine.symbols = vec![
+            "BTCUSDT".to_string(),
+            "DOGEUSDT".to_string(),
+            "ETHUSDT".to_string(),
+        ];
+
+        // Nothing to snap to before exchange info is cached
+        assert_eq!(
+            engine.round_price(dec!(0.123456), "DOGEUSDT"),
+            dec!(0.123456)
+        );
+        engine.symbol_info("BTCUSDT").await.unwrap();
+
+        assert_eq!(
+            engine.round_price(dec!(30030.004), "BTCUSDT"),
+            dec!(30030.00)
+        );
+        assert_eq!(
+            engine.round_price(dec!(30030.005), "BTCUSDT"),
+            dec!(30030.01)
+        );
+        assert_eq!(engine.round_price(dec!(0.123456), "DOGEUSDT"), dec!(0.1235));
+        assert_eq!(engine.round_price(dec!(0.12344), "DOGEUSDT"), dec!(0.1234));
+        // A tick size of 0 means the filter is off
+        assert_eq!(
+            engine.round_price(dec!(2000.123456), "ETHUSDT"),
+            dec!(2000.123456)
+        );
+
+        let buy = engine.entry_order("DOGEUSDT", OrderSide::Buy, dec!(100), dec!(0.08123000));
+        assert_eq!(buy.price, Some(dec!(0.0813)));
+        let sell = engine.entry_order("DOGEUSDT", OrderSide::Sell, dec!(100), dec!(0.08123000));
+        assert_eq!(sell.price, Some(dec!(0.0811)));
+    }
+
+    #[tokio::test]
+    async fn test_live_buy_places_priced_limit_order() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        // Golden cross on the last candle
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        let order = server
+            .mock(
+                "POST",
+                Matcher::Regex(
+                    "^/api/v3/order\\?symbol=BTCUSDT&side=BUY&type=LIMIT&quantity=[0-9.]+&price=25.25&timeInForce=GTC&"
+                        .to_string(),
+                ),
+            )
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 7, "clientOrderId": "c7", "transactTime": 0,
+                    "price": "25.25", "origQty": "7.8", "executedQty": "0", "status": "NEW",
+                    "timeInForce": "GTC", "type": "LIMIT", "side": "BUY"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let _query = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(query_order_json("NEW", "0", "0"))
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false).with_limit_orders(dec!(1));
+        engine.run_once().await.unwrap();
+
+        order.assert_async().await;
+        // Nothing filled yet, so no position is tracked
+        assert_eq!(engine.risk_manager.open_positions_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_limit_order_cancelled_once_past_ttl() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        let _order = server
+            .mock("POST", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 9, "clientOrderId": "c9", "transactTime": 0,
+                    "price": "25.25", "origQty": "7.8", "executedQty": "0", "status": "NEW",
+                    "timeInForce": "GTC", "type": "LIMIT", "side": "BUY"}"#,
+            )
+            .create_async()
+            .await;
+        let _query = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(query_order_json("NEW", "0", "0"))
+            .create_async()
+            .await;
+
+        let clock = Arc::new(MockClock::new("2024-01-01T12:00:00Z".parse().unwrap()));
+        let mut engine = create_engine(&server.url(), false)
+            .with_limit_orders(dec!(1))
+            .with_order_ttl(std::time::Duration::from_secs(60))
+            .with_clock(clock.clone());
+        engine.run_once().await.unwrap();
+        assert_eq!(engine.resting_orders.read().unwrap().len(), 1);
+
+        let cancel = server
+            .mock(
+                "DELETE",
+                Matcher::Regex("^/api/v3/order\\?symbol=BTCUSDT&orderId=9&".to_string()),
+            )
+            .with_body(cancel_json(9))
+            .expect(0)
+            .create_async()
+            .await;
+
+        // Within the TTL the order is left on the book
+        clock.advance(std::time::Duration::from_secs(59));
+        engine.check_resting_orders().await;
+        cancel.assert_async().await;
+        assert_eq!(engine.resting_orders.read().unwrap().len(), 1);
+
+        let cancel = cancel.expect(1);
+        clock.advance(std::time::Duration::from_secs(1));
+        engine.check_resting_orders().await;
+        cancel.assert_async().await;
+        assert!(engine.resting_orders.read().unwrap().is_empty());
+    }
+
+    /// An engine whose golden cross left limit buy 9 (7.8 BTC at 25.25)
+    /// resting unfilled on `server`, `ttl` later if it has one, and the
+    /// mocks that got it there.
+    async fn engine_with_resting_order(
+        server: &mut mockito::ServerGuard,
+        ttl: Option<std::time::Duration>,
+    ) -> (TradingEngine, Vec<mockito::Mock>) {
+        let exchange_info = mock_exchange_info(server).await;
+        let account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        let klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        let order = server
+            .mock("POST", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 9, "clientOrderId": "c9", "transactTime": 0,
+                    "price": "25.25", "origQty": "7.8", "executedQty": "0", "status": "NEW",
+                    "timeInForce": "GTC", "type": "LIMIT", "side": "BUY"}"#,
+            )
+            .create_async()
+            .await;
+        let query = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(query_order_json("NEW", "0", "0"))
+            .create_async()
+            .await;
+
+        let clock = Arc::new(MockClock::new("2024-01-01T12:00:00Z".parse().unwrap()));
+        let mut engine = create_engine(&server.url(), false)
+            .with_limit_orders(dec!(1))
+            .with_clock(clock.clone());
+        if let Some(ttl) = ttl {
+            engine = engine.with_order_ttl(ttl);
+        }
+        engine.run_once().await.unwrap();
+        assert!(engine.risk_manager.positions().is_empty());
+
+        clock.advance(ttl.unwrap_or_default());
+        (engine, vec![exchange_info, account, ticker, klines, order, query])
+    }
+
+    fn resting_order_json(order_id: u64, status: &str, executed_qty: &str, quote_qty: &str) -> String {
+        format!(
+            r#"{{"symbol": "BTCUSDT", "orderId": {}, "clientOrderId": "c{}",
+                "price": "25.25", "origQty": "7.8", "executedQty": "{}",
+                "cummulativeQuoteQty": "{}", "status": "{}", "timeInForce": "GTC",
+                "type": "LIMIT", "side": "BUY", "time": 0, "updateTime": 0}}"#,
+            order_id, order_id, executed_qty, quote_qty, status
+        )
+    }
+
+    #[tokio::test]
+    async fn test_resting_order_fills_are_booked_each_cycle_without_a_ttl() {
+        let mut server = mockito::Server::new_async().await;
+        let (engine, _mocks) = engine_with_resting_order(&mut server, None).await;
+        assert_eq!(engine.resting_orders.read().unwrap().len(), 1);
+
+        let partial = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(resting_order_json(9, "PARTIALLY_FILLED", "3", "75.75"))
+            .create_async()
+            .await;
+        engine.check_resting_orders().await;
+        assert_eq!(
+            engine.risk_manager.position("BTCUSDT").unwrap().quantity,
+            dec!(3)
+        );
+        assert_eq!(engine.resting_orders.read().unwrap().len(), 1);
+
+        // The rest fills at a better price; only the new part is booked
+        partial.remove_async().await;
+        let _filled = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(resting_order_json(9, "FILLED", "7.8", "193.92"))
+            .create_async()
+            .await;
+        engine.check_resting_orders().await;
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.quantity, dec!(7.8));
+        assert_eq!(position.legs, vec![dec!(3), dec!(4.8)]);
+        assert!(engine.resting_orders.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_order_that_filled_while_resting_opens_the_position() {
+        let mut server = mockito::Server::new_async().await;
+        let (engine, _mocks) = engine_with_resting_order(&mut server, Some(std::time::Duration::from_secs(60))).await;
+        let _filled = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(resting_order_json(9, "FILLED", "7.8", "196.95"))
+            .create_async()
+            .await;
+        let cancel = server
+            .mock("DELETE", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        engine.check_resting_orders().await;
+
+        cancel.assert_async().await;
+        assert!(engine.resting_orders.read().unwrap().is_empty());
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.quantity, dec!(7.8));
+        assert_eq!(position.entry_price, dec!(25.25));
+    }
+
+    #[tokio::test]
+    async fn test_expired_partial_fill_is_booked_and_the_rest_re_placed() {
+        let mut server = mockito::Server::new_async().await;
+        let (engine, _mocks) = engine_with_resting_order(&mut server, Some(std::time::Duration::from_secs(60))).await;
+        let engine = engine.with_expired_order_repricing();
+        let _partial = server
+            .mock(
+                "GET",
+                Matcher::Regex("^/api/v3/order\\?symbol=BTCUSDT&orderId=9&".to_string()),
+            )
+            .with_body(resting_order_json(9, "PARTIALLY_FILLED", "3", "75.75"))
+            .create_async()
+            .await;
+        let cancel = server
+            .mock(
+                "DELETE",
+                Matcher::Regex("^/api/v3/order\\?symbol=BTCUSDT&orderId=9&".to_string()),
+            )
+            .with_body(cancel_json(9))
+            .expect(1)
+            .create_async()
+            .await;
+        let replaced = server
+            .mock(
+                "POST",
+                Matcher::Regex("^/api/v3/order\\?.*quantity=4.8&price=25.25".to_string()),
+            )
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 10, "clientOrderId": "c10", "transactTime": 0,
+                    "price": "25.25", "origQty": "4.8", "executedQty": "0", "status": "NEW",
+                    "timeInForce": "GTC", "type": "LIMIT", "side": "BUY"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let _replaced_query = server
+            .mock(
+                "GET",
+                Matcher::Regex("^/api/v3/order\\?symbol=BTCUSDT&orderId=10&".to_string()),
+            )
+            .with_body(resting_order_json(10, "NEW", "0", "0"))
+            .create_async()
+            .await;
+
+        engine.check_resting_orders().await;
+
+        cancel.assert_async().await;
+        replaced.assert_async().await;
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.quantity, dec!(3));
+        assert_eq!(position.entry_price, dec!(25.25));
+
+        // The rest rests in its place, under the same TTL
+        let resting = engine.resting_orders.read().unwrap();
+        assert_eq!(resting.len(), 1);
+        assert_eq!(resting[0].order_id, 10);
+    }
+
+    #[tokio::test]
+    async fn test_requests_klines_at_strategy_interval() {
+        let mut server = mockito::Server::new_async().await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "20.00"}"#)
+            .create_async()
+            .await;
+        let klines = server
+            .mock(
+                "GET",
+                Matcher::Regex("^/api/v3/klines\\?symbol=BTCUSDT&interval=15m&".to_string()),
+            )
+            .with_body(klines_json(&["20", "20", "20", "20", "20", "20"]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), true);
+        engine.strategy = Box::new(crate::strategy::WithInterval::new(
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            "15m",
+        ));
+        engine.run_once().await.unwrap();
+
+        klines.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_fixed_quote_strategy_sizes_by_quote_amount() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["25"]))
+            .create_async()
+            .await;
+        // 50 USDT is within the 2% of 10000 risk cap and is spent as-is
+        let order = server
+            .mock(
+                "POST",
+                Matcher::Regex(
+                    "^/api/v3/order\\?symbol=BTCUSDT&side=BUY&type=MARKET&quoteOrderQty=50&"
+                        .to_string(),
+                ),
+            )
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 8, "clientOrderId": "c8", "transactTime": 0,
+                    "price": "0", "origQty": "2", "executedQty": "2", "status": "FILLED",
+                    "timeInForce": "GTC", "type": "MARKET", "side": "BUY"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false);
+        engine.strategy = Box::new(crate::strategy::DcaStrategy::new(dec!(50), 3));
+        engine.run_once().await.unwrap();
+
+        order.assert_async().await;
+        assert_eq!(
+            engine.risk_manager.position("BTCUSDT").unwrap().quantity,
+            dec!(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_await_fill_polls_until_filled() {
+        let mut server = mockito::Server::new_async().await;
+        let polls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = polls.clone();
+
+        // PARTIALLY_FILLED on the first poll, FILLED on the second
+        let query = server
+            .mock(
+                "GET",
+                Matcher::Regex("^/api/v3/order\\?symbol=BTCUSDT&orderId=9&".to_string()),
+            )
+            .with_body_from_request(move |_| {
+                if counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                    query_order_json("PARTIALLY_FILLED", "0.4", "40")
+                } else {
+                    query_order_json("FILLED", "1", "102")
+                }
+                .into()
+            })
+            .expect(2)
+            .create_async()
+            .await;
+
+        let engine = create_engine(&server.url(), false);
+        let fill = engine
+            .await_fill(&order_response("NEW", "0"), dec!(1), dec!(100))
+            .await;
+
+        query.assert_async().await;
+        assert_eq!(
+            fill,
+            Fill {
+                quantity: dec!(1),
+                avg_price: dec!(102)
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_await_fill_keeps_partial_fill_when_polling_gives_up() {
+        let mut server = mockito::Server::new_async().await;
+        let query = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .with_body(query_order_json("PARTIALLY_FILLED", "0.25", "25.5"))
+            .expect(3)
+            .create_async()
+            .await;
+
+        let engine = create_engine(&server.url(), false);
+        let fill = engine
+            .await_fill(&order_response("NEW", "0"), dec!(1), dec!(100))
+            .await;
+
+        query.assert_async().await;
+        assert_eq!(
+            fill,
+            Fill {
+                quantity: dec!(0.25),
+                avg_price: dec!(102)
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_await_fill_skips_polling_final_orders() {
+        let mut server = mockito::Server::new_async().await;
+        let query = server
+            .mock("GET", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let engine = create_engine(&server.url(), false);
+        let fill = engine
+            .await_fill(&order_response("FILLED", "0.5"), dec!(1), dec!(100))
+            .await;
+
+        query.assert_async().await;
+        assert_eq!(
+            fill,
+            Fill {
+                quantity: dec!(0.5),
+                avg_price: dec!(100)
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_market_buy_routed_through_twap() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        // The 100-200 USDT buy goes out as 4 quote-sized slices
+        let children = server
+            .mock(
+                "POST",
+                Matcher::Regex(
+                    "^/api/v3/order\\?symbol=BTCUSDT&side=BUY&type=MARKET&quoteOrderQty=[0-9.]+&"
+                        .to_string(),
+                ),
+            )
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 5, "clientOrderId": "c5", "transactTime": 0,
+                    "price": "0", "origQty": "1", "executedQty": "1",
+                    "cummulativeQuoteQty": "25.5", "status": "FILLED",
+                    "timeInForce": "GTC", "type": "MARKET", "side": "BUY"}"#,
+            )
+            .expect(4)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false).with_twap(
+            TwapExecutor::new(4, std::time::Duration::from_millis(4)),
+            dec!(100),
+        );
+        engine.run_once().await.unwrap();
+
+        children.assert_async().await;
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.quantity, dec!(4));
+        assert_eq!(position.entry_price, dec!(25.5));
+    }
+
+    #[test]
+    fn test_twap_only_applies_to_large_market_orders() {
+        let engine = create_engine("http://127.0.0.1:1", false).with_twap(
+            TwapExecutor::new(4, std::time::Duration::from_secs(60)),
+            dec!(1000),
+        );
+
+        let large = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(1000));
+        let small = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.01));
+        let limit = OrderRequest::limit("BTCUSDT", OrderSide::Buy, dec!(1), dec!(30000));
+
+        assert!(engine.twap_for(&large, dec!(30000)).is_some());
+        assert!(engine.twap_for(&small, dec!(30000)).is_none());
+        assert!(engine.twap_for(&limit, dec!(30000)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_market_buy_aborted_when_price_slips() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        // 25.00 when the signal is generated, 25.50 (+2%) by the time the
+        // order would be placed
+        let ticker_calls = std::sync::atomic::AtomicUsize::new(0);
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body_from_request(move |_| {
+                let price = match ticker_calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => "25.00",
+                    _ => "25.50",
+                };
+                format!(r#"{{"symbol": "BTCUSDT", "price": "{}"}}"#, price).into()
+            })
+            .expect(2)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        let order = server
+            .mock("POST", Matcher::Regex("^/api/v3/order".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false).with_slippage_protection(dec!(1));
+        engine.run_once().await.unwrap();
+
+        order.assert_async().await;
+        assert_eq!(engine.risk_manager.open_positions_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_market_buy_slippage_checked_against_best_ask() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        // Last trade was 25.00 but the book has thinned: buying pays 25.60
+        let _depth = server
+            .mock("GET", Matcher::Regex("^/api/v3/depth".to_string()))
+            .with_body(
+                r#"{"lastUpdateId": 1, "bids": [["24.90", "10"]], "asks": [["25.60", "10"]]}"#,
+            )
+            .create_async()
+            .await;
+        let order = server
+            .mock("POST", Matcher::Regex("^/api/v3/order".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), false).with_slippage_protection(dec!(1));
+        engine.client = Box::new(
+            BinanceClient::new(
+                ExchangeCredentials {
+                    api_key: "key".to_string(),
+                    secret_key: "secret".to_string(),
+                    environment: Environment::Testnet,
+                },
+                &ClientConfig::default(),
+            )
+            .unwrap()
+            .with_base_url(&server.url())
+            .with_order_book_pricing(true),
+        );
+        engine.run_once().await.unwrap();
+
+        order.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_suppresses_repeat_signals() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        // Golden cross on the last candle, so every cycle signals a buy
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        let orders = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = orders.clone();
+        let _test_order = server
+            .mock(
+                "POST",
+                Matcher::Regex("^/api/v3/order/test\\?symbol=BTCUSDT&side=BUY&".to_string()),
+            )
+            .with_body_from_request(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                "{}".into()
+            })
+            .create_async()
+            .await;
+
+        let clock = Arc::new(MockClock::new("2024-01-01T12:00:00Z".parse().unwrap()));
+        let mut engine = create_engine(&server.url(), true)
+            .with_paper_order_validation(true)
+            .with_cooldown(std::time::Duration::from_secs(15 * 60))
+            .with_clock(clock.clone());
+
+        engine.run_once().await.unwrap();
+        assert_eq!(orders.load(Ordering::SeqCst), 1);
+
+        // Same signal inside the window
+        clock.advance(std::time::Duration::from_secs(14 * 60));
+        engine.run_once().await.unwrap();
+        assert_eq!(orders.load(Ordering::SeqCst), 1);
+
+        // Past it, the signal repeats on a new candle
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        clock.advance(std::time::Duration::from_secs(60));
+        engine.run_once().await.unwrap();
+        assert_eq!(orders.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_paper_buy_reads_and_debits_wallet() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .expect(0)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+
+        let wallet = PaperWallet::new(HashMap::from([("USDT".to_string(), dec!(1000))]), dec!(0));
+        let mut engine = create_engine(&server.url(), true).with_paper_wallet(wallet);
+
+        engine.run_once().await.unwrap();
+
+        // Full-strength signal: 2% of the wallet at 25
+        let wallet = engine.paper_wallet.as_ref().unwrap();
+        assert_eq!(wallet.balance("BTC"), dec!(0.8));
+        assert_eq!(wallet.balance("USDT"), dec!(980));
+        account.assert_async().await;
+    }
+
+    /// Live engine holding 1 BTC bought at 100, with a 2% stop and 4% target.
+    fn engine_with_position(server_url: &str) -> TradingEngine {
+        let mut engine = create_engine(server_url, false);
+        engine.risk_manager = Arc::new(RiskManager::new(dec!(2), dec!(5), 3).with_exit_levels(
+            dec!(2),
+            dec!(4),
+            dec!(0.1),
+        ));
+        engine.risk_manager.open_position(Position::new(
+            "BTCUSDT",
+            dec!(100),
+            dec!(1),
+            OrderSide::Buy,
+            0,
+        ));
+        engine
+    }
+
+    fn market_data_at(price: Decimal) -> MarketData {
+        MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: price,
+            klines: Vec::new(),
+            timestamp: 0,
+            mid_price: None,
+        }
+    }
+
+    #[test]
+    fn test_stale_data_checks_timestamp_and_newest_kline() {
+        let now = 1_700_000_000_000;
+        let kline_closing_at = |close_time: u64| crate::exchange::Kline {
+            open_time: close_time - 3_600_000,
+            open: "100".to_string(),
+            high: "100".to_string(),
+            low: "100".to_string(),
+            close: "100".to_string(),
+            volume: "1".to_string(),
+            close_time,
+            quote_asset_volume: "100".to_string(),
+            number_of_trades: 1,
+            taker_buy_base_asset_volume: "0".to_string(),
+            taker_buy_quote_asset_volume: "0".to_string(),
+        };
+        let engine = create_engine("http://127.0.0.1:1", true)
+            .with_max_data_age(std::time::Duration::from_secs(60));
+
+        // Fetched just now, newest candle still open
+        let mut data = market_data_at(dec!(100));
+        data.timestamp = now - 1_000;
+        data.klines = vec![kline_closing_at(now + 1_800_000)];
+        assert_eq!(engine.stale_data(&data, now), None);
+
+        // Fetched two minutes ago
+        data.timestamp = now - 120_000;
+        assert_eq!(
+            engine.stale_data(&data, now),
+            Some("market data is 120000ms old".to_string())
+        );
+
+        // Fresh fetch, but the feed stopped producing candles an hour ago
+        data.timestamp = now;
+        data.klines = vec![kline_closing_at(now - 3_600_000)];
+        assert_eq!(
+            engine.stale_data(&data, now),
+            Some("newest kline closed 3600000ms ago".to_string())
+        );
+
+        // No limit configured
+        let engine = create_engine("http://127.0.0.1:1", true);
+        assert_eq!(engine.stale_data(&data, now), None);
+    }
+
+    #[tokio::test]
+    async fn test_stale_klines_suppress_signals() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        // Golden cross, but on candles from 1970
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        let test_order = server
+            .mock("POST", Matcher::Regex("^/api/v3/order/test".to_string()))
+            .with_body("{}")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), true)
+            .with_paper_order_validation(true)
+            .with_max_data_age(std::time::Duration::from_secs(300));
+        engine.run_once().await.unwrap();
+
+        test_order.assert_async().await;
+    }
+
+    #[test]
+    fn test_check_exits_fires_only_past_stop_or_target() {
+        let engine = engine_with_position("http://127.0.0.1:1");
+
+        assert!(matches!(
+            engine.check_exits(&market_data_at(dec!(97.5))),
+            Some(Signal::Sell { .. })
+        ));
+        assert!(matches!(
+            engine.check_exits(&market_data_at(dec!(104))),
+            Some(Signal::Sell { .. })
+        ));
+        assert!(engine.check_exits(&market_data_at(dec!(101))).is_none());
+
+        engine.risk_manager.close_position("BTCUSDT");
+        assert!(engine.check_exits(&market_data_at(dec!(90))).is_none());
+    }
+
+    /// Runs one live cycle at `price` with a flat market, so only an exit
+    /// can trigger a sell.
+    async fn run_exit_cycle(price: &str) -> TradingEngine {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(
+                r#"{
+                    "makerCommission": 10, "takerCommission": 10,
+                    "buyerCommission": 0, "sellerCommission": 0,
+                    "canTrade": true, "canWithdraw": false, "canDeposit": false,
+                    "updateTime": 0, "accountType": "SPOT",
+                    "balances": [{"asset": "USDT", "free": "1000", "locked": "0"},
+                                 {"asset": "BTC", "free": "1", "locked": "0"}]
+                }"#,
+            )
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(format!(r#"{{"symbol": "BTCUSDT", "price": "{}"}}"#, price))
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&[price; 6]))
+            .create_async()
+            .await;
+        let sell = server
+            .mock(
+                "POST",
+                Matcher::Regex(
+                    "^/api/v3/order\\?symbol=BTCUSDT&side=SELL&type=MARKET&quantity=1&".to_string(),
+                ),
+            )
+            .with_body(format!(
+                r#"{{"symbol": "BTCUSDT", "orderId": 3, "clientOrderId": "c3", "transactTime": 0,
+                    "price": "0", "origQty": "1", "executedQty": "1",
+                    "cummulativeQuoteQty": "{}", "status": "FILLED",
+                    "timeInForce": "GTC", "type": "MARKET", "side": "SELL"}}"#,
+                price
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut engine = engine_with_position(&server.url());
+        engine.run_once().await.unwrap();
+
+        sell.assert_async().await;
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_stop_loss_hit_sells_and_records_loss() {
+        let engine = run_exit_cycle("97").await;
+
+        assert!(engine.risk_manager.position("BTCUSDT").is_none());
+        assert_eq!(engine.risk_manager.current_daily_loss(), dec!(3));
+    }
+
+    #[tokio::test]
+    async fn test_take_profit_hit_sells_position() {
+        let engine = run_exit_cycle("105").await;
+
+        assert!(engine.risk_manager.position("BTCUSDT").is_none());
+        assert_eq!(engine.risk_manager.current_daily_loss(), dec!(0));
+    }
+
+    #[tokio::test]
+    async fn test_paper_orders_sent_to_test_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let _exchange_info = mock_exchange_info(&mut server).await;
+        let _account = server
+            .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+            .with_body(ACCOUNT_JSON)
+            .create_async()
+            .await;
+        let _ticker = server
+            .mock("GET", "/api/v3/ticker/price?symbol=BTCUSDT")
+            .with_body(r#"{"symbol": "BTCUSDT", "price": "25.00"}"#)
+            .create_async()
+            .await;
+        let _klines = server
+            .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+            .with_body(klines_json(&["20", "20", "10", "10", "15", "25"]))
+            .create_async()
+            .await;
+        let test_order = server
+            .mock(
+                "POST",
+                Matcher::Regex("^/api/v3/order/test\\?symbol=BTCUSDT&side=BUY&".to_string()),
+            )
+            .with_status(400)
+            .with_body(r#"{"code":-1013,"msg":"Filter failure: NOTIONAL"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let real_order = server
+            .mock("POST", Matcher::Regex("^/api/v3/order\\?".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut engine = create_engine(&server.url(), true).with_paper_order_validation(true);
+        engine.run_once().await.unwrap();
+
+        test_order.assert_async().await;
+        real_order.assert_async().await;
+    }
+
+    #[test]
+    fn test_round_quantity() {
+        // This is a simple test to verify the rounding logic
+        let quantity = dec!(0.123456789);
+
+        // BTC precision = 5
+        assert_eq!(quantity.round_dp(5), dec!(0.12346));
+
+        // ETH precision = 4
+        assert_eq!(quantity.round_dp(4), dec!(0.1235));
 
         // Other precision = 3
         assert_eq!(quantity.round_dp(3), dec!(0.123));
     }
+
+    fn mock_engine(exchange: &MockExchange) -> TradingEngine {
+        TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(2), dec!(5), 3),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCUSDT".to_string()],
+            false,
+        )
+        .with_fill_polling(std::time::Duration::from_millis(1), 3)
+    }
+
+    fn closes(prices: &[u32]) -> Vec<Decimal> {
+        prices.iter().map(|&p| Decimal::from(p)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_buys_in_one_cycle_respect_the_position_limit_and_each_other() {
+        let symbols = ["BTCUSDT", "ETHUSDT", "BNBUSDT"];
+        let mut exchange = MockExchange::new().with_balance("USDT", dec!(10000));
+        for symbol in symbols {
+            exchange = exchange.with_symbol(symbol, &symbol[..3], "USDT");
+            exchange.set_closes(symbol, &closes(&[20, 20, 10, 10, 15, 25]));
+        }
+        let mut engine = mock_engine(&exchange).with_max_concurrent_symbols(4);
+        engine.symbols = symbols.iter().map(|s| s.to_string()).collect();
+        engine.risk_manager = Arc::new(RiskManager::new(dec!(2), dec!(5), 2));
+
+        engine.run_once().await.unwrap();
+
+        // Three buy signals, room for two positions, each sized off what
+        // the one before it left
+        let mut spent: Vec<Decimal> = exchange
+            .orders()
+            .iter()
+            .map(|order| order.quote_order_qty.unwrap())
+            .collect();
+        spent.sort();
+        assert_eq!(spent, vec![dec!(196), dec!(200)]);
+        assert_eq!(engine.risk_manager.open_positions_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_against_mock_exchange_buys_on_golden_cross() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange);
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Buy);
+
+        let positions = engine.risk_manager.positions();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].entry_price, dec!(25));
+        assert_eq!(positions[0].quantity, exchange.balance("BTC"));
+        assert!(exchange.balance("USDT") < dec!(10000));
+    }
+
+    #[tokio::test]
+    async fn test_run_once_against_mock_exchange_closes_position_on_death_cross() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange);
+        engine.run_once().await.unwrap();
+        assert_eq!(engine.risk_manager.open_positions_count(), 1);
+
+        exchange.set_closes("BTCUSDT", &closes(&[10, 10, 20, 20, 15, 5]));
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[1].side, OrderSide::Sell);
+        assert_eq!(engine.risk_manager.open_positions_count(), 0);
+        assert_eq!(exchange.balance("BTC"), dec!(0));
+    }
+
+    /// 10 ETH held and tracked, 6 of them locked in an open order, on a
+    /// death cross.
+    fn engine_with_locked_eth(exchange: &MockExchange) -> TradingEngine {
+        exchange.set_closes("ETHUSDT", &closes(&[10, 10, 20, 20, 15, 5]));
+        let mut engine = mock_engine(exchange);
+        engine.symbols = vec!["ETHUSDT".to_string()];
+        engine.risk_manager.open_position(Position::new(
+            "ETHUSDT",
+            dec!(10),
+            dec!(10),
+            OrderSide::Buy,
+            0,
+        ));
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_sell_is_capped_at_the_free_balance_when_funds_are_locked() {
+        let exchange = MockExchange::new()
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("USDT", dec!(1000))
+            .with_balance("ETH", dec!(4))
+            .with_locked("ETHUSDT", dec!(6));
+        let mut engine = engine_with_locked_eth(&exchange);
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Sell);
+        assert_eq!(orders[0].quantity, dec!(4));
+        assert_eq!(exchange.balance("ETH"), dec!(0));
+    }
+
+    #[tokio::test]
+    async fn test_sell_cancels_open_orders_to_free_locked_funds() {
+        let exchange = MockExchange::new()
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("USDT", dec!(1000))
+            .with_balance("ETH", dec!(4))
+            .with_locked("ETHUSDT", dec!(6));
+        let mut engine = engine_with_locked_eth(&exchange).with_cancel_orders_to_sell(true);
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].quantity, dec!(10));
+        assert_eq!(exchange.balance("ETH"), dec!(0));
+        assert_eq!(engine.risk_manager.open_positions_count(), 0);
+    }
+
+    #[derive(Clone, Default)]
+    struct RecordingNotifier(Arc<std::sync::Mutex<Vec<Notification>>>);
+
+    #[async_trait::async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, notification: &Notification) -> Result<()> {
+            self.0.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    /// Answers every order the same way and remembers what it was asked.
+    #[derive(Clone, Default)]
+    struct FixedApprover {
+        approve: bool,
+        asked: Arc<std::sync::Mutex<Vec<OrderRequest>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl OrderApprover for FixedApprover {
+        async fn approve(&self, order: &OrderRequest) -> bool {
+            self.asked.lock().unwrap().push(order.clone());
+            self.approve
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_plans_the_orders_run_once_places() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_symbol("SOLUSDT", "SOL", "USDT")
+            .with_balance("USDT", dec!(10000))
+            .with_balance("ETH", dec!(10));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        exchange.set_closes("ETHUSDT", &closes(&[10, 10, 20, 20, 15, 5]));
+        exchange.set_closes("SOLUSDT", &closes(&[20, 20, 20, 20, 20, 20]));
+        let mut engine = mock_engine(&exchange);
+        engine.symbols = vec![
+            "BTCUSDT".to_string(),
+            "ETHUSDT".to_string(),
+            "SOLUSDT".to_string(),
+        ];
+
+        let plan = engine.dry_run_cycle().await.unwrap();
+        assert!(exchange.orders().is_empty());
+        assert_eq!(exchange.balance("USDT"), dec!(10000));
+
+        let symbols: Vec<_> = plan.iter().map(|a| a.symbol.as_str()).collect();
+        assert_eq!(symbols, ["BTCUSDT", "ETHUSDT", "SOLUSDT"]);
+        assert!(matches!(plan[2].decision, Decision::Hold));
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 2);
+        for action in &plan[..2] {
+            let Decision::Place { order: planned, .. } = &action.decision else {
+                panic!("{} planned no order: {:?}", action.symbol, action.decision);
+            };
+            let placed = orders.iter().find(|o| o.symbol == action.symbol).unwrap();
+            assert_eq!(placed.side, planned.side);
+            assert_eq!(placed.quantity, planned.quantity);
+            assert_eq!(placed.quote_order_qty, planned.quote_order_qty);
+            assert_eq!(placed.client_order_id, planned.client_order_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_risk_rejections() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(100));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let engine = TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(2), dec!(5), 3).with_min_order_notional(dec!(10)),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCUSDT".to_string()],
+            false,
+        );
+
+        let plan = engine.dry_run_cycle().await.unwrap();
+
+        // 2% of 100 USDT is below the 10 USDT minimum
+        assert!(matches!(
+            &plan[0].decision,
+            Decision::Rejected {
+                error: RiskError::BelowMinNotional { .. },
+                ..
+            }
+        ));
+        assert!(plan[0].to_string().contains("below the minimum notional"));
+        assert!(exchange.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_rebuilds_positions_from_order_history() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("USDT", dec!(10000));
+        let trade = |symbol: &str, side, quantity, price| {
+            exchange.set_closes(symbol, &closes(&[price]));
+            let order = OrderRequest::market(symbol, side, quantity);
+            let exchange = exchange.clone();
+            async move { exchange.place_order(&order).await.unwrap() }
+        };
+        // Before the restart: 2 BTC bought at 100 and 200, one sold again;
+        // ETH bought and sold in full
+        trade("BTCUSDT", OrderSide::Buy, dec!(1), 100).await;
+        trade("BTCUSDT", OrderSide::Buy, dec!(1), 200).await;
+        trade("BTCUSDT", OrderSide::Sell, dec!(1), 150).await;
+        trade("ETHUSDT", OrderSide::Buy, dec!(2), 10).await;
+        trade("ETHUSDT", OrderSide::Sell, dec!(2), 12).await;
+
+        let mut engine = mock_engine(&exchange);
+        engine.symbols = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+        engine.reconcile_positions().await.unwrap();
+
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.quantity, dec!(1));
+        assert_eq!(position.entry_price, dec!(150));
+        assert!(engine.risk_manager.position("ETHUSDT").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_symbols_below_the_volume_minimum_are_skipped() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("USDT", dec!(10000));
+        // Each mock candle trades 10000 USDT: 60000 on BTC, 120000 on ETH
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        exchange.set_closes(
+            "ETHUSDT",
+            &closes(&[20, 20, 20, 20, 20, 20, 20, 20, 10, 10, 15, 25]),
+        );
+        let mut engine = mock_engine(&exchange).with_min_24h_quote_volume(dec!(100000));
+        engine.symbols = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].symbol, "ETHUSDT");
+    }
+
+    #[tokio::test]
+    async fn test_sizing_is_exact_for_a_given_strength() {
+        let exchange = MockExchange::new().with_symbol("BTCUSDT", "BTC", "USDT");
+        let engine = mock_engine(&exchange);
+        let market_data = MarketData {
+            symbol: "BTCUSDT".to_string(),
+            current_price: dec!(100),
+            klines: Vec::new(),
+            timestamp: 0,
+            mid_price: None,
+        };
+        let balances = [("USDT", "1000"), ("BTC", "3")].map(|(asset, free)| Balance {
+            asset: asset.to_string(),
+            free: free.to_string(),
+            locked: "0".to_string(),
+        });
+
+        // 1% plus a strength of 0.1 + 0.2, which as f64 is 0.30000000000000004
+        let strength = dec!(0.1) + dec!(0.2);
+        let buy = engine
+            .plan_buy("BTCUSDT", &market_data, &balances, strength)
+            .await
+            .unwrap();
+        assert!(
+            matches!(&buy, Decision::Place { order, .. } if order.quote_order_qty == Some(dec!(13))),
+            "{:?}",
+            buy
+        );
+
+        let sell = engine
+            .plan_sell("BTCUSDT", &market_data, &balances, dec!(0.1), false)
+            .await
+            .unwrap();
+        assert!(
+            matches!(&sell, Decision::Place { order, .. } if order.quantity == dec!(0.3)),
+            "{:?}",
+            sell
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partial_sell_keeps_the_rest_of_the_position_tracked() {
+        let exchange = MockExchange::new()
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("ETH", dec!(10));
+        exchange.set_closes("ETHUSDT", &closes(&[100, 100, 100, 100, 100, 100]));
+        let mut engine = mock_engine(&exchange);
+        engine.symbols = vec!["ETHUSDT".to_string()];
+        engine.risk_manager.open_position(Position::new(
+            "ETHUSDT",
+            dec!(100),
+            dec!(10),
+            OrderSide::Buy,
+            0,
+        ));
+        let market_data = MarketData {
+            symbol: "ETHUSDT".to_string(),
+            current_price: dec!(100),
+            klines: Vec::new(),
+            timestamp: 0,
+            mid_price: None,
+        };
+        let balances = [("USDT", "0"), ("ETH", "10")].map(|(asset, free)| Balance {
+            asset: asset.to_string(),
+            free: free.to_string(),
+            locked: "0".to_string(),
+        });
+
+        engine
+            .execute_sell("ETHUSDT", &market_data, &balances, dec!(0.4), false)
+            .await
+            .unwrap();
+
+        assert_eq!(exchange.orders()[0].quantity, dec!(4));
+        let position = engine.risk_manager.position("ETHUSDT").unwrap();
+        assert_eq!(position.quantity, dec!(6));
+    }
+
+    #[tokio::test]
+    async fn test_paper_buy_is_tracked_and_stopped_out() {
+        let exchange = MockExchange::new().with_symbol("BTCUSDT", "BTC", "USDT");
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let wallet = PaperWallet::new(HashMap::from([("USDT".to_string(), dec!(1000))]), dec!(0));
+        let mut engine = mock_engine(&exchange).with_paper_wallet(wallet);
+        engine.paper_trading = true;
+        engine.risk_manager = Arc::new(RiskManager::new(dec!(2), dec!(5), 3).with_exit_levels(
+            dec!(2),
+            dec!(4),
+            dec!(0.1),
+        ));
+
+        engine.run_once().await.unwrap();
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.quantity, dec!(0.8));
+        assert_eq!(position.entry_price, dec!(25));
+
+        // 4% under the entry, past the 2% stop
+        exchange.set_closes("BTCUSDT", &closes(&[25, 25, 25, 25, 25, 24]));
+        engine.run_once().await.unwrap();
+
+        assert!(engine.risk_manager.position("BTCUSDT").is_none());
+        assert_eq!(engine.risk_manager.current_daily_loss(), dec!(4));
+        let wallet = engine.paper_wallet.as_ref().unwrap();
+        assert_eq!(wallet.balance("BTC"), dec!(0));
+        assert_eq!(wallet.balance("USDT"), dec!(999.2));
+        assert!(exchange.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_outside_trading_hours_only_exits_run() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("USDT", dec!(10000))
+            .with_balance("ETH", dec!(1));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        // 3% under the ETH entry, past its 2% stop
+        exchange.set_closes("ETHUSDT", &closes(&[100, 100, 100, 100, 100, 97]));
+
+        let clock = Arc::new(MockClock::new("2024-01-01T03:00:00Z".parse().unwrap()));
+        let window = TradingWindow::new("08:00".parse().unwrap(), "20:00".parse().unwrap());
+        let mut engine = mock_engine(&exchange)
+            .with_clock(clock.clone())
+            .with_trading_hours(vec![window]);
+        engine.symbols = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+        engine.risk_manager = Arc::new(RiskManager::new(dec!(2), dec!(5), 3).with_exit_levels(
+            dec!(2),
+            dec!(4),
+            dec!(0.1),
+        ));
+        engine.risk_manager.open_position(Position::new(
+            "ETHUSDT",
+            dec!(100),
+            dec!(1),
+            OrderSide::Buy,
+            0,
+        ));
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].symbol, "ETHUSDT");
+        assert_eq!(orders[0].side, OrderSide::Sell);
+
+        // In session the golden cross on BTC is traded
+        clock.set("2024-01-01T12:00:00Z".parse().unwrap());
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[1].symbol, "BTCUSDT");
+        assert_eq!(orders[1].side, OrderSide::Buy);
+    }
+
+    #[tokio::test]
+    async fn test_account_is_reused_within_the_cache_ttl() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[10, 10, 10, 10, 10, 10]));
+
+        let clock = Arc::new(MockClock::new("2024-01-01T03:00:00Z".parse().unwrap()));
+        let mut engine = mock_engine(&exchange)
+            .with_clock(clock.clone())
+            .with_account_cache_ttl(std::time::Duration::from_secs(60));
+
+        engine.run_once().await.unwrap();
+        engine.run_once().await.unwrap();
+        assert_eq!(exchange.account_fetches(), 1);
+
+        clock.set("2024-01-01T03:01:00Z".parse().unwrap());
+        engine.run_once().await.unwrap();
+        assert_eq!(exchange.account_fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_placing_an_order_invalidates_the_cached_account() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+
+        let clock = Arc::new(MockClock::new("2024-01-01T03:00:00Z".parse().unwrap()));
+        let mut engine = mock_engine(&exchange)
+            .with_clock(clock)
+            .with_account_cache_ttl(std::time::Duration::from_secs(60));
+
+        // Still within the TTL, but the buy changed the balances, so the
+        // next symbol's signal is acted on against fresh ones
+        engine.run_once().await.unwrap();
+        assert_eq!(exchange.orders().len(), 1);
+        assert_eq!(exchange.account_fetches(), 2);
+
+        exchange.set_closes("BTCUSDT", &closes(&[25, 25, 25, 25, 25, 25]));
+        engine.run_once().await.unwrap();
+        assert_eq!(exchange.account_fetches(), 2);
+    }
+
+    fn smoothed_engine(exchange: &MockExchange) -> TradingEngine {
+        mock_engine(exchange).with_signal_smoothing(SignalSmoothing {
+            alpha: dec!(0.5),
+            threshold: dec!(0.6),
+            cycles: 2,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_smoothing_ignores_a_one_off_signal() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = smoothed_engine(&exchange);
+
+        engine.run_once().await.unwrap();
+        exchange.set_closes("BTCUSDT", &closes(&[25, 25, 25, 25, 25, 25]));
+        for _ in 0..3 {
+            engine.run_once().await.unwrap();
+        }
+
+        assert!(exchange.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_smoothing_trades_on_a_sustained_signal() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = smoothed_engine(&exchange);
+
+        // Smoothed to 0.5, then 0.75 for the first of the two cycles needed
+        engine.run_once().await.unwrap();
+        engine.run_once().await.unwrap();
+        assert!(exchange.orders().is_empty());
+
+        // A dry run sees the next cycle trading without counting as one
+        let plan = engine.dry_run_cycle().await.unwrap();
+        assert!(matches!(plan[0].decision, Decision::Place { .. }));
+
+        engine.run_once().await.unwrap();
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Buy);
+    }
+
+    #[tokio::test]
+    async fn test_stop_loss_exits_at_the_position_cap_and_past_the_daily_loss_limit() {
+        let exchange = MockExchange::new()
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("USDT", dec!(10000))
+            .with_balance("ETH", dec!(1));
+        // 3% under the entry, past the 2% stop
+        exchange.set_closes("ETHUSDT", &closes(&[100, 100, 100, 100, 100, 97]));
+
+        let mut engine = mock_engine(&exchange);
+        engine.symbols = vec!["ETHUSDT".to_string()];
+        engine.risk_manager = Arc::new(RiskManager::new(dec!(2), dec!(5), 1).with_exit_levels(
+            dec!(2),
+            dec!(4),
+            dec!(0.1),
+        ));
+        engine.risk_manager.open_position(Position::new(
+            "ETHUSDT",
+            dec!(100),
+            dec!(1),
+            OrderSide::Buy,
+            0,
+        ));
+        engine.risk_manager.record_trade_result(dec!(-6));
+        assert!(!engine.risk_manager.can_trade());
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Sell);
+        assert_eq!(orders[0].quantity, dec!(1));
+        assert!(engine.risk_manager.position("ETHUSDT").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_approved_order_is_placed() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let approver = FixedApprover {
+            approve: true,
+            ..Default::default()
+        };
+        let mut engine = mock_engine(&exchange).with_order_approver(Box::new(approver.clone()));
+
+        engine.run_once().await.unwrap();
+
+        let asked = approver.asked.lock().unwrap().clone();
+        assert_eq!(asked.len(), 1);
+        assert_eq!(asked[0].side, OrderSide::Buy);
+        assert_eq!(exchange.orders().len(), 1);
+        assert!(engine.risk_manager.position("BTCUSDT").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_declined_order_is_not_placed() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let approver = FixedApprover::default();
+        let mut engine = mock_engine(&exchange).with_order_approver(Box::new(approver.clone()));
+
+        engine.run_once().await.unwrap();
+
+        assert_eq!(approver.asked.lock().unwrap().len(), 1);
+        assert!(exchange.orders().is_empty());
+        assert!(engine.risk_manager.position("BTCUSDT").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_notifies_fills_and_daily_loss_limit() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let notifier = RecordingNotifier::default();
+        let mut engine = mock_engine(&exchange).with_notifier(Box::new(notifier.clone()));
+
+        engine.run_once().await.unwrap();
+
+        // Reported once, not on every cycle it blocks, and the golden cross
+        // no longer buys
+        engine.risk_manager.record_trade_result(dec!(-6));
+        engine.run_once().await.unwrap();
+        engine.run_once().await.unwrap();
+        assert_eq!(exchange.orders().len(), 1);
+
+        let notifications: Vec<_> = notifier
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| {
+                !matches!(
+                    n,
+                    Notification::Signal {
+                        signal: Signal::Hold,
+                        ..
+                    }
+                )
+            })
+            .cloned()
+            .collect();
+        assert_eq!(notifications.len(), 3);
+        assert!(matches!(
+            &notifications[0],
+            Notification::Signal {
+                signal: Signal::Buy { .. },
+                ..
+            }
+        ));
+        assert!(matches!(
+            &notifications[1],
+            Notification::OrderFilled { side: OrderSide::Buy, price, paper: false, .. }
+                if *price == dec!(25)
+        ));
+        assert_eq!(
+            notifications[2],
+            Notification::DailyLossLimit {
+                loss_pct: dec!(6),
+                limit_pct: dec!(5)
+            }
+        );
+    }
+
+    #[test]
+    fn test_client_order_id_is_stable_per_trade_intent() {
+        let id = client_order_id("BTCUSDT", OrderSide::Buy, 3600000);
+
+        assert_eq!(id, client_order_id("BTCUSDT", OrderSide::Buy, 3600000));
+        assert_ne!(id, client_order_id("BTCUSDT", OrderSide::Sell, 3600000));
+        assert_ne!(id, client_order_id("BTCUSDT", OrderSide::Buy, 7200000));
+        assert_ne!(id, client_order_id("ETHUSDT", OrderSide::Buy, 3600000));
+
+        // UUID shaped, within Binance's 36 character limit
+        assert_eq!(id.len(), 36);
+        assert_eq!(id.matches('-').count(), 4);
+        assert_eq!(&id[14..15], "8");
+    }
+
+    #[tokio::test]
+    async fn test_repeated_signal_on_same_candle_is_not_ordered_twice() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange);
+
+        engine.run_once().await.unwrap();
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(
+            orders[0].client_order_id,
+            Some(client_order_id("BTCUSDT", OrderSide::Buy, 5 * 3600000))
+        );
+        assert_eq!(
+            engine.risk_manager.positions()[0].quantity,
+            exchange.balance("BTC")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_position_scaling_adds_and_peels_legs() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        let mut engine = TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(10), dec!(5), 3).with_position_scaling(PositionScaling {
+                max_position_legs: 3,
+                leg_size_pct: dec!(5),
+            }),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCUSDT".to_string()],
+            false,
+        );
+
+        // Each padded history ends in a golden cross on a newer candle
+        for padding in 0..4 {
+            let mut history = vec![20; padding];
+            history.extend([20, 20, 10, 10, 15, 25]);
+            exchange.set_closes("BTCUSDT", &closes(&history));
+            engine.run_once().await.unwrap();
+        }
+
+        // The fourth signal finds the position at its cap
+        assert_eq!(exchange.orders().len(), 3);
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.legs, vec![dec!(20), dec!(19), dec!(18.05)]);
+        assert_eq!(position.quantity, exchange.balance("BTC"));
+
+        for padding in 4..6 {
+            let mut history = vec![30; padding];
+            history.extend([30, 30, 40, 40, 35, 28]);
+            exchange.set_closes("BTCUSDT", &closes(&history));
+            engine.run_once().await.unwrap();
+        }
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 5);
+        assert_eq!(orders[3].quantity, dec!(18.05));
+        assert_eq!(orders[4].quantity, dec!(19));
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.legs, vec![dec!(20)]);
+        assert_eq!(exchange.balance("BTC"), dec!(20));
+        assert_eq!(engine.risk_manager.performance().num_trades, 2);
+    }
+
+    #[tokio::test]
+    async fn test_position_scaling_adds_legs_at_the_open_position_limit() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        let mut engine = TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(10), dec!(5), 1).with_position_scaling(PositionScaling {
+                max_position_legs: 3,
+                leg_size_pct: dec!(5),
+            }),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCUSDT".to_string()],
+            false,
+        );
+
+        for padding in 0..2 {
+            let mut history = vec![20; padding];
+            history.extend([20, 20, 10, 10, 15, 25]);
+            exchange.set_closes("BTCUSDT", &closes(&history));
+            engine.run_once().await.unwrap();
+        }
+
+        // The one open position is the one the second leg goes to
+        assert_eq!(exchange.orders().len(), 2);
+        let position = engine.risk_manager.position("BTCUSDT").unwrap();
+        assert_eq!(position.legs, vec![dec!(20), dec!(19)]);
+    }
+
+    #[tokio::test]
+    async fn test_buys_are_sized_above_the_quote_reserve() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(1000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange).with_quote_reserve(dec!(900));
+
+        assert_eq!(
+            engine
+                .spendable(&Balance {
+                    asset: "USDT".to_string(),
+                    free: "1000".to_string(),
+                    locked: "0".to_string(),
+                })
+                .free_decimal(),
+            dec!(100)
+        );
+
+        engine.run_once().await.unwrap();
+
+        // 2% of the 100 USDT above the reserve, not of the full 1000
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].quote_order_qty, Some(dec!(2)));
+        assert_eq!(exchange.balance("USDT"), dec!(998));
+
+        // With nothing above the reserve, a fresh golden cross buys nothing
+        exchange.clone().with_balance("USDT", dec!(900));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 20, 10, 10, 15, 25]));
+        engine.run_once().await.unwrap();
+        assert_eq!(exchange.orders().len(), 1);
+        assert_eq!(exchange.balance("USDT"), dec!(900));
+    }
+
+    #[tokio::test]
+    async fn test_buys_are_sized_down_to_the_max_order_notional() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(100000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(2), dec!(5), 3).with_max_order_notional(dec!(500)),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCUSDT".to_string()],
+            false,
+        )
+        .with_fill_polling(std::time::Duration::from_millis(1), 3);
+
+        engine.run_once().await.unwrap();
+
+        // 2% of the balance would be 2000
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].quote_order_qty, Some(dec!(500)));
+    }
+
+    #[tokio::test]
+    async fn test_quote_reserve_applies_to_stable_quotes_only() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCFDUSD", "BTC", "FDUSD")
+            .with_symbol("ETHBTC", "ETH", "BTC")
+            .with_balance("FDUSD", dec!(10000))
+            .with_balance("BTC", dec!(10));
+        exchange.set_closes("BTCFDUSD", &closes(&[20, 20, 10, 10, 15, 25]));
+        exchange.set_closes("ETHBTC", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange).with_quote_reserve(dec!(1000));
+        // ETHBTC first, before the BTCFDUSD buy adds to the BTC balance
+        engine.symbols = vec!["ETHBTC".to_string(), "BTCFDUSD".to_string()];
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 2);
+        let quote_qty = |symbol: &str| {
+            orders
+                .iter()
+                .find(|o| o.symbol == symbol)
+                .and_then(|o| o.quote_order_qty)
+        };
+        // 2% of the 9000 FDUSD above the reserve
+        assert_eq!(quote_qty("BTCFDUSD"), Some(dec!(180)));
+        // A USD reserve means nothing in BTC: 2% of all 10
+        assert_eq!(quote_qty("ETHBTC"), Some(dec!(0.2)));
+    }
+
+    #[tokio::test]
+    async fn test_warns_when_fee_asset_runs_low() {
+        let balance = |asset: &str, free: &str| Balance {
+            asset: asset.to_string(),
+            free: free.to_string(),
+            locked: "0".to_string(),
+        };
+        let engine = mock_engine(&MockExchange::new()).with_fee_asset("BNB", dec!(0.05));
+
+        let low = [balance("USDT", "1000"), balance("BNB", "0.01")];
+        let warning = engine.fee_asset_warning(&low).unwrap();
+        assert!(
+            warning.contains("BNB balance 0.01 is below 0.05"),
+            "{}",
+            warning
+        );
+        // No BNB at all is as low as it gets
+        assert!(engine
+            .fee_asset_warning(&[balance("USDT", "1000")])
+            .is_some());
+
+        let plenty = [balance("USDT", "1000"), balance("BNB", "0.5")];
+        assert_eq!(engine.fee_asset_warning(&plenty), None);
+
+        // Silent without a fee asset, and for paper fills paid in quote
+        assert_eq!(
+            mock_engine(&MockExchange::new()).fee_asset_warning(&low),
+            None
+        );
+        let mut paper = mock_engine(&MockExchange::new()).with_fee_asset("BNB", dec!(0.05));
+        paper.paper_trading = true;
+        assert_eq!(paper.fee_asset_warning(&low), None);
+    }
+
+    #[tokio::test]
+    async fn test_signal_log_keeps_last_cycles_with_indicators() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(1000));
+        exchange.set_closes("BTCUSDT", &closes(&[10, 10, 10, 10, 10, 10]));
+        let mut engine = mock_engine(&exchange).with_signal_log_capacity(2);
+
+        engine.run_once().await.unwrap();
+        engine.run_once().await.unwrap();
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        engine.run_once().await.unwrap();
+
+        let records = engine.signal_log().records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].signal, Signal::Hold);
+        let golden = &records[1];
+        assert_eq!(golden.symbol, "BTCUSDT");
+        assert!(matches!(golden.signal, Signal::Buy { .. }));
+        assert_eq!(golden.indicators["short_sma"], dec!(20));
+        assert_eq!(golden.indicators["long_sma"], dec!(15));
+    }
+
+    #[tokio::test]
+    async fn test_buy_below_one_lot_step_is_skipped() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_lot_size("BTCUSDT", "0.01", "0.01")
+            .with_balance("USDT", dec!(10));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange);
+
+        // 2% of 10 USDT buys 0.008 at 25, under the 0.01 step
+        engine.run_once().await.unwrap();
+        assert!(exchange.orders().is_empty());
+        assert_eq!(engine.round_quantity(dec!(0.008), "BTCUSDT"), dec!(0));
+        assert_eq!(engine.round_quantity(dec!(0.0399), "BTCUSDT"), dec!(0.03));
+
+        // 2% of 100 USDT buys 0.08, enough for eight steps
+        exchange.clone().with_balance("USDT", dec!(100));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 20, 10, 10, 15, 25]));
+        engine.run_once().await.unwrap();
+        assert_eq!(exchange.orders().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_sell_is_clamped_to_held() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("BTC", dec!(1))
+            .with_balance("USDT", dec!(1000));
+        exchange.set_closes("BTCUSDT", &closes(&[10, 10, 20, 20, 15, 14]));
+        let mut engine = mock_engine(&exchange).with_reduce_only(true);
+        engine.risk_manager.open_position(Position::new(
+            "BTCUSDT",
+            dec!(14),
+            dec!(0.1),
+            OrderSide::Buy,
+            0,
+        ));
+
+        // The death cross would sell part of the whole 1 BTC balance
+        engine.run_once().await.unwrap();
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Sell);
+        assert_eq!(orders[0].quantity, dec!(0.1));
+
+        // Without a tracked position the free balance is the cap, rounded
+        // down rather than up past it
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.5)).with_reduce_only();
+        let clamped = engine.clamp_reduce_only(order, dec!(0.123456)).unwrap();
+        assert_eq!(clamped.quantity, dec!(0.12345));
+
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.5));
+        let untouched = engine.clamp_reduce_only(order, dec!(0.1)).unwrap();
+        assert_eq!(untouched.quantity, dec!(0.5));
+
+        let order = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.5)).with_reduce_only();
+        assert!(engine.clamp_reduce_only(order, dec!(0)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sell_that_would_strand_dust_sells_everything() {
+        // A narrow death cross: strength 0.8976 sells that much of the 1 BTC
+        let death = closes(&[1000, 1000, 1010, 1010, 1004, 1000]);
+
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_lot_size("BTCUSDT", "0.001", "0.001")
+            .with_balance("BTC", dec!(1));
+        exchange.set_closes("BTCUSDT", &death);
+        mock_engine(&exchange).run_once().await.unwrap();
+        assert_eq!(exchange.orders()[0].quantity, dec!(0.897));
+
+        // The 0.103 BTC left is under a 0.2 BTC minQty
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_lot_size("BTCUSDT", "0.2", "0.001")
+            .with_balance("BTC", dec!(1));
+        exchange.set_closes("BTCUSDT", &death);
+        mock_engine(&exchange).run_once().await.unwrap();
+        assert_eq!(exchange.orders()[0].quantity, dec!(1));
+        assert_eq!(exchange.balance("BTC"), dec!(0));
+
+        // ...or worth 103 USDT, under a 150 USDT minimum order value
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_lot_size("BTCUSDT", "0.001", "0.001")
+            .with_min_notional("BTCUSDT", "150")
+            .with_balance("BTC", dec!(1));
+        exchange.set_closes("BTCUSDT", &death);
+        mock_engine(&exchange).run_once().await.unwrap();
+        assert_eq!(exchange.orders()[0].quantity, dec!(1));
+    }
+
+    #[tokio::test]
+    async fn test_symbols_trade_on_their_configured_strategies() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        exchange.set_closes("ETHUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange).with_symbol_strategy(
+            "ETHUSDT",
+            Box::new(crate::strategy::DcaStrategy::new(dec!(50), 3)),
+        );
+        engine.symbols = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+
+        engine.run_once().await.unwrap();
+
+        // BTCUSDT buys on the default crossover with 2% of the balance,
+        // ETHUSDT on its DCA schedule for the fixed 50 USDT
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 2);
+        let btc = orders.iter().find(|o| o.symbol == "BTCUSDT").unwrap();
+        let eth = orders.iter().find(|o| o.symbol == "ETHUSDT").unwrap();
+        assert_eq!(btc.quote_order_qty, Some(dec!(200)));
+        assert_eq!(eth.quote_order_qty, Some(dec!(50)));
+
+        let records = engine.signal_log().records();
+        let indicators = |symbol: &str| {
+            records
+                .iter()
+                .find(|r| r.symbol == symbol)
+                .unwrap()
+                .indicators
+                .clone()
+        };
+        assert!(indicators("BTCUSDT").contains_key("short_sma"));
+        assert!(indicators("ETHUSDT").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_halted_symbol_is_skipped() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_symbol("ETHUSDT", "ETH", "USDT")
+            .with_status("ETHUSDT", "HALT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        exchange.set_closes("ETHUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange);
+        engine.symbols = vec!["ETHUSDT".to_string(), "BTCUSDT".to_string()];
+
+        engine.run_once().await.unwrap();
+
+        let orders = exchange.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].symbol, "BTCUSDT");
+        assert!(engine.risk_manager.position("ETHUSDT").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_account_places_no_orders() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000))
+            .with_trading_disabled();
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange);
+
+        let err = engine.run_once().await.unwrap_err();
+        assert!(err.to_string().contains("not permitted"), "{}", err);
+        assert!(exchange.orders().is_empty());
+
+        // Refused at startup rather than failing every cycle
+        let err = engine.run(1000).await.unwrap_err();
+        assert!(err.to_string().contains("not permitted"), "{}", err);
+        assert!(exchange.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_symbol_without_spot_trading_is_skipped() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .without_spot_trading("BTCUSDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        let mut engine = mock_engine(&exchange);
+
+        engine.run_once().await.unwrap();
+
+        assert!(exchange.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drawdown_across_cycles_stops_trading() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 20, 20, 20, 20]));
+        let mut engine = TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(2), dec!(5), 3).with_max_drawdown(dec!(15)),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCUSDT".to_string()],
+            false,
+        );
+
+        // Equity peaks, then bleeds away over cycles that each stay well
+        // inside the daily loss limit
+        engine.run_once().await.unwrap();
+        exchange.clone().with_balance("USDT", dec!(9500));
+        engine.run_once().await.unwrap();
+        assert_eq!(engine.risk_manager.current_drawdown_pct(), dec!(5));
+        assert!(engine.risk_manager.can_trade());
+
+        exchange.clone().with_balance("USDT", dec!(8250));
+        engine.run_once().await.unwrap();
+        assert_eq!(engine.risk_manager.current_drawdown_pct(), dec!(17.5));
+        assert_eq!(engine.risk_manager.current_daily_loss(), dec!(0));
+
+        exchange.set_closes("BTCUSDT", &closes(&[20, 20, 10, 10, 15, 25]));
+        engine.run_once().await.unwrap();
+        assert!(exchange.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drawdown_values_the_account_in_the_equity_quote_asset() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCFDUSD", "BTC", "FDUSD")
+            .with_balance("FDUSD", dec!(10000));
+        exchange.set_closes("BTCFDUSD", &closes(&[20, 20, 20, 20, 20, 20]));
+        let mut engine = TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(2), dec!(5), 3).with_max_drawdown(dec!(15)),
+            Box::new(crate::strategy::MaCrossoverStrategy::new(2, 4, dec!(0))),
+            vec!["BTCFDUSD".to_string()],
+            false,
+        )
+        .with_equity_quote_asset("FDUSD");
+
+        engine.run_once().await.unwrap();
+        exchange.clone().with_balance("FDUSD", dec!(8000));
+        engine.run_once().await.unwrap();
+
+        assert_eq!(engine.risk_manager.current_drawdown_pct(), dec!(20));
+        assert!(!engine.risk_manager.can_trade());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_cycle_gets_one_catch_up_tick_not_a_burst() {
+        let period = tokio::time::Duration::from_millis(100);
+        let mut interval = cycle_interval(period);
+        let start = tokio::time::Instant::now();
+        interval.tick().await;
+
+        // A cycle that runs through three ticks' worth of time
+        tokio::time::sleep(tokio::time::Duration::from_millis(350)).await;
+
+        interval.tick().await;
+        assert_eq!(start.elapsed(), tokio::time::Duration::from_millis(350));
+        // The ticks missed at 100, 200 and 300ms fire once, not back to back
+        interval.tick().await;
+        assert_eq!(start.elapsed(), tokio::time::Duration::from_millis(400));
+    }
+
+    /// Holds on every candle and remembers the close it last saw.
+    #[derive(Clone, Default)]
+    struct RecordingStrategy {
+        closes: Arc<std::sync::Mutex<Vec<Decimal>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Strategy for RecordingStrategy {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn analyze(&self, market_data: &MarketData) -> Signal {
+            let close = market_data.klines.last().unwrap().close_decimal();
+            self.closes.lock().unwrap().push(close);
+            Signal::Hold
+        }
+
+        fn required_history(&self) -> usize {
+            2
+        }
+    }
+
+    fn kline_message(open_time_hours: u64, close: &str, is_closed: bool) -> WsMessage {
+        let open_time = open_time_hours * 3600000;
+        WsMessage::Kline(Box::new(WsKlineUpdate {
+            event_type: "kline".to_string(),
+            event_time: open_time,
+            symbol: "BTCUSDT".to_string(),
+            kline: crate::exchange::WsKline {
+                open_time,
+                close_time: open_time + 3599999,
+                interval: "1h".to_string(),
+                open: close.to_string(),
+                high: close.to_string(),
+                low: close.to_string(),
+                close: close.to_string(),
+                volume: "1".to_string(),
+                number_of_trades: 1,
+                is_closed,
+                quote_asset_volume: close.to_string(),
+                taker_buy_base_asset_volume: "0".to_string(),
+                taker_buy_quote_asset_volume: "0".to_string(),
+            },
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_streaming_runs_the_strategy_once_per_closed_candle() {
+        let exchange = MockExchange::new()
+            .with_symbol("BTCUSDT", "BTC", "USDT")
+            .with_balance("USDT", dec!(10000));
+        exchange.set_closes("BTCUSDT", &closes(&[10, 11, 12]));
+        let strategy = RecordingStrategy::default();
+        let engine = TradingEngine::new(
+            Box::new(exchange.clone()),
+            RiskManager::new(dec!(2), dec!(5), 3),
+            Box::new(strategy.clone()),
+            vec!["BTCUSDT".to_string()],
+            false,
+        );
+        let mut buffers = engine.seed_candle_buffers().await.unwrap();
+
+        // The seeded candle at hour 2 is still forming; it ticks, then
+        // closes, then the next one opens, ticks and closes, and its close
+        // is sent twice
+        for message in [
+            WsMessage::Connected,
+            kline_message(2, "13", false),
+            kline_message(2, "14", true),
+            kline_message(3, "15", false),
+            kline_message(3, "16", false),
+            kline_message(3, "17", true),
+            kline_message(3, "17", true),
+        ] {
+            engine
+                .on_stream_message(&mut buffers, message)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(*strategy.closes.lock().unwrap(), [dec!(14), dec!(17)]);
+        assert_eq!(engine.signal_log().records().len(), 2);
+        assert!(exchange.orders().is_empty());
+    }
 }
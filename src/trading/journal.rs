@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::debug;
+
+use crate::exchange::OrderSide;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeRecord {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub timestamp: u64,
+    pub paper: bool,
+    pub order_id: Option<u64>,
+}
+
+/// Durable record of every executed or simulated order, backed by SQLite.
+pub struct TradeJournal {
+    conn: Mutex<Connection>,
+}
+
+impl TradeJournal {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open(path.as_ref()).with_context(|| {
+            format!(
+                "Failed to open trade journal at {}",
+                path.as_ref().display()
+            )
+        })?;
+        Self::init(conn)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory journal")?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS trades (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                side TEXT NOT NULL,
+                quantity TEXT NOT NULL,
+                price TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                paper INTEGER NOT NULL,
+                order_id INTEGER
+            )",
+            [],
+        )
+        .context("Failed to create trades table")?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn record(&self, trade: &TradeRecord) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO trades (symbol, side, quantity, price, timestamp, paper, order_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                trade.symbol,
+                trade.side.to_string(),
+                trade.quantity.to_string(),
+                trade.price.to_string(),
+                trade.timestamp as i64,
+                trade.paper,
+                trade.order_id.map(|id| id as i64),
+            ],
+        )
+        .context("Failed to insert trade")?;
+
+        debug!(
+            "Journaled {} {} {}",
+            trade.side, trade.quantity, trade.symbol
+        );
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.conn
+            .lock()
+            .unwrap()
+            .cache_flush()
+            .context("Failed to flush trade journal")
+    }
+
+    /// Most recent trades first.
+    pub fn recent_trades(&self, limit: usize) -> Result<Vec<TradeRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT symbol, side, quantity, price, timestamp, paper, order_id
+                 FROM trades ORDER BY id DESC LIMIT ?1",
+            )
+            .context("Failed to prepare recent trades query")?;
+
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                let side: String = row.get(1)?;
+                let quantity: String = row.get(2)?;
+                let price: String = row.get(3)?;
+                let timestamp: i64 = row.get(4)?;
+                let order_id: Option<i64> = row.get(6)?;
+
+                Ok(TradeRecord {
+                    symbol: row.get(0)?,
+                    side: if side == "SELL" {
+                        OrderSide::Sell
+                    } else {
+                        OrderSide::Buy
+                    },
+                    quantity: quantity.parse().unwrap_or_default(),
+                    price: price.parse().unwrap_or_default(),
+                    timestamp: timestamp as u64,
+                    paper: row.get(5)?,
+                    order_id: order_id.map(|id| id as u64),
+                })
+            })
+            .context("Failed to query recent trades")?;
+
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to read trade row")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn create_trade(symbol: &str, side: OrderSide, timestamp: u64) -> TradeRecord {
+        TradeRecord {
+            symbol: symbol.to_string(),
+            side,
+            quantity: dec!(0.0004),
+            price: dec!(50000.12),
+            timestamp,
+            paper: false,
+            order_id: Some(12345),
+        }
+    }
+
+    #[test]
+    fn test_record_and_read_back() {
+        let journal = TradeJournal::in_memory().unwrap();
+        let trade = create_trade("BTCUSDT", OrderSide::Buy, 1);
+
+        journal.record(&trade).unwrap();
+
+        let trades = journal.recent_trades(10).unwrap();
+        assert_eq!(trades, vec![trade]);
+    }
+
+    #[test]
+    fn test_recent_trades_newest_first_and_limited() {
+        let journal = TradeJournal::in_memory().unwrap();
+        journal
+            .record(&create_trade("BTCUSDT", OrderSide::Buy, 1))
+            .unwrap();
+        journal
+            .record(&create_trade("ETHUSDT", OrderSide::Buy, 2))
+            .unwrap();
+
+        let mut paper_sell = create_trade("BTCUSDT", OrderSide::Sell, 3);
+        paper_sell.paper = true;
+        paper_sell.order_id = None;
+        journal.record(&paper_sell).unwrap();
+
+        let trades = journal.recent_trades(2).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0], paper_sell);
+        assert_eq!(trades[1].symbol, "ETHUSDT");
+    }
+}
@@ -1,3 +1,25 @@
+mod approval;
+mod backtest;
+mod candles;
 mod engine;
+#[cfg(feature = "journal")]
+mod journal;
+mod paper;
+mod plan;
+mod session;
+mod signal_log;
+mod smoothing;
+mod twap;
 
-pub use engine::TradingEngine;
+pub use approval::{OrderApprover, StdinApprover};
+pub use backtest::{BacktestReport, Backtester};
+pub use candles::CandleBuffer;
+pub use engine::{TradingEngine, DEFAULT_FILL_POLL_ATTEMPTS, DEFAULT_FILL_POLL_INTERVAL_MS};
+#[cfg(feature = "journal")]
+pub use journal::{TradeJournal, TradeRecord};
+pub use paper::{PaperFill, PaperWallet};
+pub use plan::{Decision, PlannedAction};
+pub use session::TradingWindow;
+pub use signal_log::{SignalLog, SignalRecord, DEFAULT_SIGNAL_LOG_CAPACITY};
+pub use smoothing::{SignalSmoother, SignalSmoothing};
+pub use twap::{TwapExecutor, TwapFill};
@@ -0,0 +1,305 @@
+use anyhow::Result;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::exchange::{Balance, OrderRequest, OrderSide};
+
+/// A simulated fill applied to the wallet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaperFill {
+    pub quantity: Decimal,
+    pub price: Decimal,
+    /// Charged in the quote asset
+    pub fee: Decimal,
+    /// Net of fees on both legs; only set on sells of quantity the wallet bought
+    pub realized_pnl: Option<Decimal>,
+}
+
+/// Local balances for paper trading. Simulated fills move base and quote
+/// assets at the given price, less a percentage fee charged in the quote
+/// asset, so paper runs show what the strategy would have earned. Market
+/// orders can also be filled a percentage worse than the quoted price.
+#[derive(Debug)]
+pub struct PaperWallet {
+    fee_pct: Decimal,
+    slippage_pct: Decimal,
+    state: RwLock<WalletState>,
+}
+
+#[derive(Debug, Default)]
+struct WalletState {
+    balances: HashMap<String, Decimal>,
+    /// (base, quote) -> (quantity held, quote spent on it including fees)
+    cost_basis: HashMap<(String, String), (Decimal, Decimal)>,
+    /// Quote asset -> realized PnL
+    realized_pnl: HashMap<String, Decimal>,
+}
+
+impl PaperWallet {
+    pub fn new(initial_balances: HashMap<String, Decimal>, fee_pct: Decimal) -> Self {
+        // Config keys arrive lowercased
+        let balances = initial_balances
+            .into_iter()
+            .map(|(asset, amount)| (asset.to_uppercase(), amount))
+            .collect();
+
+        Self {
+            fee_pct,
+            slippage_pct: dec!(0),
+            state: RwLock::new(WalletState {
+                balances,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Fills market orders `slippage_pct` percent above the quoted price on
+    /// buys and below it on sells.
+    pub fn with_slippage(mut self, slippage_pct: Decimal) -> Self {
+        self.slippage_pct = slippage_pct;
+        self
+    }
+
+    /// Current balances, all free, in the account API's shape.
+    pub fn balances(&self) -> Vec<Balance> {
+        let state = self.state.read().unwrap();
+        let mut balances: Vec<Balance> = state
+            .balances
+            .iter()
+            .map(|(asset, amount)| Balance {
+                asset: asset.clone(),
+                free: amount.normalize().to_string(),
+                locked: "0".to_string(),
+            })
+            .collect();
+        balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+        balances
+    }
+
+    pub fn balance(&self, asset: &str) -> Decimal {
+        self.state
+            .read()
+            .unwrap()
+            .balances
+            .get(asset)
+            .copied()
+            .unwrap_or(dec!(0))
+    }
+
+    /// Realized PnL so far on sells into `quote_asset`.
+    pub fn realized_pnl(&self, quote_asset: &str) -> Decimal {
+        self.state
+            .read()
+            .unwrap()
+            .realized_pnl
+            .get(quote_asset)
+            .copied()
+            .unwrap_or(dec!(0))
+    }
+
+    /// Simulates `order` filling `quantity`: limit orders at their limit
+    /// price, market orders at `market_price` less the configured slippage.
+    pub fn fill_order(
+        &self,
+        order: &OrderRequest,
+        base: &str,
+        quote: &str,
+        quantity: Decimal,
+        market_price: Decimal,
+    ) -> Result<PaperFill> {
+        let price = order.price.unwrap_or_else(|| {
+            let slippage = market_price * self.slippage_pct / dec!(100);
+            match order.side {
+                OrderSide::Buy => market_price + slippage,
+                OrderSide::Sell => market_price - slippage,
+            }
+        });
+        self.apply_fill(base, quote, order.side, quantity, price)
+    }
+
+    /// Simulates `quantity` of `base` traded for `quote` at `price`. Fails
+    /// without changing anything when the wallet can't cover the trade.
+    pub fn apply_fill(
+        &self,
+        base: &str,
+        quote: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        price: Decimal,
+    ) -> Result<PaperFill> {
+        let notional = quantity * price;
+        let fee = notional * self.fee_pct / dec!(100);
+        let mut state = self.state.write().unwrap();
+        let key = (base.to_string(), quote.to_string());
+
+        let realized_pnl = match side {
+            OrderSide::Buy => {
+                let cost = notional + fee;
+                let available = state.balances.get(quote).copied().unwrap_or(dec!(0));
+                if available < cost {
+                    anyhow::bail!(
+                        "Insufficient paper {} balance: {} needed, {} available",
+                        quote,
+                        cost,
+                        available
+                    );
+                }
+
+                *state.balances.entry(quote.to_string()).or_default() -= cost;
+                *state.balances.entry(base.to_string()).or_default() += quantity;
+
+                let basis = state.cost_basis.entry(key).or_default();
+                basis.0 += quantity;
+                basis.1 += cost;
+                None
+            }
+            OrderSide::Sell => {
+                let available = state.balances.get(base).copied().unwrap_or(dec!(0));
+                if available < quantity {
+                    anyhow::bail!(
+                        "Insufficient paper {} balance: {} needed, {} available",
+                        base,
+                        quantity,
+                        available
+                    );
+                }
+
+                *state.balances.entry(base.to_string()).or_default() -= quantity;
+                *state.balances.entry(quote.to_string()).or_default() += notional - fee;
+
+                // Starting balances have no known cost, so only the part
+                // the wallet bought counts towards PnL
+                let realized = state.cost_basis.get_mut(&key).and_then(|basis| {
+                    let matched = quantity.min(basis.0);
+                    if matched <= dec!(0) {
+                        return None;
+                    }
+
+                    let cost = basis.1 * matched / basis.0;
+                    basis.0 -= matched;
+                    basis.1 -= cost;
+                    Some((price * matched) * (dec!(1) - self.fee_pct / dec!(100)) - cost)
+                });
+
+                if let Some(pnl) = realized {
+                    *state.realized_pnl.entry(quote.to_string()).or_default() += pnl;
+                }
+                realized
+            }
+        };
+
+        Ok(PaperFill {
+            quantity,
+            price,
+            fee,
+            realized_pnl,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet(usdt: Decimal) -> PaperWallet {
+        PaperWallet::new(HashMap::from([("USDT".to_string(), usdt)]), dec!(0.1))
+    }
+
+    #[test]
+    fn test_buy_then_sell_updates_balances_and_pnl() {
+        let wallet = wallet(dec!(10000));
+
+        let buy = wallet
+            .apply_fill("BTC", "USDT", OrderSide::Buy, dec!(0.1), dec!(30000))
+            .unwrap();
+        assert_eq!(buy.fee, dec!(3));
+        assert_eq!(buy.realized_pnl, None);
+        assert_eq!(wallet.balance("USDT"), dec!(6997));
+        assert_eq!(wallet.balance("BTC"), dec!(0.1));
+
+        let sell = wallet
+            .apply_fill("BTC", "USDT", OrderSide::Sell, dec!(0.1), dec!(31000))
+            .unwrap();
+        assert_eq!(sell.fee, dec!(3.1));
+        // 3100 - 3.1 fee back, against 3003 spent
+        assert_eq!(sell.realized_pnl, Some(dec!(93.9)));
+        assert_eq!(wallet.balance("USDT"), dec!(10093.9));
+        assert_eq!(wallet.balance("BTC"), dec!(0));
+        assert_eq!(wallet.realized_pnl("USDT"), dec!(93.9));
+    }
+
+    #[test]
+    fn test_partial_sell_realizes_proportional_cost() {
+        let wallet = PaperWallet::new(HashMap::from([("USDT".to_string(), dec!(1000))]), dec!(0));
+
+        wallet
+            .apply_fill("ETH", "USDT", OrderSide::Buy, dec!(1), dec!(100))
+            .unwrap();
+        wallet
+            .apply_fill("ETH", "USDT", OrderSide::Buy, dec!(1), dec!(200))
+            .unwrap();
+
+        let sell = wallet
+            .apply_fill("ETH", "USDT", OrderSide::Sell, dec!(1), dec!(120))
+            .unwrap();
+        assert_eq!(sell.realized_pnl, Some(dec!(-30)));
+        assert_eq!(wallet.balance("ETH"), dec!(1));
+        assert_eq!(wallet.balance("USDT"), dec!(820));
+    }
+
+    /// Buys 0.1 BTC at a quoted 30000 and sells it at 31000, both at market.
+    fn round_trip(wallet: &PaperWallet) -> Decimal {
+        let buy = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(0.1));
+        let sell = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.1));
+        wallet
+            .fill_order(&buy, "BTC", "USDT", dec!(0.1), dec!(30000))
+            .unwrap();
+        wallet
+            .fill_order(&sell, "BTC", "USDT", dec!(0.1), dec!(31000))
+            .unwrap()
+            .realized_pnl
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fees_and_slippage_come_off_simulated_pnl() {
+        let free = PaperWallet::new(HashMap::from([("USDT".to_string(), dec!(10000))]), dec!(0));
+        assert_eq!(round_trip(&free), dec!(100));
+
+        // Buy fills at 30150 for 3018.015 with fees, sell at 30845 for
+        // 3081.4155 after fees
+        let costly = wallet(dec!(10000)).with_slippage(dec!(0.5));
+        assert_eq!(round_trip(&costly), dec!(63.4005));
+        assert_eq!(costly.balance("USDT"), dec!(10063.4005));
+    }
+
+    #[test]
+    fn test_limit_orders_fill_at_their_limit_despite_slippage() {
+        let wallet = wallet(dec!(10000)).with_slippage(dec!(0.5));
+        let order = OrderRequest::limit("BTCUSDT", OrderSide::Buy, dec!(0.1), dec!(29900));
+
+        let fill = wallet
+            .fill_order(&order, "BTC", "USDT", dec!(0.1), dec!(30000))
+            .unwrap();
+        assert_eq!(fill.price, dec!(29900));
+    }
+
+    #[test]
+    fn test_rejects_fills_the_wallet_cannot_cover() {
+        let wallet = PaperWallet::new(HashMap::from([("usdt".to_string(), dec!(100))]), dec!(0.1));
+
+        assert!(wallet
+            .apply_fill("BTC", "USDT", OrderSide::Buy, dec!(1), dec!(100))
+            .is_err());
+        assert!(wallet
+            .apply_fill("BTC", "USDT", OrderSide::Sell, dec!(1), dec!(100))
+            .is_err());
+
+        let balances = wallet.balances();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].asset, "USDT");
+        assert_eq!(balances[0].free_decimal(), dec!(100));
+    }
+}
@@ -0,0 +1,57 @@
+use rust_decimal::Decimal;
+use std::fmt;
+
+use crate::exchange::OrderRequest;
+use crate::risk::RiskError;
+use crate::strategy::Signal;
+
+use super::approval::describe_order;
+
+/// What a trading cycle would do about one symbol, as worked out by
+/// `TradingEngine::dry_run_cycle`.
+#[derive(Debug)]
+pub struct PlannedAction {
+    pub symbol: String,
+    /// The strategy's signal, or the sell a protective exit forces
+    pub signal: Signal,
+    pub decision: Decision,
+}
+
+/// The outcome of sizing and risk-checking a signal.
+#[derive(Debug)]
+pub enum Decision {
+    Hold,
+    /// Would be placed, subject to the checks made when sending it: price
+    /// slippage, confirmation and the exchange's own filters. `quantity` is
+    /// the base amount expected to fill, also for quote-sized orders
+    Place {
+        order: OrderRequest,
+        quantity: Decimal,
+    },
+    /// Sized, but refused by the risk manager
+    Rejected {
+        order: OrderRequest,
+        error: RiskError,
+    },
+    /// No order could be sized, e.g. nothing to sell or a budget below the
+    /// minimum lot
+    Skipped {
+        reason: String,
+    },
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.symbol)?;
+        match &self.decision {
+            Decision::Hold => write!(f, "hold"),
+            Decision::Place { order, quantity } => {
+                write!(f, "{} (~{})", describe_order(order), quantity.normalize())
+            }
+            Decision::Rejected { order, error } => {
+                write!(f, "{} rejected: {}", describe_order(order), error)
+            }
+            Decision::Skipped { reason } => write!(f, "{:?} skipped: {}", self.signal, reason),
+        }
+    }
+}
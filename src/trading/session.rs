@@ -0,0 +1,58 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::Deserialize;
+
+/// A daily span of UTC time, e.g. 08:00 to 20:00, during which the
+/// strategy may trade. A window ending before it starts runs past midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct TradingWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TradingWindow {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    /// Whether `now` falls in the window, including its start and not its
+    /// end.
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        let time = now.time();
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str) -> TradingWindow {
+        TradingWindow::new(start.parse().unwrap(), end.parse().unwrap())
+    }
+
+    fn at(time: &str) -> DateTime<Utc> {
+        format!("2024-01-01T{}:00Z", time).parse().unwrap()
+    }
+
+    #[test]
+    fn test_window_includes_start_and_excludes_end() {
+        let day = window("08:00", "20:00");
+        assert!(day.contains(at("08:00")));
+        assert!(day.contains(at("19:59")));
+        assert!(!day.contains(at("20:00")));
+        assert!(!day.contains(at("03:00")));
+    }
+
+    #[test]
+    fn test_window_can_run_past_midnight() {
+        let night = window("22:00", "02:00");
+        assert!(night.contains(at("23:30")));
+        assert!(night.contains(at("01:59")));
+        assert!(!night.contains(at("02:00")));
+        assert!(!night.contains(at("12:00")));
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::strategy::Signal;
+
+/// Entries kept by the engine's signal log unless configured otherwise.
+pub const DEFAULT_SIGNAL_LOG_CAPACITY: usize = 100;
+
+/// One strategy verdict, with the indicator values it was based on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignalRecord {
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    pub indicators: BTreeMap<&'static str, Decimal>,
+    pub signal: Signal,
+}
+
+/// The most recent signals across all symbols, oldest first, for working
+/// out why a strategy is or isn't trading. Once full, each new record
+/// pushes out the oldest.
+#[derive(Debug)]
+pub struct SignalLog {
+    capacity: usize,
+    records: Mutex<VecDeque<SignalRecord>>,
+}
+
+impl SignalLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(&self, record: SignalRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut records = self.records.lock().unwrap();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    pub fn records(&self) -> Vec<SignalRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn record(symbol: &str, short_sma: Decimal) -> SignalRecord {
+        SignalRecord {
+            timestamp: "2024-01-01T00:00:00Z".parse().unwrap(),
+            symbol: symbol.to_string(),
+            indicators: BTreeMap::from([("short_sma", short_sma), ("long_sma", dec!(100))]),
+            signal: Signal::Hold,
+        }
+    }
+
+    #[test]
+    fn test_keeps_only_the_last_n_records() {
+        let log = SignalLog::new(3);
+        for (i, symbol) in ["A", "B", "C", "D", "E"].iter().enumerate() {
+            log.record(record(symbol, Decimal::from(i)));
+        }
+
+        let records = log.records();
+        let symbols: Vec<_> = records.iter().map(|r| r.symbol.as_str()).collect();
+        assert_eq!(symbols, ["C", "D", "E"]);
+        assert_eq!(records[0].indicators["short_sma"], dec!(2));
+    }
+
+    #[test]
+    fn test_zero_capacity_keeps_nothing() {
+        let log = SignalLog::new(0);
+        log.record(record("A", dec!(1)));
+        assert!(log.records().is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::strategy::Signal;
+
+/// Exponential smoothing of signal strength across cycles: buys count as
+/// positive strength, sells as negative and holds as zero. A signal is only
+/// acted on once the smoothed value has been at least `threshold` on its
+/// side for `cycles` cycles in a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct SignalSmoothing {
+    /// Weight of the newest signal, between 0 (exclusive) and 1; 1 turns
+    /// smoothing off
+    pub alpha: Decimal,
+    pub threshold: Decimal,
+    #[serde(default = "default_smoothing_cycles")]
+    pub cycles: u32,
+}
+
+fn default_smoothing_cycles() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Smoothed {
+    value: Decimal,
+    /// Consecutive cycles `value` has cleared the threshold, positive on
+    /// the buy side and negative on the sell side
+    streak: i64,
+}
+
+/// Per-symbol state for `SignalSmoothing`.
+#[derive(Debug)]
+pub struct SignalSmoother {
+    settings: SignalSmoothing,
+    state: RwLock<HashMap<String, Smoothed>>,
+}
+
+impl SignalSmoother {
+    pub fn new(settings: SignalSmoothing) -> Self {
+        Self {
+            settings,
+            state: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Folds this cycle's `signal` for `symbol` into its smoothed strength
+    /// and returns the signal to act on: `Hold` until the streak is long
+    /// enough, then a buy or sell at the smoothed strength.
+    pub fn update(&self, symbol: &str, signal: &Signal) -> Signal {
+        let mut state = self.state.write().unwrap();
+        let smoothed = state.entry(symbol.to_string()).or_default();
+        *smoothed = self.next(*smoothed, signal);
+        self.signal_for(*smoothed)
+    }
+
+    /// What `update` would return, leaving the state as it is.
+    pub fn peek(&self, symbol: &str, signal: &Signal) -> Signal {
+        let smoothed = self
+            .state
+            .read()
+            .unwrap()
+            .get(symbol)
+            .copied()
+            .unwrap_or_default();
+        self.signal_for(self.next(smoothed, signal))
+    }
+
+    fn next(&self, previous: Smoothed, signal: &Signal) -> Smoothed {
+        let strength = match signal {
+            Signal::Buy { strength } => *strength,
+            Signal::Sell { strength } => -*strength,
+            Signal::Hold => Decimal::ZERO,
+        };
+        let alpha = self.settings.alpha;
+        let value = alpha * strength + (Decimal::ONE - alpha) * previous.value;
+
+        let streak = if value >= self.settings.threshold && value > Decimal::ZERO {
+            previous.streak.max(0) + 1
+        } else if -value >= self.settings.threshold && value < Decimal::ZERO {
+            previous.streak.min(0) - 1
+        } else {
+            0
+        };
+        Smoothed { value, streak }
+    }
+
+    fn signal_for(&self, smoothed: Smoothed) -> Signal {
+        let cycles = i64::from(self.settings.cycles);
+        let strength = smoothed.value.abs().min(Decimal::ONE);
+        if smoothed.streak >= cycles {
+            Signal::Buy { strength }
+        } else if smoothed.streak <= -cycles {
+            Signal::Sell { strength }
+        } else {
+            Signal::Hold
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn smoother() -> SignalSmoother {
+        SignalSmoother::new(SignalSmoothing {
+            alpha: dec!(0.5),
+            threshold: dec!(0.6),
+            cycles: 2,
+        })
+    }
+
+    #[test]
+    fn test_one_off_spike_is_smoothed_away() {
+        let smoother = smoother();
+        let buy = Signal::Buy { strength: dec!(1) };
+
+        assert_eq!(smoother.update("BTCUSDT", &buy), Signal::Hold);
+        for _ in 0..3 {
+            assert_eq!(smoother.update("BTCUSDT", &Signal::Hold), Signal::Hold);
+        }
+    }
+
+    #[test]
+    fn test_sustained_strength_fires_after_enough_cycles() {
+        let smoother = smoother();
+        let sell = Signal::Sell { strength: dec!(1) };
+
+        // Smoothed to -0.5, -0.75 (first over the threshold), -0.875
+        assert_eq!(smoother.update("ETHUSDT", &sell), Signal::Hold);
+        assert_eq!(smoother.peek("ETHUSDT", &sell), Signal::Hold);
+        assert_eq!(smoother.update("ETHUSDT", &sell), Signal::Hold);
+        assert_eq!(
+            smoother.update("ETHUSDT", &sell),
+            Signal::Sell {
+                strength: dec!(0.875)
+            }
+        );
+
+        // Symbols are smoothed separately
+        assert_eq!(smoother.peek("BTCUSDT", &sell), Signal::Hold);
+    }
+}
@@ -0,0 +1,334 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::exchange::{Balance, Exchange, OrderRequest};
+use crate::risk::RiskManager;
+
+use super::engine::{name_uuid, QUOTE_PRECISION};
+
+/// What a TWAP execution actually filled across its child orders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwapFill {
+    pub children_filled: u32,
+    pub quantity: Decimal,
+    pub quote_qty: Decimal,
+}
+
+/// Time-weighted execution: splits a market order into `slices` equal child
+/// orders spread evenly over `duration`, so a large order is averaged over
+/// time instead of sweeping a thin book at once.
+#[derive(Debug, Clone)]
+pub struct TwapExecutor {
+    slices: u32,
+    duration: Duration,
+}
+
+impl TwapExecutor {
+    pub fn new(slices: u32, duration: Duration) -> Self {
+        assert!(slices > 0, "TWAP needs at least one slice");
+
+        Self { slices, duration }
+    }
+
+    /// Child market orders for `parent`, splitting whichever of quantity or
+    /// quote quantity it is sized in. Quantities are rounded down by
+    /// `floor_quantity`, to the symbol's lot step, and the last child takes
+    /// the remainder, so they always sum to the parent.
+    pub fn child_orders(
+        &self,
+        parent: &OrderRequest,
+        floor_quantity: &dyn Fn(Decimal) -> Decimal,
+    ) -> Vec<OrderRequest> {
+        let (total, slice) = match parent.quote_order_qty {
+            Some(quote_qty) => (
+                quote_qty,
+                (quote_qty / Decimal::from(self.slices))
+                    .round_dp_with_strategy(QUOTE_PRECISION, RoundingStrategy::ToZero),
+            ),
+            None => (
+                parent.quantity,
+                floor_quantity(parent.quantity / Decimal::from(self.slices)),
+            ),
+        };
+
+        // Too small to split at this precision
+        let sizes = if slice <= dec!(0) {
+            vec![total]
+        } else {
+            let mut sizes = vec![slice; self.slices as usize - 1];
+            sizes.push(total - slice * Decimal::from(self.slices - 1));
+            sizes
+        };
+
+        sizes
+            .into_iter()
+            .enumerate()
+            .map(|(i, size)| {
+                let child = match parent.quote_order_qty {
+                    Some(_) => {
+                        OrderRequest::market_quote(&parent.symbol, parent.side, size.normalize())
+                    }
+                    None => OrderRequest::market(&parent.symbol, parent.side, size.normalize()),
+                };
+
+                // Each child is its own order, so it needs its own id
+                match &parent.client_order_id {
+                    Some(id) => child.with_client_order_id(name_uuid(&format!("{}/{}", id, i))),
+                    None => child,
+                }
+            })
+            .collect()
+    }
+
+    /// Places the child orders of `parent` one interval apart, checking each
+    /// with the risk manager first. Stops early on a risk rejection or a
+    /// failed order and reports what filled up to that point. Returns only
+    /// once the last child is placed, up to the whole `duration` later.
+    pub async fn execute(
+        &self,
+        client: &dyn Exchange,
+        risk_manager: &RiskManager,
+        parent: &OrderRequest,
+        floor_quantity: &(dyn Fn(Decimal) -> Decimal + Sync),
+        quote_balance: &Balance,
+        current_price: Decimal,
+    ) -> TwapFill {
+        let children = self.child_orders(parent, floor_quantity);
+        let interval = self.duration / children.len() as u32;
+
+        info!(
+            "TWAP {} {}: {} child orders over {:?}",
+            parent.side,
+            parent.symbol,
+            children.len(),
+            self.duration
+        );
+
+        let mut fill = TwapFill {
+            children_filled: 0,
+            quantity: dec!(0),
+            quote_qty: dec!(0),
+        };
+
+        for (i, child) in children.iter().enumerate() {
+            if i > 0 {
+                tokio::time::sleep(interval).await;
+            }
+
+            if let Err(e) = risk_manager.validate_order(child, quote_balance, current_price) {
+                warn!(
+                    "TWAP {} stopped after {} of {} children: {}",
+                    parent.symbol,
+                    i,
+                    children.len(),
+                    e
+                );
+                break;
+            }
+
+            match client.place_order(child).await {
+                Ok(response) => {
+                    let quantity: Decimal = response.executed_qty.parse().unwrap_or(dec!(0));
+                    let quote_qty: Decimal =
+                        response.cummulative_quote_qty.parse().unwrap_or(dec!(0));
+
+                    info!(
+                        "TWAP {} child {}/{}: order {} filled {}",
+                        parent.symbol,
+                        i + 1,
+                        children.len(),
+                        response.order_id,
+                        quantity
+                    );
+
+                    fill.children_filled += 1;
+                    fill.quantity += quantity;
+                    fill.quote_qty += quote_qty;
+                }
+                Err(e) => {
+                    warn!(
+                        "TWAP {} child {}/{} failed, stopping: {}",
+                        parent.symbol,
+                        i + 1,
+                        children.len(),
+                        e
+                    );
+                    break;
+                }
+            }
+        }
+
+        fill
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ClientConfig, Environment, ExchangeCredentials};
+    use crate::exchange::{BinanceClient, OrderSide, RetryPolicy};
+    use mockito::Matcher;
+
+    fn to_dp(dp: u32) -> impl Fn(Decimal) -> Decimal {
+        move |quantity| quantity.round_dp_with_strategy(dp, RoundingStrategy::ToZero)
+    }
+
+    fn usdt(free: &str) -> Balance {
+        Balance {
+            asset: "USDT".to_string(),
+            free: free.to_string(),
+            locked: "0".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_splits_quantity_into_children_summing_to_total() {
+        let twap = TwapExecutor::new(3, Duration::from_secs(60));
+        let parent = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(1.0));
+
+        let children = twap.child_orders(&parent, &to_dp(5));
+
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[0].quantity, dec!(0.33333));
+        assert_eq!(children[1].quantity, dec!(0.33333));
+        assert_eq!(children[2].quantity, dec!(0.33334));
+        assert_eq!(
+            children.iter().map(|c| c.quantity).sum::<Decimal>(),
+            dec!(1.0)
+        );
+        assert!(children.iter().all(|c| c.side == OrderSide::Buy));
+    }
+
+    #[test]
+    fn test_children_are_floored_to_the_lot_step() {
+        let twap = TwapExecutor::new(3, Duration::from_secs(60));
+        let parent = OrderRequest::market("DOGEUSDT", OrderSide::Buy, dec!(1000));
+
+        // DOGE trades in whole coins
+        let children = twap.child_orders(&parent, &|quantity: Decimal| quantity.floor());
+
+        let quantities: Vec<Decimal> = children.iter().map(|c| c.quantity).collect();
+        assert_eq!(quantities, vec![dec!(333), dec!(333), dec!(334)]);
+    }
+
+    #[test]
+    fn test_splits_quote_quantity() {
+        let twap = TwapExecutor::new(4, Duration::from_secs(60));
+        let parent = OrderRequest::market_quote("BTCUSDT", OrderSide::Buy, dec!(1000));
+
+        let children = twap.child_orders(&parent, &to_dp(5));
+
+        assert_eq!(children.len(), 4);
+        assert!(children
+            .iter()
+            .all(|c| c.quote_order_qty == Some(dec!(250))));
+    }
+
+    #[test]
+    fn test_children_get_distinct_ids_derived_from_parent() {
+        let twap = TwapExecutor::new(3, Duration::from_secs(60));
+        let parent = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(1.0))
+            .with_client_order_id("parent");
+
+        let ids: Vec<String> = twap
+            .child_orders(&parent, &to_dp(5))
+            .into_iter()
+            .map(|c| c.client_order_id.unwrap())
+            .collect();
+
+        assert_eq!(ids.len(), 3);
+        assert!(ids[0] != ids[1] && ids[1] != ids[2] && ids[0] != ids[2]);
+        assert_eq!(ids[0], name_uuid("parent/0"));
+
+        let unnamed = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(1.0));
+        assert!(twap
+            .child_orders(&unnamed, &to_dp(5))
+            .iter()
+            .all(|c| c.client_order_id.is_none()));
+    }
+
+    #[test]
+    fn test_tiny_order_is_not_split() {
+        let twap = TwapExecutor::new(5, Duration::from_secs(60));
+        let parent = OrderRequest::market("BTCUSDT", OrderSide::Sell, dec!(0.00003));
+
+        let children = twap.child_orders(&parent, &to_dp(5));
+
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].quantity, dec!(0.00003));
+    }
+
+    #[tokio::test]
+    async fn test_execute_places_each_child_and_stops_on_risk_rejection() {
+        let mut server = mockito::Server::new_async().await;
+        let orders = server
+            .mock(
+                "POST",
+                Matcher::Regex(
+                    "^/api/v3/order\\?symbol=BTCUSDT&side=BUY&type=MARKET&quantity=0.25&"
+                        .to_string(),
+                ),
+            )
+            .with_body(
+                r#"{"symbol": "BTCUSDT", "orderId": 1, "clientOrderId": "c1", "transactTime": 0,
+                    "price": "0", "origQty": "0.25", "executedQty": "0.25",
+                    "cummulativeQuoteQty": "25", "status": "FILLED",
+                    "timeInForce": "GTC", "type": "MARKET", "side": "BUY"}"#,
+            )
+            .expect(4)
+            .create_async()
+            .await;
+
+        let client = BinanceClient::new(
+            ExchangeCredentials {
+                api_key: "key".to_string(),
+                secret_key: "secret".to_string(),
+                environment: Environment::Testnet,
+            },
+            &ClientConfig::default(),
+        )
+        .unwrap()
+        .with_base_url(&server.url())
+        .with_retry_policy(RetryPolicy::new(0, Duration::from_millis(1)));
+        let risk_manager = RiskManager::new(dec!(2), dec!(5), 3);
+        let twap = TwapExecutor::new(4, Duration::from_millis(4));
+        let parent = OrderRequest::market("BTCUSDT", OrderSide::Buy, dec!(1.0));
+
+        let fill = twap
+            .execute(
+                &client,
+                &risk_manager,
+                &parent,
+                &to_dp(5),
+                &usdt("10000"),
+                dec!(100),
+            )
+            .await;
+
+        orders.assert_async().await;
+        assert_eq!(
+            fill,
+            TwapFill {
+                children_filled: 4,
+                quantity: dec!(1.0),
+                quote_qty: dec!(100),
+            }
+        );
+
+        // Daily loss limit hit: no child may go out
+        risk_manager.record_trade_result(dec!(-5));
+        let fill = twap
+            .execute(
+                &client,
+                &risk_manager,
+                &parent,
+                &to_dp(5),
+                &usdt("10000"),
+                dec!(100),
+            )
+            .await;
+        assert_eq!(fill.children_filled, 0);
+    }
+}
@@ -0,0 +1,184 @@
+#![cfg(feature = "api")]
+
+use cryptobot::api::{self, ApiState};
+use cryptobot::config::{ApiConfig, ClientConfig, Environment, ExchangeCredentials};
+use cryptobot::exchange::{BinanceClient, OrderSide, RetryPolicy};
+use cryptobot::risk::{Position, RiskManager};
+use cryptobot::strategy::{MaCrossoverStrategy, Signal};
+use cryptobot::trading::{SignalRecord, TradingEngine};
+use mockito::Matcher;
+use rust_decimal_macros::dec;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+fn test_engine(server_url: &str) -> TradingEngine {
+    let credentials = ExchangeCredentials {
+        api_key: "key".to_string(),
+        secret_key: "secret".to_string(),
+        environment: Environment::Testnet,
+    };
+    let client = BinanceClient::new(credentials, &ClientConfig::default())
+        .unwrap()
+        .with_base_url(server_url)
+        .with_retry_policy(RetryPolicy::new(0, Duration::from_millis(1)));
+
+    TradingEngine::new(
+        Box::new(client),
+        RiskManager::new(dec!(2), dec!(5), 3),
+        Box::new(MaCrossoverStrategy::new(2, 4, dec!(0))),
+        vec!["BTCUSDT".to_string()],
+        true,
+    )
+}
+
+/// Serves the API for `engine` on an ephemeral port and returns its base URL.
+async fn start_api(engine: &TradingEngine) -> String {
+    let config = ApiConfig {
+        enabled: true,
+        bind_address: "127.0.0.1".to_string(),
+        port: 0,
+    };
+    let state = ApiState::new(engine.risk_manager(), engine.trading_enabled())
+        .with_signal_log(engine.signal_log());
+    let addr = api::serve(&config, state).await.unwrap();
+
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_status_reports_risk_state() {
+    let engine = test_engine("http://127.0.0.1:1");
+    let base = start_api(&engine).await;
+
+    engine.risk_manager().open_position(Position::new(
+        "BTCUSDT",
+        dec!(30000),
+        dec!(0.01),
+        OrderSide::Buy,
+        0,
+    ));
+    engine.risk_manager().record_trade_result(dec!(-1.5));
+
+    let status: Value = reqwest::get(format!("{}/status", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(status["paused"], false);
+    assert_eq!(status["can_trade"], true);
+    assert_eq!(status["open_positions"], 1);
+    assert_eq!(status["daily_loss_pct"], "1.5");
+    assert_eq!(status["performance"]["num_trades"], 1);
+    assert_eq!(status["performance"]["losses"], 1);
+    assert_eq!(status["performance"]["avg_loss"], "-1.5");
+    assert_eq!(status["performance"]["profit_factor"], "0");
+}
+
+#[tokio::test]
+async fn test_positions_lists_open_positions() {
+    let engine = test_engine("http://127.0.0.1:1");
+    let base = start_api(&engine).await;
+
+    engine.risk_manager().open_position(Position::new(
+        "ETHUSDT",
+        dec!(2000),
+        dec!(0.5),
+        OrderSide::Buy,
+        0,
+    ));
+
+    let positions: Value = reqwest::get(format!("{}/positions", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let positions = positions.as_array().unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0]["symbol"], "ETHUSDT");
+    assert_eq!(positions[0]["entry_price"], "2000");
+    assert_eq!(positions[0]["quantity"], "0.5");
+    assert_eq!(positions[0]["side"], "BUY");
+}
+
+#[tokio::test]
+async fn test_signals_lists_recent_signals() {
+    let engine = test_engine("http://127.0.0.1:1");
+    let base = start_api(&engine).await;
+
+    engine.signal_log().record(SignalRecord {
+        timestamp: "2024-01-01T00:00:00Z".parse().unwrap(),
+        symbol: "BTCUSDT".to_string(),
+        indicators: BTreeMap::from([("short_sma", dec!(20)), ("long_sma", dec!(15))]),
+        signal: Signal::Buy {
+            strength: dec!(0.5),
+        },
+    });
+
+    let signals: Value = reqwest::get(format!("{}/signals", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let signals = signals.as_array().unwrap();
+    assert_eq!(signals.len(), 1);
+    assert_eq!(signals[0]["symbol"], "BTCUSDT");
+    assert_eq!(signals[0]["indicators"]["short_sma"], "20");
+    assert_eq!(signals[0]["signal"]["Buy"]["strength"], 0.5);
+}
+
+#[tokio::test]
+async fn test_pause_and_resume_gate_trading_cycles() {
+    let mut server = mockito::Server::new_async().await;
+    let account = server
+        .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+        .with_status(500)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut engine = test_engine(&server.url());
+    let base = start_api(&engine).await;
+    let http = reqwest::Client::new();
+
+    let paused: Value = http
+        .post(format!("{}/pause", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(paused["paused"], true);
+
+    // Paused: the cycle returns before touching the exchange
+    engine.run_once().await.unwrap();
+
+    let status: Value = reqwest::get(format!("{}/status", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(status["paused"], true);
+
+    let resumed: Value = http
+        .post(format!("{}/resume", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(resumed["paused"], false);
+
+    // Resumed: the cycle fetches the account again
+    assert!(engine.run_once().await.is_err());
+    account.assert_async().await;
+}
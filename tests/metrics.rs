@@ -0,0 +1,111 @@
+use cryptobot::config::{ClientConfig, Environment, ExchangeCredentials, MetricsConfig};
+use cryptobot::exchange::BinanceClient;
+use cryptobot::risk::RiskManager;
+use cryptobot::strategy::MaCrossoverStrategy;
+use cryptobot::trading::TradingEngine;
+use mockito::Matcher;
+use rust_decimal_macros::dec;
+
+fn kline_json(i: u64, close: &str) -> String {
+    format!(
+        r#"[{},"{c}","{c}","{c}","{c}","100",{},"10000",100,"50","5000","0"]"#,
+        i * 3600000,
+        (i + 1) * 3600000,
+        c = close
+    )
+}
+
+#[tokio::test]
+async fn test_order_counter_increments_after_run_once() {
+    let mut server = mockito::Server::new_async().await;
+
+    let _account = server
+        .mock("GET", Matcher::Regex("^/api/v3/account".to_string()))
+        .with_body(
+            r#"{
+                "makerCommission": 10, "takerCommission": 10,
+                "buyerCommission": 0, "sellerCommission": 0,
+                "canTrade": true, "canWithdraw": false, "canDeposit": false,
+                "updateTime": 0, "accountType": "SPOT",
+                "balances": [{"asset": "USDT", "free": "10000", "locked": "0"}]
+            }"#,
+        )
+        .create_async()
+        .await;
+    let _exchange_info = server
+        .mock("GET", "/api/v3/exchangeInfo")
+        .with_body(
+            r#"{"timezone": "UTC", "serverTime": 0, "symbols": [{"symbol": "BTCUSDT",
+                "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                "baseAssetPrecision": 8, "quotePrecision": 8}]}"#,
+        )
+        .create_async()
+        .await;
+    let _ticker = server
+        .mock("GET", Matcher::Regex("^/api/v3/ticker/price".to_string()))
+        .with_body(r#"{"symbol": "BTCUSDT", "price": "25"}"#)
+        .create_async()
+        .await;
+
+    // Short SMA crosses above the long SMA on the last candle
+    let closes = ["20", "20", "10", "10", "15", "25"];
+    let klines: Vec<String> = closes
+        .iter()
+        .enumerate()
+        .map(|(i, c)| kline_json(i as u64, c))
+        .collect();
+    let _klines = server
+        .mock("GET", Matcher::Regex("^/api/v3/klines".to_string()))
+        .with_body(format!("[{}]", klines.join(",")))
+        .create_async()
+        .await;
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    cryptobot::metrics::install(&MetricsConfig {
+        enabled: true,
+        bind_address: "127.0.0.1".to_string(),
+        port,
+    })
+    .unwrap();
+
+    let credentials = ExchangeCredentials {
+        api_key: "key".to_string(),
+        secret_key: "secret".to_string(),
+        environment: Environment::Testnet,
+    };
+    let client = BinanceClient::new(credentials, &ClientConfig::default())
+        .unwrap()
+        .with_base_url(&server.url());
+
+    let mut engine = TradingEngine::new(
+        Box::new(client),
+        RiskManager::new(dec!(2), dec!(5), 3),
+        Box::new(MaCrossoverStrategy::new(2, 4, dec!(0))),
+        vec!["BTCUSDT".to_string()],
+        true,
+    );
+
+    engine.run_once().await.unwrap();
+
+    let body = reqwest::get(format!("http://127.0.0.1:{}/metrics", port))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    let placed = body
+        .lines()
+        .find(|l| l.starts_with("cryptobot_orders_placed_total{") && l.contains("side=\"BUY\""))
+        .unwrap_or_else(|| panic!("order counter missing from scrape:\n{}", body));
+    assert!(
+        placed.ends_with(" 1"),
+        "unexpected counter line: {}",
+        placed
+    );
+    assert!(body.contains("cryptobot_analyze_duration_seconds"));
+}